@@ -371,6 +371,7 @@ export default function Home() {
                     <option value="ab_messaging">Messaging Strategy A/B</option>
                     <option value="creative_test">Creative Asset Evaluation</option>
                     <option value="cx_flow">Customer Journey Simulation</option>
+                    <option value="price_sensitivity">Price Sensitivity (Van Westendorp)</option>
                   </select>
                   <ChevronDown className="absolute right-3 top-1/2 -translate-y-1/2 w-4 h-4 text-slate-400 pointer-events-none group-hover:text-slate-600 transition-colors" />
                 </div>
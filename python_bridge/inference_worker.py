@@ -3,6 +3,7 @@
 # UPDATED: Added Global GPU Lock to prevent Metal/MPS Crashes on M-Series Chips
 
 import sys
+import argparse
 import json
 import base64
 import io
@@ -16,13 +17,14 @@ import datetime
 import uvicorn
 import threading 
 from fastapi import FastAPI, HTTPException
+from fastapi.responses import StreamingResponse
 from pydantic import BaseModel
 from typing import Optional, List, Any
 
 # MLX & Intelligence Modules
 from pypdf import PdfReader
 from sentence_transformers import SentenceTransformer
-from mlx_vlm import load, generate
+from mlx_vlm import load, generate, stream_generate
 from PIL import Image
 from duckduckgo_search import DDGS 
 
@@ -31,7 +33,7 @@ MODEL_PATH = "mlx-community/Phi-3.5-vision-instruct-4bit"
 EMBEDDING_MODEL = "all-MiniLM-L6-v2" 
 DB_PATH = "./knowledge_db" 
 USER_AGENT = 'OraculumMarketBot/1.0 (Student Project)'
-PORT = 8003 
+PORT = 8003  # Default; the Rust worker pool passes --port for workers 2..N
 
 # --- GLOBAL STATE ---
 app = FastAPI(title="Oraculum Neural Engine")
@@ -54,14 +56,28 @@ class GenerateRequest(BaseModel):
     temperature: float = 0.0
     image: Optional[str] = None
     pdf: Optional[str] = None
+    stream: bool = False  # NDJSON token stream, see _stream_generation
+
+class GenerateBatchRequest(BaseModel):
+    prompts: List[str]
+    max_tokens: int = 300
+    temperature: float = 0.0
 
 class ResearchRequest(BaseModel):
     product: str
     context: str
+    sources: List[str] = ["reddit", "wiki"]
 
 class QueryRequest(BaseModel):
     query: str
 
+class EmbedRequest(BaseModel):
+    text: str
+
+class OcrRequest(BaseModel):
+    image: str
+    max_tokens: int = 400
+
 # --- LIFECYCLE STARTUP ---
 @app.on_event("startup")
 async def startup_event():
@@ -100,46 +116,67 @@ def clean_query_step(query):
         if len(tokens) > 1: query = " ".join(tokens[:-1])
     return query
 
-def perform_federated_research(topic, audience_context):
+def perform_federated_research(topic, audience_context, sources=("reddit", "wiki")):
     voices = []
     current_q = topic
-    for _ in range(3): 
-        try:
-            url = f"https://www.reddit.com/search.json?q={urllib.parse.quote(current_q)}&sort=relevance&limit=8"
-            resp = requests.get(url, headers={'User-Agent': USER_AGENT}, timeout=5)
-            if resp.status_code == 200:
-                posts = resp.json().get('data', {}).get('children', [])
-                if posts:
-                    for p in posts:
-                        title = p['data']['title']
-                        if len(title) > 15: voices.append(title)
-                    break 
-        except: pass
-        new_q = clean_query_step(current_q)
-        if new_q == current_q: break
-        current_q = new_q
+    if "reddit" in sources:
+        for _ in range(3): 
+            try:
+                url = f"https://www.reddit.com/search.json?q={urllib.parse.quote(current_q)}&sort=relevance&limit=8"
+                resp = requests.get(url, headers={'User-Agent': USER_AGENT}, timeout=5)
+                if resp.status_code == 200:
+                    posts = resp.json().get('data', {}).get('children', [])
+                    if posts:
+                        for p in posts:
+                            title = p['data']['title']
+                            if len(title) > 15: voices.append(title)
+                        break 
+            except: pass
+            new_q = clean_query_step(current_q)
+            if new_q == current_q: break
+            current_q = new_q
+
+    if "wiki" in sources:
+        brand_guess = topic.split()[0]
+        wiki_summary = fetch_wiki_extract(brand_guess)[:300]
+        if wiki_summary:
+            voices.append(f"[Context] Brand Background: {wiki_summary}...")
+
+    if not voices:
+        return ["SYSTEM_ALERT: No digital footprint found. The product might be too new or niche."]
+    
+    return list(set(voices))[:15]
 
-    wiki_summary = ""
-    brand_guess = topic.split()[0]
+def fetch_wiki_extract(title):
     try:
         url = "https://en.wikipedia.org/w/api.php"
         params = {
-            "action": "query", "format": "json", "prop": "extracts",
-            "exintro": True, "explaintext": True, "titles": brand_guess
+            "action": "query", "format": "json", "prop": "extracts", "redirects": 1,
+            "exintro": True, "explaintext": True, "titles": title
         }
         resp = requests.get(url, params=params, headers={'User-Agent': USER_AGENT}, timeout=4)
         data = resp.json().get("query", {}).get("pages", {})
         for _, page in data.items():
-            if "extract" in page:
-                wiki_summary = page["extract"][:300]
-                voices.append(f"[Context] Brand Background: {wiki_summary}...")
-                break
+            if page.get("extract"):
+                return page["extract"]
     except: pass
+    return ""
 
-    if not voices:
-        return ["SYSTEM_ALERT: No digital footprint found. The product might be too new or niche."]
-    
-    return list(set(voices))[:15]
+def perform_wiki_lookup(query):
+    # Same query relaxation as the fact check, then the brand alone ("Maggi Masala 70g" -> "Maggi")
+    current_q = query
+    for _ in range(3):
+        summary = fetch_wiki_extract(current_q)
+        if summary:
+            return summary[:800]
+        new_q = clean_query_step(current_q)
+        if new_q == current_q: break
+        current_q = new_q
+
+    brand_guess = query.split()[0] if query.split() else ""
+    if brand_guess and brand_guess != current_q:
+        return fetch_wiki_extract(brand_guess)[:800]
+    return ""
 
 def perform_fact_check(query):
     current_q = query
@@ -202,6 +239,26 @@ def _update_graph_memory(agent_name, response_text, topic):
 
 # --- API ENDPOINTS ---
 
+def _stream_generation(full_prompt, images, req, agent, topic):
+    # Line protocol read by AgentBrain::generate_streaming (one JSON object per line):
+    #   {"token": "..."} per chunk, then {"done": true, "text": "..."} or {"error": "..."}
+    try:
+        chunks = []
+        with gpu_lock:
+            for chunk in stream_generate(model, processor, full_prompt, images, max_tokens=req.max_tokens, temp=req.temperature):
+                chunks.append(chunk.text)
+                if "<|end|>" in chunk.text:
+                    break
+                yield json.dumps({"token": chunk.text}) + "\n"
+
+        final_text = "".join(chunks).split("<|end|>")[0].strip()
+        _update_graph_memory(agent, final_text, topic)
+        yield json.dumps({"done": True, "text": final_text}) + "\n"
+
+    except Exception as e:
+        print(f"Stream Error: {e}")
+        yield json.dumps({"error": str(e)}) + "\n"
+
 @app.get("/health")
 def health_check():
     return {"status": "ready"}
@@ -253,6 +310,12 @@ def generate_text(req: GenerateRequest):
                      full_prompt = full_prompt.replace("<|user|>", "<|user|>\n<|image_1|>", 1)
             except: pass
 
+        if req.stream:
+            return StreamingResponse(
+                _stream_generation(full_prompt, images, req, agent, topic),
+                media_type="application/x-ndjson",
+            )
+
         # CRITICAL: GPU INFERENCE MUST BE LOCKED
         # Only one thread can run 'generate' at a time on Metal
         with gpu_lock:
@@ -270,6 +333,35 @@ def generate_text(req: GenerateRequest):
         print(f"Generate Error: {e}")
         raise HTTPException(status_code=500, detail=str(e))
 
+@app.post("/generate_batch")
+def generate_batch(req: GenerateBatchRequest):
+    # Text-only batch: one HTTP round-trip and one GPU lock acquisition for the whole chunk
+    try:
+        texts = []
+        with gpu_lock:
+            for prompt in req.prompts:
+                agent = "Unknown"
+                topic = "General"
+                if "Name:" in prompt: agent = prompt.split("Name:")[1].split("\n")[0].strip()
+                if "Topic:" in prompt: topic = prompt.split("Topic:")[1].split("\n")[0].strip()
+
+                sharded_ctx = get_sharded_context(agent, topic)
+                if "<|user|>" in prompt:
+                    full_prompt = prompt.replace("<|user|>", f"<|user|>\n{sharded_ctx}\n", 1) + "<|end|>\n<|assistant|>"
+                else:
+                    full_prompt = f"<|user|>\n{sharded_ctx}\n{prompt}<|end|>\n<|assistant|>"
+
+                res = generate(model, processor, full_prompt, max_tokens=req.max_tokens, temp=req.temperature, verbose=False)
+                final_text = res.text.split("<|end|>")[0].strip()
+                _update_graph_memory(agent, final_text, topic)
+                texts.append(final_text)
+
+        return {"status": "success", "texts": texts}
+
+    except Exception as e:
+        print(f"Generate Batch Error: {e}")
+        raise HTTPException(status_code=500, detail=str(e))
+
 @app.post("/query_memory")
 def query_memory_endpoint(req: QueryRequest):
     results = []
@@ -314,9 +406,19 @@ def query_memory_endpoint(req: QueryRequest):
             
     return {"status": "success", "data": results}
 
+@app.post("/embed")
+def embed_endpoint(req: EmbedRequest):
+    # Same MiniLM model as the knowledge bank, so vectors are comparable
+    try:
+        with gpu_lock:
+            vec = embed_model.encode([req.text])[0]
+        return {"status": "success", "embedding": [float(x) for x in vec]}
+    except Exception as e:
+        return {"status": "error", "embedding": [], "message": str(e)}
+
 @app.post("/research")
 def research_endpoint(req: ResearchRequest):
-    voices = perform_federated_research(req.product, req.context)
+    voices = perform_federated_research(req.product, req.context, req.sources)
     return {"status": "success", "research_data": voices}
 
 @app.post("/get_facts")
@@ -324,5 +426,35 @@ def facts_endpoint(req: QueryRequest):
     fact = perform_fact_check(req.query)
     return {"status": "success", "fact_sheet": fact}
 
+@app.post("/get_wiki")
+def wiki_endpoint(req: QueryRequest):
+    summary = perform_wiki_lookup(req.query)
+    return {"status": "success", "summary": summary}
+
+# The vision model doubles as OCR: it transcribes the pack instead of describing it
+OCR_PROMPT = (
+    "<|user|>\n<|image_1|>\n"
+    "Transcribe the text printed on this product packaging: brand, claims, nutrition figures, "
+    "certifications and badges. One item per line, exactly as written. No commentary. "
+    "If there is no readable text, reply NONE.<|end|>\n<|assistant|>"
+)
+
+@app.post("/ocr")
+def ocr_endpoint(req: OcrRequest):
+    try:
+        img = Image.open(io.BytesIO(base64.b64decode(req.image))).convert("RGB")
+        with gpu_lock:
+            res = generate(model, processor, OCR_PROMPT, [img], max_tokens=req.max_tokens, temp=0.0, verbose=False)
+        text = res.text.split("<|end|>")[0].strip()
+        if text.upper() == "NONE":
+            text = ""
+        return {"status": "success", "text": text}
+    except Exception as e:
+        return {"status": "error", "text": "", "message": str(e)}
+
 if __name__ == "__main__":
+    parser = argparse.ArgumentParser(description="Oraculum Neural Engine worker")
+    parser.add_argument("--port", type=int, default=PORT)
+    args = parser.parse_args()
+    PORT = args.port
     uvicorn.run(app, host="127.0.0.1", port=PORT)
\ No newline at end of file
@@ -1,10 +1,12 @@
 // src/agent_swarm.rs
 // Agent Swarm Engine - Headless Marketing Intelligence
 
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use crate::memory::MemoryStream;
+use crate::parsing::PurchaseIntent;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Agent {
@@ -29,12 +31,16 @@ pub struct Agent {
     pub simulated_responses: u32,
     pub avg_sentiment: f32,
 
+    // Experiment arm ("control" / "treatment") when the job runs a control cohort, see make_control
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cohort: Option<String>,
+
     // The Memory Stream (The "Mind")
     #[serde(skip)] 
     pub memory: Arc<Mutex<MemoryStream>>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct SimulationResult {
     pub agent_id: u32,
     // Explicit Name field for Blackboard Architecture
@@ -45,6 +51,9 @@ pub struct SimulationResult {
     pub timestamp: String,
     pub prompt: String,
     pub response: String,
+    // The untruncated answer, set only when a ResponseLimit shortened `response`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_full: Option<String>,
     
     // Stores the hidden [Thinking] block
     pub thought_process: Option<String>, 
@@ -53,13 +62,187 @@ pub struct SimulationResult {
     // Stores the "Acquired Knowledge" (e.g., "Found 5 Reddit posts...")
     // This enables the "Glass Box" UI where users see the evidence.
     pub sources: Option<String>,
+    // Machine-readable provenance: one entry per skill that returned data
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub source_refs: Vec<SourceRef>,
     // -------------------------------
     
     pub sentiment: String,
+    // Continuous polarity (-1.0..=1.0) for charting; see AgentSwarm::sentiment_score
+    #[serde(default)]
+    pub sentiment_score: f32,
     // Changed to Option to support flexible categories
     pub category: Option<String>,
+    // Normalized Yes/No/Maybe parsed from the verdict (product launch only); see VerdictDistribution
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub intent: Option<PurchaseIntent>,
+    // Focus groups: agent_id of the speaker this message rebuts (conversation threading)
+    #[serde(default)]
+    pub reply_to: Option<u32>,
+    // Product this response is about (product-line jobs run several through one panel)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub product: Option<String>,
+    // Inference kept failing after every retry; `response` holds the last error text
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub failed: bool,
+    // Copied from Agent::cohort: "control" for blank-slate baseline agents, else "treatment"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cohort: Option<String>,
+    // Wall-clock telemetry for this turn (JSON only; not exported to CSV)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timing: Option<Timing>,
+}
+
+/// Where one agent's turn spent its time, in milliseconds.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Timing {
+    pub total_ms: u64,     // Prompt + skills + inference (+ retries)
+    pub inference_ms: u64, // Brain calls only; batch engine: the agent's chunk round-trip
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub skills_ms: BTreeMap<String, u64>, // Skill id -> run time (skills run concurrently)
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct LatencyStats {
+    pub count: usize,
+    pub total_ms: u64,
+    pub avg_ms: u64,
+    pub p95_ms: u64,
+}
+
+impl LatencyStats {
+    fn from_samples(mut samples: Vec<u64>) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        samples.sort_unstable();
+        let total_ms: u64 = samples.iter().sum();
+        let p95_idx = ((samples.len() as f32 * 0.95).ceil() as usize).clamp(1, samples.len()) - 1;
+        Self {
+            count: samples.len(),
+            total_ms,
+            avg_ms: total_ms / samples.len() as u64,
+            p95_ms: samples[p95_idx],
+        }
+    }
+}
+
+/// Job-level roll-up of every `SimulationResult::timing` (JobStatus `timing`).
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct TimingSummary {
+    pub turns: LatencyStats,     // Whole agent turns
+    pub inference: LatencyStats,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub skills: BTreeMap<String, LatencyStats>,
+}
+
+impl TimingSummary {
+    /// None when no result carries timing (focus groups, older jobs).
+    pub fn from_results(results: &[SimulationResult]) -> Option<Self> {
+        let timings: Vec<&Timing> = results.iter().filter_map(|r| r.timing.as_ref()).collect();
+        if timings.is_empty() {
+            return None;
+        }
+        let mut skills: BTreeMap<String, Vec<u64>> = BTreeMap::new();
+        for (skill_id, ms) in timings.iter().flat_map(|t| &t.skills_ms) {
+            skills.entry(skill_id.clone()).or_default().push(*ms);
+        }
+        Some(Self {
+            turns: LatencyStats::from_samples(timings.iter().map(|t| t.total_ms).collect()),
+            inference: LatencyStats::from_samples(timings.iter().map(|t| t.inference_ms).collect()),
+            skills: skills.into_iter().map(|(id, samples)| (id, LatencyStats::from_samples(samples))).collect(),
+        })
+    }
+}
+
+/// Yes/No/Maybe tally of `SimulationResult::intent` (JobStatus `verdicts`). Percentages are of
+/// every answered product-launch response, so verdicts that took no position count as `unclear`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct VerdictDistribution {
+    pub yes: usize,
+    pub no: usize,
+    pub maybe: usize,
+    pub unclear: usize,
+    pub yes_pct: f32,
+    pub no_pct: f32,
+    pub maybe_pct: f32,
+    pub unclear_pct: f32,
+    // Product lines: the same tally per product (empty for single-product jobs)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub by_product: BTreeMap<String, VerdictDistribution>,
+}
+
+impl VerdictDistribution {
+    /// None unless some non-failed product-launch response is present.
+    pub fn from_results<'a>(results: impl IntoIterator<Item = &'a SimulationResult>) -> Option<Self> {
+        let answered: Vec<&SimulationResult> = results
+            .into_iter()
+            .filter(|r| r.scenario == "product_launch" && !r.failed)
+            .collect();
+        if answered.is_empty() {
+            return None;
+        }
+        let mut distribution = Self::tally(&answered);
+
+        let mut products: BTreeMap<String, Vec<&SimulationResult>> = BTreeMap::new();
+        for result in &answered {
+            if let Some(product) = &result.product {
+                products.entry(product.clone()).or_default().push(result);
+            }
+        }
+        if products.len() > 1 {
+            distribution.by_product = products.into_iter().map(|(product, group)| (product, Self::tally(&group))).collect();
+        }
+        Some(distribution)
+    }
+
+    fn tally(results: &[&SimulationResult]) -> Self {
+        let count = |intent: PurchaseIntent| results.iter().filter(|r| r.intent == Some(intent)).count();
+        let (yes, no, maybe) = (count(PurchaseIntent::Yes), count(PurchaseIntent::No), count(PurchaseIntent::Maybe));
+        let unclear = results.len() - yes - no - maybe;
+        let pct = |n: usize| n as f32 / results.len().max(1) as f32 * 100.0;
+        Self {
+            yes,
+            no,
+            maybe,
+            unclear,
+            yes_pct: pct(yes),
+            no_pct: pct(no),
+            maybe_pct: pct(maybe),
+            unclear_pct: pct(unclear),
+            by_product: BTreeMap::new(),
+        }
+    }
+
+    /// One line (plus one per product), e.g. "Yes 45.0% (18) | No 30.0% (12) | Maybe 20.0% (8) | Unclear 5.0% (2)".
+    pub fn to_markdown(&self) -> String {
+        let line = |d: &VerdictDistribution| {
+            format!(
+                "Yes {:.1}% ({}) | No {:.1}% ({}) | Maybe {:.1}% ({}) | Unclear {:.1}% ({})",
+                d.yes_pct, d.yes, d.no_pct, d.no, d.maybe_pct, d.maybe, d.unclear_pct, d.unclear
+            )
+        };
+        let mut lines = vec![format!("- Overall: {}", line(self))];
+        lines.extend(self.by_product.iter().map(|(product, d)| format!("- {}: {}", product, line(d))));
+        lines.join("\n")
+    }
+}
+
+/// One skill observation an agent answered with (see `SimulationResult::source_refs`).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SourceRef {
+    pub skill_id: String,
+    pub source: String,  // SkillMetadata::source, e.g. "HybridMemory"
+    pub snippet: String, // Start of the skill output (SOURCE_SNIPPET_CHARS)
 }
 
+// Length of SourceRef::snippet, in chars
+pub const SOURCE_SNIPPET_CHARS: usize = 200;
+
+// Agent::cohort / SimulationResult::cohort values
+pub const CONTROL_COHORT: &str = "control";
+pub const TREATMENT_COHORT: &str = "treatment";
+
 impl Agent {
     // This static constructor is a FALLBACK only. 
     // In the active simulation, agents are created dynamically by 'PersonaGenerator'
@@ -163,10 +346,52 @@ impl Agent {
 
             simulated_responses: 0,
             avg_sentiment: 0.5,
+            cohort: None,
             memory: Arc::new(Mutex::new(MemoryStream::new())), 
         }
     }
 
+    /// Turns this persona into a blank-slate control agent: name kept, everything that steers
+    /// the answer (demographic detail, beliefs, affinities, voice, skills, memories) dropped.
+    pub fn make_control(&mut self) {
+        self.role = "Consumer".to_string();
+        self.demographic = "Adult consumer".to_string();
+        self.beliefs.clear();
+        self.spending_profile = "Moderate".to_string();
+        self.product_affinity.clear();
+        self.messaging_resonance.clear();
+        self.speaking_style = "Neutral".to_string();
+        self.skepticism_level = "Medium".to_string();
+        self.skills.clear();
+        self.cohort = Some(CONTROL_COHORT.to_string());
+        self.memory = Arc::new(Mutex::new(MemoryStream::new()));
+    }
+
+    pub fn is_control(&self) -> bool {
+        self.cohort.as_deref() == Some(CONTROL_COHORT)
+    }
+
+    /// Prompt line with this persona's product affinities and the messaging that lands with them.
+    /// None when there's nothing specific to say (generated personas default to "General").
+    pub fn affinity_hint(&self) -> Option<String> {
+        let specific = |items: &[String]| -> Vec<String> {
+            items.iter()
+                .filter(|s| !s.trim().is_empty() && !s.to_lowercase().starts_with("general"))
+                .cloned()
+                .collect()
+        };
+        let affinity = specific(&self.product_affinity);
+        if affinity.is_empty() {
+            return None;
+        }
+        let mut hint = format!("PREFERENCES: You naturally gravitate towards {}.", affinity.join(", "));
+        let resonance = specific(&self.messaging_resonance);
+        if !resonance.is_empty() {
+            hint.push_str(&format!(" Messaging about {} wins you over.", resonance.join(", ")));
+        }
+        Some(hint)
+    }
+
     pub fn update_sentiment(&mut self, sentiment_score: f32, response_count: u32) {
         self.simulated_responses = response_count;
         self.avg_sentiment = sentiment_score;
@@ -178,14 +403,165 @@ impl Agent {
     }
 }
 
+// Built-in polarity lexicon (word -> weight). Hedges like "maybe" carry no weight and land in "neutral".
+const SENTIMENT_LEXICON: &[(&str, f32)] = &[
+    // Positive
+    ("love", 2.0), ("loved", 2.0), ("amazing", 2.0), ("perfect", 2.0), ("excellent", 2.0),
+    ("awesome", 2.0), ("fantastic", 2.0), ("great", 1.5), ("best", 1.5), ("recommend", 1.5),
+    ("delicious", 1.5), ("definitely", 1.0), ("good", 1.0), ("nice", 1.0), ("happy", 1.0),
+    ("worth", 1.0), ("tasty", 1.0), ("convenient", 1.0), ("trust", 1.0), ("affordable", 0.5),
+    // Negative
+    ("hate", -2.0), ("terrible", -2.0), ("awful", -2.0), ("worst", -2.0), ("useless", -2.0),
+    ("scam", -2.0), ("bad", -1.5), ("dislike", -1.5), ("poor", -1.5), ("overpriced", -1.5),
+    ("fake", -1.5), ("disappointing", -1.5), ("disappointed", -1.5), ("waste", -1.5),
+    ("gimmick", -1.5), ("expensive", -1.0), ("boring", -1.0), ("bland", -1.0), ("doubt", -1.0),
+];
+
+// Starter lexicon for Hindi/Hinglish responses. Multi-word phrases match as consecutive words.
+const HINGLISH_LEXICON: &[(&str, f32)] = &[
+    ("mast", 1.5), ("badhiya", 1.5), ("zabardast", 2.0), ("jhakaas", 2.0), ("kamaal", 1.5),
+    ("accha", 1.0), ("acha", 1.0), ("sahi", 1.0), ("bindaas", 1.0), ("paisa vasool", 2.0),
+    ("bakwaas", -2.0), ("bakwas", -2.0), ("ghatiya", -2.0), ("bekaar", -1.5), ("bekar", -1.5),
+    ("faltu", -1.5), ("bura", -1.5), ("mehenga", -1.0), ("mehnga", -1.0), ("paisa barbaad", -2.0),
+];
+
+// Token-set Jaccard similarity at which two responses count as near-duplicates (see duplicate_ratio)
+pub const DUPLICATE_SIMILARITY: f32 = 0.8;
+
+// Tokens that flip the polarity of the next token (any "...n't" contraction counts too)
+const NEGATORS: &[&str] = &["not", "no", "never", "nothing", "hardly", "nahi", "nahin"];
+
+// Weighted lexicon scoring with basic negation handling:
+// "not great" reads as negative, "I don't hate it" reads as positive.
+// Matching is case-insensitive and token-based, so "mast" never matches inside "mastermind".
+#[derive(Clone, Debug, Default)]
+pub struct SentimentLexicon {
+    // User-supplied entries (lowercased, tokenized like responses). Checked before the built-in English lexicon.
+    extra: HashMap<String, f32>,
+    // Word count of the longest user phrase (how far ahead score_tokens looks)
+    longest_phrase: usize,
+    // Skip the built-in English lexicon (non-English runs: only user entries count)
+    user_only: bool,
+}
+
+impl SentimentLexicon {
+    pub fn with_entries(mut self, entries: HashMap<String, f32>) -> Self {
+        for (word, weight) in entries {
+            let lower = word.to_lowercase();
+            let words = Self::tokenize(&lower);
+            if !words.is_empty() {
+                self.longest_phrase = self.longest_phrase.max(words.len());
+                self.extra.insert(words.join(" "), weight);
+            }
+        }
+        self
+    }
+
+    /// Scores with the user-supplied entries only, e.g. for Tamil responses where English
+    /// keywords would only pick up stray loanwords.
+    pub fn user_only(mut self) -> Self {
+        self.user_only = true;
+        self
+    }
+
+    /// The shipped Hinglish starter lexicon ("mast", "bakwaas", "paisa vasool", ...).
+    pub fn hinglish() -> HashMap<String, f32> {
+        HINGLISH_LEXICON.iter().map(|(w, v)| (w.to_string(), *v)).collect()
+    }
+
+    pub fn classify(&self, response: &str) -> String {
+        let (score, positive_hits, negative_hits) = self.score_tokens(response);
+        let normalized = Self::normalize_score(score);
+
+        if positive_hits > 0 && negative_hits > 0 && normalized.abs() < 0.3 {
+            "mixed".to_string()
+        } else if normalized >= 0.2 {
+            "positive".to_string()
+        } else if normalized <= -0.2 {
+            "negative".to_string()
+        } else {
+            "neutral".to_string()
+        }
+    }
+
+    pub fn score(&self, response: &str) -> f32 {
+        let (score, _, _) = self.score_tokens(response);
+        Self::normalize_score(score)
+    }
+
+    fn normalize_score(score: f32) -> f32 {
+        (score / 2.0).tanh()
+    }
+
+    fn lookup(&self, token: &str) -> Option<f32> {
+        self.extra.get(token).copied().or_else(|| {
+            if self.user_only {
+                return None;
+            }
+            SENTIMENT_LEXICON.iter().find(|(word, _)| *word == token).map(|(_, w)| *w)
+        })
+    }
+
+    // Lowercase text -> words; apostrophes stay inside words ("don't")
+    fn tokenize(lower: &str) -> Vec<&str> {
+        lower
+            .split(|c: char| !(c.is_alphanumeric() || c == '\''))
+            .filter(|t| !t.is_empty())
+            .collect()
+    }
+
+    // Returns (summed polarity, positive hits, negative hits)
+    fn score_tokens(&self, response: &str) -> (f32, usize, usize) {
+        let lower = response.to_lowercase();
+        let tokens = Self::tokenize(&lower);
+
+        let mut score = 0.0;
+        let mut positive_hits = 0;
+        let mut negative_hits = 0;
+        let mut negate_next = false;
+        let mut i = 0;
+
+        while i < tokens.len() {
+            let token = tokens[i];
+
+            // Longest phrase first ("paisa vasool", "not worth the money"), so phrases that
+            // start with a negator keep their own weight
+            let phrase = (2..=self.longest_phrase.min(tokens.len() - i))
+                .rev()
+                .find_map(|width| self.extra.get(&tokens[i..i + width].join(" ")).map(|w| (*w, width)));
+            if phrase.is_none() && (NEGATORS.contains(&token) || token.ends_with("n't")) {
+                negate_next = true;
+                i += 1;
+                continue;
+            }
+            let (weight, width) = match phrase {
+                Some((w, width)) => (Some(w), width),
+                None => (self.lookup(token), 1),
+            };
+
+            if let Some(weight) = weight {
+                let weight = if negate_next { -weight } else { weight };
+                if weight > 0.0 { positive_hits += 1 } else { negative_hits += 1 }
+                score += weight;
+            }
+            // Negation only applies to the very next token
+            negate_next = false;
+            i += width;
+        }
+
+        (score, positive_hits, negative_hits)
+    }
+}
+
 pub struct AgentSwarm {
     pub agents: Arc<Mutex<Vec<Agent>>>,
     pub results: Arc<Mutex<Vec<SimulationResult>>>,
+    pub lexicon: SentimentLexicon,
 }
 
 impl AgentSwarm {
     pub fn new(agent_count: usize) -> Self {
-        let roles = vec!["Trader", "Guard", "Miner", "Hacker", "Drone"];
+        let roles = ["Trader", "Guard", "Miner", "Hacker", "Drone"];
         let mut agents = Vec::with_capacity(agent_count);
 
         for i in 0..agent_count {
@@ -196,6 +572,7 @@ impl AgentSwarm {
         Self {
             agents: Arc::new(Mutex::new(agents)),
             results: Arc::new(Mutex::new(Vec::new())),
+            lexicon: SentimentLexicon::default(),
         }
     }
 
@@ -212,6 +589,25 @@ impl AgentSwarm {
             .unwrap_or_default()
     }
 
+    /// Writes each agent's `simulated_responses` / `avg_sentiment` (mean `sentiment_score`,
+    /// -1.0..=1.0) from this swarm's results. Failed inferences don't count; agents without
+    /// a usable result keep their previous values.
+    pub fn refresh_agent_stats(&self) {
+        let results = self.get_results();
+        if let Ok(mut agents) = self.agents.lock() {
+            for agent in agents.iter_mut() {
+                let scores: Vec<f32> = results.iter()
+                    .filter(|r| r.agent_id == agent.id && !r.failed)
+                    .map(|r| r.sentiment_score)
+                    .collect();
+                if !scores.is_empty() {
+                    let avg = scores.iter().sum::<f32>() / scores.len() as f32;
+                    agent.update_sentiment(avg, scores.len() as u32);
+                }
+            }
+        }
+    }
+
     pub fn get_results(&self) -> Vec<SimulationResult> {
         self.results
             .lock()
@@ -224,71 +620,166 @@ impl AgentSwarm {
         now.format("%Y-%m-%dT%H:%M:%SZ").to_string()
     }
 
+    // --- SENTIMENT ENGINE ---
+    // Static helpers use the built-in English lexicon only.
+    // Swarms created `with_lexicon` should use `classify_sentiment` / `score_sentiment` instead.
     pub fn sentiment_from_response(response: &str) -> String {
-        let response_lower = response.to_lowercase();
-        if response_lower.contains("love") || response_lower.contains("great")
-            || response_lower.contains("amazing") || response_lower.contains("perfect")
-            || response_lower.contains("excellent") || response_lower.contains("definitely")
-        {
-            "positive".to_string()
-        } else if response_lower.contains("bad") || response_lower.contains("terrible")
-            || response_lower.contains("hate") || response_lower.contains("awful")
-            || response_lower.contains("dislike")
-        {
-            "negative".to_string()
-        } else if response_lower.contains("maybe") || response_lower.contains("could")
-            || response_lower.contains("depends") || response_lower.contains("interesting")
-        {
-            "neutral".to_string()
-        } else {
-            "mixed".to_string()
+        SentimentLexicon::default().classify(response)
+    }
+
+    /// Raw polarity of a response, squashed into -1.0 (very negative) ..= 1.0 (very positive).
+    pub fn sentiment_score(response: &str) -> f32 {
+        SentimentLexicon::default().score(response)
+    }
+
+    /// Adds user-supplied `word -> polarity` entries on top of the built-in lexicon
+    /// (e.g. `SentimentLexicon::hinglish()` for Indian audiences).
+    pub fn with_lexicon(mut self, entries: HashMap<String, f32>) -> Self {
+        self.lexicon = self.lexicon.with_entries(entries);
+        self
+    }
+
+    pub fn classify_sentiment(&self, response: &str) -> String {
+        self.lexicon.classify(response)
+    }
+
+    pub fn score_sentiment(&self, response: &str) -> f32 {
+        self.lexicon.score(response)
+    }
+
+    /// Deterministic agreement score (0-100) for a set of responses:
+    /// 50% = share of the most common sentiment label, 50% = 1 - std-dev of sentiment_score
+    /// (scores live in -1..=1, so the std-dev is at most 1). Unanimity -> 100, a 50/50 split
+    /// between strong positives and strong negatives -> 25. Fewer than two responses -> 100.
+    pub fn consensus_score(results: &[&SimulationResult]) -> f32 {
+        Self::weighted_consensus_score(results, |_| 1.0)
+    }
+
+    /// `consensus_score` where each response counts `weight(result)` times (label shares,
+    /// mean and variance are all weighted). Non-positive total weight -> 100.
+    pub fn weighted_consensus_score(results: &[&SimulationResult], weight: impl Fn(&SimulationResult) -> f32) -> f32 {
+        if results.len() < 2 {
+            return 100.0;
+        }
+        let weights: Vec<f32> = results.iter().map(|r| weight(r).max(0.0)).collect();
+        let n: f32 = weights.iter().sum();
+        if n <= 0.0 {
+            return 100.0;
         }
+
+        let mut label_weights: HashMap<&str, f32> = HashMap::new();
+        for (r, w) in results.iter().zip(&weights) {
+            *label_weights.entry(r.sentiment.as_str()).or_insert(0.0) += w;
+        }
+        let dominant_share = label_weights.values().cloned().fold(0.0, f32::max) / n;
+
+        let mean = results.iter().zip(&weights).map(|(r, w)| r.sentiment_score * w).sum::<f32>() / n;
+        let variance = results.iter().zip(&weights).map(|(r, w)| (r.sentiment_score - mean).powi(2) * w).sum::<f32>() / n;
+        let agreement = 1.0 - variance.sqrt().min(1.0);
+
+        ((dominant_share * 0.5 + agreement * 0.5) * 100.0).clamp(0.0, 100.0)
     }
 
-    pub fn extract_category(response: &str, scenario: &str) -> Option<String> {
-        let category = match scenario {
-            "product_launch" => {
-                if response.contains("buy") || response.contains("purchase") {
-                    "intent_to_buy"
-                } else if response.contains("healthy") || response.contains("quality") {
-                    "quality_focused"
-                } else if response.contains("price") || response.contains("cost") {
-                    "price_sensitive"
-                } else {
-                    "intrigued"
-                }
-            }
-            "creative_test" => {
-                if response.to_lowercase().contains("second") {
-                    "option_b_preference"
-                } else if response.to_lowercase().contains("first") {
-                    "option_a_preference"
-                } else {
-                    "unclear_preference"
-                }
-            }
-            "cx_flow" => {
-                if response.contains("buy") || response.contains("cart") {
-                    "converted"
-                } else if response.contains("consider") || response.contains("check") {
-                    "considering"
-                } else {
-                    "aware"
+    /// Share (0-1) of responses that nearly repeat at least one other response
+    /// (token-set Jaccard >= DUPLICATE_SIMILARITY). A high ratio means the model collapsed
+    /// onto one answer, so sentiment agreement says little about the audience.
+    pub fn duplicate_ratio(results: &[&SimulationResult]) -> f32 {
+        if results.len() < 2 {
+            return 0.0;
+        }
+
+        let token_sets: Vec<HashSet<String>> = results
+            .iter()
+            .map(|r| {
+                r.response
+                    .to_lowercase()
+                    .split(|c: char| !c.is_alphanumeric())
+                    .filter(|t| !t.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .collect();
+
+        let mut duplicated = vec![false; token_sets.len()];
+        for i in 0..token_sets.len() {
+            for j in (i + 1)..token_sets.len() {
+                let (a, b) = (&token_sets[i], &token_sets[j]);
+                let union = a.union(b).count();
+                if union == 0 {
+                    continue; // Two empty responses are failures, not duplicates
                 }
-            }
-            "ab_messaging" => {
-                if response.contains("affordable") || response.contains("value") {
-                    "value_resonance"
-                } else if response.contains("premium") || response.contains("indulgent") {
-                    "premium_resonance"
-                } else {
-                    "neutral_resonance"
+                if a.intersection(b).count() as f32 / union as f32 >= DUPLICATE_SIMILARITY {
+                    duplicated[i] = true;
+                    duplicated[j] = true;
                 }
             }
-            "persona_generation" => "persona_data",
-            _ => "general",
-        };
-        
+        }
+
+        duplicated.iter().filter(|d| **d).count() as f32 / results.len() as f32
+    }
+
+    /// Fallback buckets for scenarios without their own `Scenario::categorize` (custom templates):
+    /// a generic keyword table, first match wins. Persona generation output is always persona data.
+    pub fn extract_category(response: &str, scenario: &str) -> Option<String> {
+        const BUCKETS: [(&str, &[&str]); 3] = [
+            ("intent_to_buy", &["buy", "purchase", "order", "try it", "lunga", "lungi"]),
+            ("price_sensitive", &["price", "cost", "expensive", "cheap", "afford", "mehenga", "₹"]),
+            ("quality_focused", &["quality", "healthy", "taste", "ingredient", "fresh"]),
+        ];
+        if scenario == "persona_generation" {
+            return Some("persona_data".to_string());
+        }
+
+        let response = response.to_lowercase();
+        let category = BUCKETS
+            .iter()
+            .find(|(_, keywords)| keywords.iter().any(|k| response.contains(k)))
+            .map_or("general", |(bucket, _)| *bucket);
         Some(category.to_string())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negation_flips_the_next_word() {
+        assert_eq!(AgentSwarm::sentiment_from_response("Honestly, not great."), "negative");
+        assert_eq!(AgentSwarm::sentiment_from_response("I don't hate it"), "positive");
+        assert_eq!(AgentSwarm::sentiment_from_response("It is never boring"), "positive");
+        assert!(AgentSwarm::sentiment_score("not good at all") < 0.0);
+    }
+
+    #[test]
+    fn negation_does_not_reach_past_one_word() {
+        // "not" applies to "sure", which carries no weight; "love" keeps its polarity
+        assert_eq!(AgentSwarm::sentiment_from_response("Not sure yet, but I love the taste"), "positive");
+    }
+
+    #[test]
+    fn user_phrases_of_any_length_match() {
+        let entries = HashMap::from([("not worth the money".to_string(), -2.0), ("Paisa-Vasool".to_string(), 2.0)]);
+        let lexicon = SentimentLexicon::default().user_only().with_entries(entries);
+
+        assert!(lexicon.score("Honestly it's not worth the money.") < 0.0);
+        assert!(lexicon.score("Ekdum paisa vasool!") > 0.0);
+        assert_eq!(lexicon.score("worth the wait"), 0.0);
+    }
+
+    #[test]
+    fn opposing_words_read_as_mixed_and_hedges_as_neutral() {
+        assert_eq!(AgentSwarm::sentiment_from_response("Great taste but bad packaging"), "mixed");
+        assert_eq!(AgentSwarm::sentiment_from_response("Maybe, I would have to see"), "neutral");
+    }
+
+    #[test]
+    fn fallback_categories_read_the_response() {
+        let category = |response: &str| AgentSwarm::extract_category(response, "template");
+        assert_eq!(category("I'd BUY two packs").as_deref(), Some("intent_to_buy"));
+        assert_eq!(category("Too expensive at ₹120").as_deref(), Some("price_sensitive"));
+        assert_eq!(category("The taste is bland").as_deref(), Some("quality_focused"));
+        assert_eq!(category("Nice pack design").as_deref(), Some("general"));
+        assert_eq!(AgentSwarm::extract_category("[{\"name\": \"Asha\"}]", "persona_generation").as_deref(), Some("persona_data"));
+    }
+}
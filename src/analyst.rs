@@ -1,36 +1,699 @@
 // src/analyst.rs
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
-use crate::brain::AgentBrain;
-use crate::agent_swarm::SimulationResult;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::brain::{AgentBrain, BrainError};
+use crate::agent_swarm::{Agent, AgentSwarm, SimulationResult, VerdictDistribution, CONTROL_COHORT, TREATMENT_COHORT};
+use crate::scenarios::PricePoints;
+use crate::focus_group::FocusGroupSession;
+use crate::persona_generator::clean_json_block;
 
 pub struct AnalystEngine;
 
+// Low temperature keeps reports factual; overridable per request (AnalyzeRequest)
+pub const DEFAULT_ANALYSIS_TEMPERATURE: f32 = 0.4;
+
+/// Machine-readable analyst output (see `AnalystEngine::generate_report_structured`).
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct AnalysisReport {
+    pub executive_summary: String,
+    pub consensus_score: f32,
+    pub key_findings: Vec<String>,
+    pub recommendations: Vec<String>,
+    pub psychological_profile: String,
+    // Filled from the data after parsing; never taken from the model
+    pub metrics: VerifiedMetrics,
+}
+
+// Low temperature for the JSON repair round-trip: fix the format, don't rewrite the analysis
+const REPAIR_TEMPERATURE: f32 = 0.1;
+
+// (field, expected JSON type) the model must return; `metrics` is ours, never the model's
+const REPORT_SCHEMA: [(&str, &str); 5] = [
+    ("executive_summary", "string"),
+    ("consensus_score", "number"),
+    ("key_findings", "array of strings"),
+    ("recommendations", "array of strings"),
+    ("psychological_profile", "string"),
+];
+
+impl AnalysisReport {
+    /// Parses the model's reply and checks every REPORT_SCHEMA field (present, right type).
+    /// The error lists every problem, ready to be quoted back in a repair prompt.
+    pub fn from_model_json(raw: &str) -> Result<Self, String> {
+        let value: Value = serde_json::from_str(&clean_json_block(raw, '{', '}')).map_err(|e| format!("not valid JSON: {}", e))?;
+        let object = value.as_object().ok_or("top level is not a JSON object")?;
+
+        let problems: Vec<String> = REPORT_SCHEMA
+            .iter()
+            .filter_map(|(field, expected)| {
+                let ok = match (object.get(*field), *expected) {
+                    (None, _) => return Some(format!("`{}` is missing", field)),
+                    (Some(v), "string") => v.is_string(),
+                    (Some(v), "number") => v.is_number(),
+                    (Some(v), _) => v.as_array().is_some_and(|items| items.iter().all(Value::is_string)),
+                };
+                (!ok).then(|| format!("`{}` must be {}", field, expected))
+            })
+            .collect();
+        if !problems.is_empty() {
+            return Err(problems.join(", "));
+        }
+        serde_json::from_value(value).map_err(|e| e.to_string())
+    }
+
+    fn repair_prompt(previous: &str, error: &str) -> String {
+        format!(
+            "<|user|>Your previous JSON was invalid: {}.\n\
+            \n\
+            --- PREVIOUS OUTPUT ---\n{}\n--- END ---\n\
+            \n\
+            Fix it without changing the analysis. Return ONLY a JSON object with exactly these fields:\n\
+            {{\"executive_summary\": string, \"consensus_score\": number, \"key_findings\": [string], \
+            \"recommendations\": [string], \"psychological_profile\": string}}<|end|>\n<|assistant|>",
+            error,
+            previous.trim()
+        )
+    }
+
+    /// Best-effort report from a reply that failed validation: well-typed fields are kept, near
+    /// misses are coerced (a list where text was expected is joined, text where a list was
+    /// expected becomes one item per line) and the rest default. Returns the report plus a note
+    /// per field that was changed. None when the reply isn't a JSON object at all.
+    fn salvage(raw: &str) -> Option<(Self, Vec<String>)> {
+        let value: Value = serde_json::from_str(&clean_json_block(raw, '{', '}')).ok()?;
+        let object = value.as_object()?;
+        let mut fixes = Vec::new();
+
+        let mut text = |field: &str| -> String {
+            match object.get(field) {
+                Some(Value::String(s)) => s.clone(),
+                Some(Value::Array(items)) => {
+                    fixes.push(format!("{}: list joined", field));
+                    items.iter().map(|v| v.as_str().map(str::to_string).unwrap_or_else(|| v.to_string())).collect::<Vec<_>>().join(" ")
+                }
+                Some(other) => {
+                    fixes.push(format!("{}: {} converted to text", field, other));
+                    other.to_string()
+                }
+                None => {
+                    fixes.push(format!("{}: missing", field));
+                    String::new()
+                }
+            }
+        };
+        let executive_summary = text("executive_summary");
+        let psychological_profile = text("psychological_profile");
+
+        let mut list = |field: &str| -> Vec<String> {
+            match object.get(field) {
+                Some(Value::Array(items)) => {
+                    if !items.iter().all(Value::is_string) {
+                        fixes.push(format!("{}: non-text items converted", field));
+                    }
+                    items.iter().map(|v| v.as_str().map(str::to_string).unwrap_or_else(|| v.to_string())).collect()
+                }
+                Some(Value::String(s)) => {
+                    fixes.push(format!("{}: text split into items", field));
+                    s.lines().map(|l| l.trim().trim_start_matches(['-', '*', '•']).trim().to_string()).filter(|l| !l.is_empty()).collect()
+                }
+                Some(_) => {
+                    fixes.push(format!("{}: dropped (wrong type)", field));
+                    Vec::new()
+                }
+                None => {
+                    fixes.push(format!("{}: missing", field));
+                    Vec::new()
+                }
+            }
+        };
+        let key_findings = list("key_findings");
+        let recommendations = list("recommendations");
+
+        // consensus_score is overwritten with the computed one anyway
+        if !object.get("consensus_score").is_some_and(Value::is_number) {
+            fixes.push("consensus_score: computed value used".to_string());
+        }
+        Some((
+            Self { executive_summary, key_findings, recommendations, psychological_profile, ..Self::default() },
+            fixes,
+        ))
+    }
+}
+
+/// Hard numbers computed in Rust from the results (the model narrates around these).
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct VerifiedMetrics {
+    pub participants: usize,
+    pub sentiment_counts: BTreeMap<String, usize>,
+    pub category_counts: BTreeMap<String, usize>,
+    pub thoughts_captured: usize,
+    pub consensus_score: f32,
+    // Share of near-identical responses (see AgentSwarm::duplicate_ratio)
+    #[serde(default)]
+    pub duplicate_ratio: f32,
+    // Skeptic-weighted read, only when the request asked for it (AnalystLens::agent_weights)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weighted: Option<WeightedMetrics>,
+}
+
+/// Sentiment shares and consensus with each agent weighted by its `skepticism_level`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct WeightedMetrics {
+    pub sentiment_pct: BTreeMap<String, f32>,
+    pub consensus_score: f32,
+}
+
+impl WeightedMetrics {
+    pub fn from_results(results: &[&SimulationResult], agent_weights: &HashMap<u32, f32>) -> Self {
+        // Agents missing from the map (e.g. older jobs) count once
+        let weight = |r: &SimulationResult| agent_weights.get(&r.agent_id).copied().unwrap_or(1.0);
+        let total: f32 = results.iter().map(|r| weight(r)).sum();
+
+        let mut sentiment_pct = BTreeMap::new();
+        for r in results {
+            *sentiment_pct.entry(r.sentiment.clone()).or_insert(0.0) += weight(r);
+        }
+        for share in sentiment_pct.values_mut() {
+            *share = if total > 0.0 { *share / total * 100.0 } else { 0.0 };
+        }
+
+        Self {
+            sentiment_pct,
+            consensus_score: AgentSwarm::weighted_consensus_score(results, weight),
+        }
+    }
+}
+
+/// Per-agent weights by `skepticism_level` for the decision-grade read (see AnalyzeRequest).
+/// Skeptics' verdicts predict high-stakes purchases better than enthusiasts'.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct SkepticismWeights {
+    pub high: f32,
+    pub medium: f32,
+    pub low: f32,
+}
+
+impl Default for SkepticismWeights {
+    fn default() -> Self {
+        Self { high: 1.5, medium: 1.0, low: 0.7 }
+    }
+}
+
+impl SkepticismWeights {
+    /// Unknown levels weigh like "Medium".
+    pub fn weight(&self, skepticism_level: &str) -> f32 {
+        match skepticism_level.trim().to_lowercase().as_str() {
+            "high" => self.high,
+            "low" => self.low,
+            _ => self.medium,
+        }
+    }
+
+    pub fn agent_weights(&self, agents: &[Agent]) -> HashMap<u32, f32> {
+        agents.iter().map(|a| (a.id, self.weight(&a.skepticism_level))).collect()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if [self.high, self.medium, self.low].iter().any(|w| !w.is_finite() || *w < 0.0) {
+            return Err("skepticism_weights must be non-negative numbers".to_string());
+        }
+        Ok(())
+    }
+}
+
+// Above this duplicate_ratio the analyst is warned that agreement may be model repetition
+pub const LOW_DIVERSITY_RATIO: f32 = 0.3;
+
+impl VerifiedMetrics {
+    pub fn from_results(results: &[&SimulationResult]) -> Self {
+        let mut sentiment_counts = BTreeMap::new();
+        let mut category_counts = BTreeMap::new();
+        for r in results {
+            *sentiment_counts.entry(r.sentiment.clone()).or_insert(0) += 1;
+            let category = r.category.clone().unwrap_or_else(|| "uncategorized".to_string());
+            *category_counts.entry(category).or_insert(0) += 1;
+        }
+
+        Self {
+            participants: results.len(),
+            sentiment_counts,
+            category_counts,
+            thoughts_captured: results.iter().filter(|r| r.thought_process.is_some()).count(),
+            consensus_score: AgentSwarm::consensus_score(results),
+            duplicate_ratio: AgentSwarm::duplicate_ratio(results),
+            weighted: None,
+        }
+    }
+
+    fn percent(&self, count: usize) -> f32 {
+        if self.participants > 0 { (count as f32 / self.participants as f32) * 100.0 } else { 0.0 }
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("- Participants: {}\n", self.participants);
+        for (label, count) in &self.sentiment_counts {
+            out.push_str(&format!("- Sentiment `{}`: {} ({:.1}%)\n", label, count, self.percent(*count)));
+        }
+        for (category, count) in &self.category_counts {
+            out.push_str(&format!("- Category `{}`: {} ({:.1}%)\n", category, count, self.percent(*count)));
+        }
+        out.push_str(&format!(
+            "- Hidden thoughts captured: {} ({:.0}%)\n- Consensus score: {:.0}%\n- Near-duplicate responses: {:.0}%",
+            self.thoughts_captured,
+            self.percent(self.thoughts_captured),
+            self.consensus_score,
+            self.duplicate_ratio * 100.0
+        ));
+        if let Some(weighted) = &self.weighted {
+            for (label, pct) in &weighted.sentiment_pct {
+                out.push_str(&format!("\n- Sentiment `{}` (skeptic-weighted): {:.1}%", label, pct));
+            }
+            out.push_str(&format!("\n- Consensus score (skeptic-weighted): {:.0}%", weighted.consensus_score));
+        }
+        out
+    }
+}
+
+/// One side of a `/api/compare` run.
+#[derive(Serialize, Clone, Debug)]
+pub struct JobComparisonSummary {
+    pub job_id: String,
+    pub scenario: String,
+    pub avg_sentiment_score: f32,
+    pub positive_pct: f32,
+    pub metrics: VerifiedMetrics,
+}
+
+impl JobComparisonSummary {
+    fn from_results(job_id: &str, results: &[SimulationResult]) -> Self {
+        let participants: Vec<&SimulationResult> = results.iter().filter(|r| !FocusGroupSession::is_moderator(r) && !AnalystEngine::is_pipe_error(r)).collect();
+        let metrics = VerifiedMetrics::from_results(&participants);
+        let n = participants.len().max(1) as f32;
+
+        Self {
+            job_id: job_id.to_string(),
+            scenario: results.first().map(|r| r.scenario.clone()).unwrap_or_else(|| "unknown".to_string()),
+            avg_sentiment_score: participants.iter().map(|r| r.sentiment_score).sum::<f32>() / n,
+            positive_pct: metrics.percent(*metrics.sentiment_counts.get("positive").unwrap_or(&0)),
+            metrics,
+        }
+    }
+}
+
+/// B minus A. Category shifts are in percentage points.
+#[derive(Serialize, Clone, Debug)]
+pub struct ComparisonDelta {
+    pub sentiment_shift: f32,
+    pub positive_pct_shift: f32,
+    pub consensus_shift: f32,
+    pub category_shift: BTreeMap<String, f32>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ComparisonReport {
+    pub job_a: JobComparisonSummary,
+    pub job_b: JobComparisonSummary,
+    pub delta: ComparisonDelta,
+    pub narrative: String,
+}
+
+/// How much of a run the analyst reads verbatim (ORACULUM_ANALYST_* env vars).
+/// Runs larger than `max_transcript_items` are summarized window by window first (map-reduce),
+/// so a 200-message focus group is analyzed in full instead of just its first 60 messages.
+#[derive(Clone, Copy, Debug)]
+pub struct AnalystConfig {
+    pub max_transcript_items: usize, // ORACULUM_ANALYST_MAX_ITEMS
+    pub chunk_size: usize,           // ORACULUM_ANALYST_CHUNK_SIZE
+}
+
+impl Default for AnalystConfig {
+    fn default() -> Self {
+        Self { max_transcript_items: 60, chunk_size: 30 }
+    }
+}
+
+impl AnalystConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let read = |key: &str, default: usize| {
+            std::env::var(key).ok().and_then(|v| v.parse::<usize>().ok()).filter(|v| *v > 0).unwrap_or(default)
+        };
+        Self {
+            max_transcript_items: read("ORACULUM_ANALYST_MAX_ITEMS", defaults.max_transcript_items),
+            chunk_size: read("ORACULUM_ANALYST_CHUNK_SIZE", defaults.chunk_size),
+        }
+    }
+}
+
+/// Per-request override of the analyst persona and questions (see AnalyzeRequest).
+/// Empty fields fall back to the scenario's defaults, e.g. a "CFO" lens with the usual questions.
+#[derive(Clone, Debug, Default)]
+pub struct AnalystLens {
+    pub role: Option<String>,
+    pub questions: Vec<String>,
+    // agent_id -> weight (SkepticismWeights::agent_weights); None keeps every agent at 1.0
+    pub agent_weights: Option<HashMap<u32, f32>>,
+}
+
+// Computed once, then rendered into either report flavour
+struct ReportInputs {
+    role_context: String,
+    specific_questions: String,
+    total: usize,
+    sentiment_score: f32,
+    consensus_score: f32,
+    metrics: VerifiedMetrics,
+    transcript: String,
+    price_section: Option<String>,
+    price_block: String,
+    diversity_block: String, // Empty unless duplicate_ratio >= LOW_DIVERSITY_RATIO
+    excluded_block: String,  // Empty unless some responses were pipe errors
+    sources_block: String,   // Empty when no agent used a skill
+    product_block: String,   // Empty unless the job covered several products
+    cohort_section: Option<String>,
+    cohort_block: String,    // Empty unless the job ran a control cohort
+    verdict_section: Option<String>,
+    verdict_block: String,   // Empty unless the job ran product launch
+}
+
 impl AnalystEngine {
     pub fn generate_report(
         brain: &Arc<AgentBrain>, 
         scenario_type: &str, 
-        results: &[SimulationResult]
+        results: &[SimulationResult],
+        temperature: f32,
+        lens: &AnalystLens,
     ) -> String {
-        // 1. Contextualize the Data
-        let total = results.len();
-        let positive = results.iter().filter(|r| r.sentiment == "positive").count();
+        let inputs = Self::prepare(brain, scenario_type, results, lens);
+
+        // 4. Construct the Final Prompt
+        let prompt = Self::analysis_prompt(
+            &inputs,
+            scenario_type,
+            "Generate a Management Report in Markdown.\n\
+            CRITICAL: Focus on the 'HIDDEN THOUGHTS' to find true consumer intent.",
+            "Output Format:\n\
+            ## Executive Summary\n\
+            ## The Psychological Profile (Deep Dive)\n\
+            ## Key Findings\n\
+            ## Strategic Recommendations\n\
+            (Be concise, professional, and use bullet points)",
+        );
+
+        // 5. Generate
+        println!("🧠 ANALYST: Generating report for scenario '{}'...", scenario_type);
+        let report = brain.generate(&prompt, 1500, None, None, temperature);
+
+        // Computed sections lead the report, ahead of the narrative
+        let mut header = format!("## Verified Metrics\n{}\n\n", inputs.metrics.to_markdown());
+        if let Some(section) = inputs.verdict_section {
+            header.push_str(&format!("## Purchase Intent\n{}\n\n", section));
+        }
+        if let Some(section) = inputs.price_section {
+            header.push_str(&format!("## Van Westendorp Price Sensitivity\n{}\n\n", section));
+        }
+        if let Some(section) = inputs.cohort_section {
+            header.push_str(&format!("## Treatment vs Control\n{}\n\n", section));
+        }
+        format!("{}{}", header, report)
+    }
+
+    /// Same analysis as `generate_report`, but the brain is asked for JSON so dashboards can read KPIs.
+    /// If the model's JSON can't be parsed, the raw text lands in `executive_summary`.
+    pub fn generate_report_structured(
+        brain: &Arc<AgentBrain>,
+        scenario_type: &str,
+        results: &[SimulationResult],
+        temperature: f32,
+        lens: &AnalystLens,
+    ) -> AnalysisReport {
+        let inputs = Self::prepare(brain, scenario_type, results, lens);
+
+        let output_format = format!(
+            "Return ONLY a JSON object, no other text:\n\
+            {{\"executive_summary\": \"...\", \"consensus_score\": {:.0}, \"key_findings\": [\"...\"], \
+            \"recommendations\": [\"...\"], \"psychological_profile\": \"...\"}}",
+            inputs.consensus_score
+        );
+        let prompt = Self::analysis_prompt(
+            &inputs,
+            scenario_type,
+            "Analyze the data. Focus on the 'HIDDEN THOUGHTS' to find true consumer intent.",
+            &output_format,
+        );
+
+        println!("🧠 ANALYST: Generating structured report for scenario '{}'...", scenario_type);
+        let raw = brain.generate(&prompt, 1500, None, None, temperature);
+
+        // Schema check, then one repair round-trip, then a best-effort partial report
+        let mut report = match AnalysisReport::from_model_json(&raw) {
+            Ok(report) => report,
+            Err(error) => {
+                println!("   ⚠️ ANALYST: Structured report failed validation ({}). Asking for a fix...", error);
+                let repaired = brain.generate(&AnalysisReport::repair_prompt(&raw, &error), 1500, None, None, REPAIR_TEMPERATURE);
+                match AnalysisReport::from_model_json(&repaired) {
+                    Ok(report) => {
+                        println!("   🔧 ANALYST: Repair prompt fixed the report");
+                        report
+                    }
+                    Err(repair_error) => {
+                        println!("   ⚠️ ANALYST: Repair failed too ({}). Keeping what parses.", repair_error);
+                        // The repair attempt usually gets closer; fall back to the first answer if it isn't JSON at all
+                        let (report, fixes) = match AnalysisReport::salvage(&repaired) {
+                            Some(salvaged) => salvaged,
+                            None => AnalysisReport::salvage(&raw).unwrap_or_else(|| {
+                                (AnalysisReport { executive_summary: raw.trim().to_string(), ..AnalysisReport::default() }, vec!["whole reply kept as executive_summary".to_string()])
+                            }),
+                        };
+                        println!("   🔧 ANALYST: Salvaged partial report ({})", fixes.join("; "));
+                        report
+                    }
+                }
+            }
+        };
+
+        // Never trust a model-invented number over the computed one
+        report.consensus_score = inputs.consensus_score;
+        report.metrics = inputs.metrics;
+        report
+    }
+
+    // Shared analyst prompt: the Markdown and JSON reports differ only in `task` and `output_format`.
+    // 'Topic: ... Analysis' lets the Python Brain link the report to the graph nodes created
+    // during the simulation.
+    fn analysis_prompt(inputs: &ReportInputs, scenario_type: &str, task: &str, output_format: &str) -> String {
+        format!(
+            "<|user|>You are an expert {}.\n\
+            Analyze the following synthetic research data (N={} Participants, Sentiment: {:.1}% Positive).\n\
+            Consensus Score: {:.0}% (computed from the data; quote it, do NOT estimate your own).\n\n\
+            --- ROOM CONTEXT ---\n\
+            Topic: {} Analysis\n\n\
+            --- RAW DATA (TRANSCRIPT WITH HIDDEN THOUGHTS) ---\n\
+            {}\n\
+            --- END DATA ---\n\n\
+            --- VERIFIED METRICS (computed; use these exact figures) ---\n\
+            {}\n\n\
+            {}{}{}{}{}{}{}\
+            TASK: {}\n\
+            {}\n\
+            \
+            {}<|end|>\n<|assistant|>",
+            inputs.role_context, inputs.total, inputs.sentiment_score, inputs.consensus_score, scenario_type,
+            inputs.transcript, inputs.metrics.to_markdown(), inputs.verdict_block, inputs.product_block, inputs.cohort_block,
+            inputs.price_block, inputs.diversity_block, inputs.excluded_block, inputs.sources_block, task,
+            inputs.specific_questions, output_format
+        )
+    }
+
+    /// Side-by-side of two runs (e.g. two messaging strategies on the same product).
+    /// Deltas are computed in Rust; the brain only writes the narrative around them.
+    pub fn compare_reports(
+        brain: &Arc<AgentBrain>,
+        job_a: &str,
+        results_a: &[SimulationResult],
+        job_b: &str,
+        results_b: &[SimulationResult],
+    ) -> ComparisonReport {
+        let a = JobComparisonSummary::from_results(job_a, results_a);
+        let b = JobComparisonSummary::from_results(job_b, results_b);
+
+        let share = |summary: &JobComparisonSummary, category: &str| {
+            summary.metrics.percent(*summary.metrics.category_counts.get(category).unwrap_or(&0))
+        };
+        let category_shift: BTreeMap<String, f32> = a.metrics.category_counts.keys()
+            .chain(b.metrics.category_counts.keys())
+            .map(|category| (category.clone(), share(&b, category) - share(&a, category)))
+            .collect();
+
+        let delta = ComparisonDelta {
+            sentiment_shift: b.avg_sentiment_score - a.avg_sentiment_score,
+            positive_pct_shift: b.positive_pct - a.positive_pct,
+            consensus_shift: b.metrics.consensus_score - a.metrics.consensus_score,
+            category_shift,
+        };
+
+        // Short transcripts: enough flavour for the narrative without blowing the context window
+        let excerpt = |results: &[SimulationResult]| -> String {
+            results.iter()
+                .filter(|r| !FocusGroupSession::is_moderator(r) && !AnalystEngine::is_pipe_error(r))
+                .take(20)
+                .map(|r| format!("- {}: \"{}\"\n", r.agent_role, r.response))
+                .collect()
+        };
+
+        let prompt = format!(
+            "<|user|>You are an expert Research Director comparing two synthetic research runs.\n\n\
+            --- RUN A ({}, scenario: {}) ---\n\
+            {}\n\
+            {}\n\
+            --- RUN B ({}, scenario: {}) ---\n\
+            {}\n\
+            {}\n\
+            --- VERIFIED DELTA (B minus A; use these exact figures) ---\n\
+            - Average sentiment score: {:+.3}\n\
+            - Positive share: {:+.1} pts\n\
+            - Consensus score: {:+.0} pts\n\n\
+            TASK: Write a comparative Management Report in Markdown.\n\
+            Output Format:\n\
+            ## Which Run Won (and Why)\n\
+            ## What Changed in the Audience's Reasoning\n\
+            ## Recommendation\n\
+            (Be concise, professional, and use bullet points)<|end|>\n<|assistant|>",
+            a.job_id, a.scenario, a.metrics.to_markdown(), excerpt(results_a),
+            b.job_id, b.scenario, b.metrics.to_markdown(), excerpt(results_b),
+            delta.sentiment_shift, delta.positive_pct_shift, delta.consensus_shift
+        );
+
+        println!("🧠 ANALYST: Comparing jobs {} vs {}...", job_a, job_b);
+        let narrative = brain.generate(&prompt, 1200, None, None, DEFAULT_ANALYSIS_TEMPERATURE);
+
+        ComparisonReport { job_a: a, job_b: b, delta, narrative }
+    }
+
+    /// One metrics line per product (in first-seen order), or None for single-product jobs.
+    fn per_product_summary(participants: &[&SimulationResult]) -> Option<String> {
+        let mut products: Vec<&str> = Vec::new();
+        for product in participants.iter().filter_map(|r| r.product.as_deref()) {
+            if !products.contains(&product) {
+                products.push(product);
+            }
+        }
+        if products.len() < 2 {
+            return None;
+        }
+
+        let lines: Vec<String> = products
+            .into_iter()
+            .map(|product| {
+                let group: Vec<&SimulationResult> =
+                    participants.iter().copied().filter(|r| r.product.as_deref() == Some(product)).collect();
+                let metrics = VerifiedMetrics::from_results(&group);
+                let n = group.len().max(1) as f32;
+                format!(
+                    "- {}: {} responses, {:.1}% positive, avg sentiment {:+.2}, consensus {:.0}%",
+                    product,
+                    group.len(),
+                    metrics.percent(*metrics.sentiment_counts.get("positive").unwrap_or(&0)),
+                    group.iter().map(|r| r.sentiment_score).sum::<f32>() / n,
+                    metrics.consensus_score
+                )
+            })
+            .collect();
+        Some(lines.join("\n"))
+    }
+
+    /// One metrics line per cohort plus the treatment - control gap, or None unless both arms answered.
+    fn cohort_summary(participants: &[&SimulationResult]) -> Option<String> {
+        let arm = |cohort: &str| -> Vec<&SimulationResult> {
+            participants.iter().copied().filter(|r| r.cohort.as_deref() == Some(cohort)).collect()
+        };
+        let (treatment, control) = (arm(TREATMENT_COHORT), arm(CONTROL_COHORT));
+        if treatment.is_empty() || control.is_empty() {
+            return None;
+        }
+
+        // (positive %, avg sentiment, consensus)
+        let stats = |group: &[&SimulationResult]| -> (f32, f32, f32) {
+            let metrics = VerifiedMetrics::from_results(group);
+            (
+                metrics.percent(*metrics.sentiment_counts.get("positive").unwrap_or(&0)),
+                group.iter().map(|r| r.sentiment_score).sum::<f32>() / group.len() as f32,
+                metrics.consensus_score,
+            )
+        };
+        let (t, c) = (stats(&treatment), stats(&control));
+        let line = |label: &str, n: usize, (positive, avg, consensus): (f32, f32, f32)| {
+            format!("- {}: {} responses, {:.1}% positive, avg sentiment {:+.2}, consensus {:.0}%", label, n, positive, avg, consensus)
+        };
+        Some(format!(
+            "{}\n{}\n- Persona effect (treatment - control): {:+.1} pts positive, {:+.2} avg sentiment",
+            line("Treatment (personas)", treatment.len(), t),
+            line("Control (blank slate)", control.len(), c),
+            t.0 - c.0,
+            t.1 - c.1
+        ))
+    }
+
+    /// Distinct (skill, source) pairs the participants cited, most-cited first, with one snippet each.
+    /// None when nobody used a skill.
+    fn cited_sources(participants: &[&SimulationResult]) -> Option<String> {
+        let mut cited: BTreeMap<(String, String), (usize, String)> = BTreeMap::new();
+        for source in participants.iter().flat_map(|r| &r.source_refs) {
+            let entry = cited
+                .entry((source.skill_id.clone(), source.source.clone()))
+                .or_insert_with(|| (0, source.snippet.clone()));
+            entry.0 += 1;
+        }
+        if cited.is_empty() {
+            return None;
+        }
+
+        let mut cited: Vec<_> = cited.into_iter().collect();
+        cited.sort_by_key(|(_, (agents, _))| std::cmp::Reverse(*agents));
+        let lines: Vec<String> = cited
+            .into_iter()
+            .take(8)
+            .map(|((skill_id, source), (agents, snippet))| {
+                format!("- {} / {} (cited by {} agents): \"{}\"", skill_id, source, agents, snippet.replace('\n', " "))
+            })
+            .collect();
+        Some(lines.join("\n"))
+    }
+
+    // Steps 1-3 are shared by the Markdown and the structured (JSON) report.
+    fn prepare(brain: &Arc<AgentBrain>, scenario_type: &str, results: &[SimulationResult], lens: &AnalystLens) -> ReportInputs {
+        // 0. Pipe errors are not verdicts: drop them before anything is counted or quoted
+        let usable: Vec<SimulationResult> = results.iter().filter(|r| !Self::is_pipe_error(r)).cloned().collect();
+        let excluded = results.len() - usable.len();
+        let results = &usable[..];
+        if excluded > 0 {
+            println!("⚠️ ANALYST: Excluding {} failed/error responses from the report", excluded);
+        }
+
+        // 1. Contextualize the Data (moderator turns are not opinions)
+        let participants: Vec<&SimulationResult> = results.iter().filter(|r| !FocusGroupSession::is_moderator(r)).collect();
+        let total = participants.len();
+        let positive = participants.iter().filter(|r| r.sentiment == "positive").count();
         let sentiment_score = if total > 0 { (positive as f32 / total as f32) * 100.0 } else { 0.0 };
+        // Ground truth: computed, never asked of the LLM
+        let mut metrics = VerifiedMetrics::from_results(&participants);
+        metrics.weighted = lens.agent_weights.as_ref().map(|weights| WeightedMetrics::from_results(&participants, weights));
+        let consensus_score = metrics.consensus_score;
 
         // 2. Prepare the Transcript (Deep Read)
         // CRITICAL UPDATE: We now include the 'thought_process' (Internal Monologue)
         // This allows the Analyst to detect the gap between "What they said" vs "What they meant".
-        let mut transcript = String::new();
-        for r in results.iter().take(60) { 
-            let thought_display = r.thought_process.as_deref().unwrap_or("No internal thought captured");
-            
-            transcript.push_str(&format!(
-                "- Participant: {} ({})\n  HIDDEN THOUGHT: {}\n  PUBLIC VERDICT: \"{}\"\n\n", 
-                r.agent_role, 
-                r.agent_demographic, 
-                thought_display, 
-                r.response
-            ));
-        }
+        // Large runs are condensed per window first, so nothing is silently dropped.
+        let config = AnalystConfig::from_env();
+        let transcript = if results.len() <= config.max_transcript_items {
+            Self::transcript(results)
+        } else {
+            Self::summarize_in_windows(brain, scenario_type, results, config.chunk_size)
+        };
 
         // 3. Dynamic Prompting based on Scenario
         let (role_context, specific_questions) = match scenario_type {
@@ -46,6 +709,10 @@ impl AnalystEngine {
                 "Brand Strategist",
                 "1. **Trust Analysis**: Which message generated less skeptical internal thoughts?\n2. **Clarity**: Was there confusion in the internal monologue?\n3. **Recommendation**: Which value prop is stronger?"
             ),
+            "price_sensitivity" => (
+                "Pricing Strategist",
+                "1. **Acceptable Range**: Does the computed Van Westendorp range match what the Hidden Thoughts reveal?\n2. **Reference Prices**: Which products or stores are they comparing against?\n3. **Recommendation**: Where should the price sit, and which segment is lost at that price?"
+            ),
             "cx_flow" => (
                 "UX Researcher",
                 "1. **Friction Points**: Where did the internal monologue show frustration?\n2. **Impulse vs Logic**: Did they buy on impulse or calculation?\n3. **Fixes**: Top 3 UX improvements."
@@ -56,32 +723,284 @@ impl AnalystEngine {
             )
         };
 
-        // 4. Construct the Final Prompt
-        // We inject 'Topic: ... Analysis' to allow the Python Brain to potentially link this report 
-        // to the graph nodes created during the simulation.
-        let prompt = format!(
-            "<|user|>You are an expert {}.\n\
-            Analyze the following synthetic research data (N={} Participants, Sentiment: {:.1}% Positive).\n\n\
-            --- ROOM CONTEXT ---\n\
-            Topic: {} Analysis\n\n\
-            --- RAW DATA (TRANSCRIPT WITH HIDDEN THOUGHTS) ---\n\
-            {}\n\
-            --- END DATA ---\n\n\
-            TASK: Generate a Management Report in Markdown.\n\
-            CRITICAL: Focus on the 'HIDDEN THOUGHTS' to find true consumer intent.\n\
-            {}\n\
-            \
-            Output Format:\n\
-            ## Executive Summary\n\
-            ## The Psychological Profile (Deep Dive)\n\
-            ## Key Findings\n\
-            ## Strategic Recommendations\n\
-            (Be concise, professional, and use bullet points)<|end|>\n<|assistant|>",
-            role_context, total, sentiment_score, scenario_type, transcript, specific_questions
+        // 3a. Caller-supplied lens replaces the scenario defaults
+        let role_context = lens.role.clone().unwrap_or_else(|| role_context.to_string());
+        let specific_questions = if lens.questions.is_empty() {
+            specific_questions.to_string()
+        } else {
+            lens.questions.iter().enumerate().map(|(i, q)| format!("{}. {}", i + 1, q)).collect::<Vec<_>>().join("\n")
+        };
+
+        // 3a'. Product lines: the same panel rated several products, so compare them head to head
+        let product_section = Self::per_product_summary(&participants);
+        let specific_questions = match &product_section {
+            Some(_) => format!(
+                "{}\n{}. **Product Comparison**: Which product wins overall, which personas prefer each one, and why?",
+                specific_questions,
+                specific_questions.lines().filter(|l| !l.trim().is_empty()).count() + 1
+            ),
+            None => specific_questions,
+        };
+        let product_block = product_section
+            .map(|section| format!("--- PER-PRODUCT METRICS (same panel; computed) ---\n{}\n\n", section))
+            .unwrap_or_default();
+
+        // 3a''. Control cohort: blank-slate agents are the baseline the personas are measured against
+        let cohort_section = Self::cohort_summary(&participants);
+        let specific_questions = match &cohort_section {
+            Some(_) => format!(
+                "{}\n{}. **Persona Effect**: How far do the personas move the verdict from the control baseline, and which traits drive the gap?",
+                specific_questions,
+                specific_questions.lines().filter(|l| !l.trim().is_empty()).count() + 1
+            ),
+            None => specific_questions,
+        };
+        let cohort_block = cohort_section
+            .as_ref()
+            .map(|section| format!("--- TREATMENT VS CONTROL (control = blank-slate agents; computed) ---\n{}\n\n", section))
+            .unwrap_or_default();
+
+        // 3a'''. Product launch: the Yes/No/Maybe split, parsed from each verdict in Rust
+        let verdict_section = VerdictDistribution::from_results(participants.iter().copied()).map(|d| d.to_markdown());
+        let verdict_block = verdict_section
+            .as_ref()
+            .map(|section| format!("--- PURCHASE INTENT (Yes/No/Maybe parsed from each verdict; computed) ---\n{}\n\n", section))
+            .unwrap_or_default();
+
+        // 3b. Pricing scenarios get hard numbers computed in Rust (the LLM is bad at arithmetic)
+        let price_section = if scenario_type == "price_sensitivity" {
+            Self::van_westendorp_summary(results)
+        } else {
+            None
+        };
+        let price_block = price_section
+            .as_ref()
+            .map(|section| format!("--- COMPUTED PRICE METRICS ---\n{}\n", section))
+            .unwrap_or_default();
+
+        // 3c. Mode collapse: near-identical answers look like consensus but aren't
+        let diversity_block = if metrics.duplicate_ratio >= LOW_DIVERSITY_RATIO {
+            format!(
+                "--- LOW DIVERSITY WARNING ---\n{:.0}% of responses are near-duplicates of another response. \
+                Treat agreement as possible model repetition; do NOT over-claim consensus.\n\n",
+                metrics.duplicate_ratio * 100.0
+            )
+        } else {
+            String::new()
+        };
+
+        // 3c'. Tell the analyst the sample is smaller than the panel, and why
+        let excluded_block = if excluded > 0 {
+            format!(
+                "--- EXCLUDED RESPONSES ---\n{} responses were inference errors, not agent opinions, and were left out of the data and metrics above.\n\n",
+                excluded
+            )
+        } else {
+            String::new()
+        };
+
+        // 3d. Provenance: which skill sources the agents answered with (see SourceRef)
+        let sources_block = Self::cited_sources(&participants)
+            .map(|list| format!("--- SOURCES AGENTS CITED (ground recommendations in these) ---\n{}\n\n", list))
+            .unwrap_or_default();
+
+        ReportInputs {
+            role_context,
+            specific_questions,
+            total,
+            sentiment_score,
+            consensus_score,
+            metrics,
+            transcript,
+            price_section,
+            price_block,
+            diversity_block,
+            excluded_block,
+            sources_block,
+            product_block,
+            cohort_section,
+            cohort_block,
+            verdict_section,
+            verdict_block,
+        }
+    }
+
+    /// A failed inference (after retries) or a raw brain error string standing in for a verdict.
+    pub fn is_pipe_error(result: &SimulationResult) -> bool {
+        result.failed || BrainError::is_error_reply(&result.response)
+    }
+
+    fn transcript(results: &[SimulationResult]) -> String {
+        // Product-line jobs: every line says which product it is about
+        let first_product = results.first().and_then(|r| r.product.as_deref());
+        let multi_product = results.iter().any(|r| r.product.as_deref() != first_product);
+
+        let mut transcript = String::new();
+        for r in results { 
+            let thought_display = r.thought_process.as_deref().unwrap_or("No internal thought captured");
+            let product_label = match (&r.product, multi_product) {
+                (Some(product), true) => format!(" on {}", product),
+                _ => String::new(),
+            };
+            // Control agents are the baseline, not personas: say so next to their lines
+            let cohort_label = if r.cohort.as_deref() == Some(CONTROL_COHORT) { " [control]" } else { "" };
+            
+            transcript.push_str(&format!(
+                "- Participant: {} ({}){}{}\n  HIDDEN THOUGHT: {}\n  PUBLIC VERDICT: \"{}\"\n\n", 
+                r.agent_role, 
+                r.agent_demographic, 
+                product_label,
+                cohort_label,
+                thought_display, 
+                r.response
+            ));
+        }
+        transcript
+    }
+
+    // Map step: one partial summary per window of `chunk_size` messages (in parallel).
+    // The partials, in transcript order, stand in for the raw transcript in the final prompt.
+    fn summarize_in_windows(brain: &Arc<AgentBrain>, scenario_type: &str, results: &[SimulationResult], chunk_size: usize) -> String {
+        use rayon::prelude::*;
+
+        let windows: Vec<&[SimulationResult]> = results.chunks(chunk_size).collect();
+        println!("🧠 ANALYST: {} messages exceed the transcript limit. Summarizing {} windows first...", results.len(), windows.len());
+
+        let partials: Vec<String> = windows
+            .par_iter()
+            .enumerate()
+            .map(|(i, window)| {
+                let prompt = format!(
+                    "<|user|>You are a research assistant condensing part of a consumer research transcript.\n\
+                    Topic: {} Analysis\n\n\
+                    --- TRANSCRIPT EXCERPT ---\n\
+                    {}\
+                    --- END EXCERPT ---\n\n\
+                    TASK: Summarize this excerpt in 5-8 bullet points. Keep the gap between HIDDEN THOUGHTS \
+                    and PUBLIC VERDICTS, recurring objections, price remarks and notable quotes (with who said them).\n\
+                    Do not add conclusions that are not in the excerpt.<|end|>\n<|assistant|>",
+                    scenario_type,
+                    Self::transcript(window)
+                );
+                let summary = brain.generate(&prompt, 500, None, None, 0.3);
+
+                let first = i * chunk_size + 1;
+                format!("### Messages {}-{}\n{}\n\n", first, first + window.len() - 1, summary.trim())
+            })
+            .collect();
+
+        format!("(Condensed: {} messages summarized in windows of {})\n\n{}", results.len(), chunk_size, partials.concat())
+    }
+
+    /// Aggregates the per-agent PRICE POINTS lines into the classic Van Westendorp outputs.
+    /// Returns None if fewer than two agents gave usable price points.
+    pub fn van_westendorp_summary(results: &[SimulationResult]) -> Option<String> {
+        let parsed: Vec<(PricePoints, Option<f32>)> = results
+            .iter()
+            .filter_map(|r| PricePoints::from_response(&r.response))
+            .collect();
+        if parsed.len() < 2 {
+            return None;
+        }
+
+        let points: Vec<PricePoints> = parsed.iter().map(|(p, _)| *p).collect();
+        let n = points.len() as f32;
+
+        // Cumulative curves: share of respondents for whom price `p` is ...
+        let too_cheap = |p: f32| points.iter().filter(|x| x.too_cheap >= p).count() as f32 / n;
+        let not_bargain = |p: f32| points.iter().filter(|x| x.bargain <= p).count() as f32 / n;
+        let not_expensive = |p: f32| points.iter().filter(|x| x.expensive >= p).count() as f32 / n;
+        let too_expensive = |p: f32| points.iter().filter(|x| x.too_expensive <= p).count() as f32 / n;
+
+        // Candidate prices = every value anyone mentioned
+        let mut candidates: Vec<f32> = points
+            .iter()
+            .flat_map(|x| [x.too_cheap, x.bargain, x.expensive, x.too_expensive])
+            .collect();
+        candidates.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        candidates.dedup();
+
+        // Intersection = candidate where the two curves are closest
+        let crossing = |a: &dyn Fn(f32) -> f32, b: &dyn Fn(f32) -> f32| -> f32 {
+            candidates
+                .iter()
+                .copied()
+                .min_by(|x, y| {
+                    let dx = (a(*x) - b(*x)).abs();
+                    let dy = (a(*y) - b(*y)).abs();
+                    dx.partial_cmp(&dy).unwrap_or(std::cmp::Ordering::Equal)
+                })
+                .unwrap_or(0.0)
+        };
+
+        let pmc = crossing(&too_cheap, &not_bargain); // Point of Marginal Cheapness
+        let pme = crossing(&too_expensive, &not_expensive); // Point of Marginal Expensiveness
+        let opp = crossing(&too_cheap, &too_expensive); // Optimal Price Point
+
+        let median = |f: fn(&PricePoints) -> f32| -> f32 {
+            let mut values: Vec<f32> = points.iter().map(f).collect();
+            values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+            values[values.len() / 2]
+        };
+
+        let mut summary = format!(
+            "- Respondents with usable price points: {}\n\
+            - Median answers: too cheap ₹{:.0} | bargain ₹{:.0} | expensive ₹{:.0} | too expensive ₹{:.0}\n\
+            - Acceptable price range: ₹{:.0} – ₹{:.0}\n\
+            - Optimal price point: ₹{:.0}",
+            points.len(),
+            median(|x| x.too_cheap),
+            median(|x| x.bargain),
+            median(|x| x.expensive),
+            median(|x| x.too_expensive),
+            pmc.min(pme),
+            pmc.max(pme),
+            opp
         );
 
-        // 5. Generate
-        println!("🧠 ANALYST: Generating report for scenario '{}'...", scenario_type);
-        brain.generate(&prompt, 1500, None, None, 0.4)
+        // Where the brand's own price lands
+        if let Some(anchor) = parsed.iter().find_map(|(_, a)| *a) {
+            let rejected = points.iter().filter(|x| anchor > x.too_expensive).count();
+            summary.push_str(&format!(
+                "\n- Tested price ₹{:.0}: {} ({:.0}% of respondents find it too expensive)",
+                anchor,
+                if anchor >= pmc.min(pme) && anchor <= pmc.max(pme) { "inside the acceptable range" } else { "outside the acceptable range" },
+                rejected as f32 / n * 100.0
+            ));
+        }
+
+        Some(summary)
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repair_prompt_has_no_source_indentation() {
+        let prompt = AnalysisReport::repair_prompt("{\"executive_summary\": 3}", "`executive_summary` must be string");
+        assert!(!prompt.contains("  "), "{:?}", prompt);
+        assert!(prompt.contains("\n--- PREVIOUS OUTPUT ---\n{\"executive_summary\": 3}\n--- END ---\n"));
+        assert!(prompt.contains("\"key_findings\": [string], \"recommendations\""));
+    }
+
+    #[test]
+    fn markdown_and_json_prompts_share_the_analysis_body() {
+        let brain = Arc::new(AgentBrain::demo());
+        let results = vec![SimulationResult {
+            agent_id: 1,
+            agent_role: "Priya".to_string(),
+            response: "Too pricey for 40g".to_string(),
+            sentiment: "negative".to_string(),
+            ..SimulationResult::default()
+        }];
+        let inputs = AnalystEngine::prepare(&brain, "pricing", &results, &AnalystLens::default());
+
+        let markdown = AnalystEngine::analysis_prompt(&inputs, "pricing", "Write Markdown.", "## Summary");
+        let json = AnalystEngine::analysis_prompt(&inputs, "pricing", "Return JSON.", "{}");
+        let body = |prompt: &str| prompt.split("TASK:").next().unwrap().to_string();
+        assert_eq!(body(&markdown), body(&json));
+        assert!(markdown.contains("Too pricey for 40g"));
+        assert!(markdown.ends_with("## Summary<|end|>\n<|assistant|>"));
     }
-}
\ No newline at end of file
+}
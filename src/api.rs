@@ -2,28 +2,299 @@
 // ORACULUM CORE - API HANDLER
 // Orchestrates the Simulation, Memory Priming, and Execution.
 
-use actix_web::{web, HttpResponse, Responder};
+use actix_web::{web, HttpRequest, HttpResponse, Responder, ResponseError};
+use actix_web::http::{header, StatusCode};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
+use std::collections::HashMap;
 use std::sync::Arc;
-use crate::AppState;
-use crate::agent_swarm::{Agent, SimulationResult, AgentSwarm};
-use crate::scenarios::Scenario; 
-use crate::persona_generator::PersonaGenerator;
-use crate::focus_group::FocusGroupSession; 
-use crate::analyst::AnalystEngine;
+use dashmap::DashMap;
+use futures_util::{stream, StreamExt};
+use tokio::sync::{mpsc, watch};
+use crate::{AppState, RunSettings};
+use crate::brain::AgentBrain;
+use crate::agent_swarm::{Agent, SimulationResult, AgentSwarm, SentimentLexicon, TimingSummary, VerdictDistribution};
+use crate::scenarios::{is_english, LocalizedScenario, Scenario, ScenarioRegistry, TemplateScenario};
+use crate::persona_generator::{panel_path, ArchetypeMix, PersonaGenerator, ARCHETYPES, DEFAULT_PERSONA_TEMPERATURE};
+use crate::focus_group::{AdvocateIntensity, FocusGroupSession, InjectedQuestions, PhaseConfig};
+use crate::analyst::{AnalysisReport, AnalystEngine, AnalystLens, SkepticismWeights, DEFAULT_ANALYSIS_TEMPERATURE, LOW_DIVERSITY_RATIO};
+use crate::reporter::Reporter;
+use crate::batch_inference::{DEFAULT_BATCH_SIZE, MAX_BATCH_SIZE};
+use crate::parsing::ResponseLimit;
+use crate::memory::{MemoryType, PersonaMemoryStore};
+use crate::research_cache::{CachedResearch, ResearchCache, ResearchSources};
+use crate::skills::SkillRegistry;
+use std::fmt;
+use std::io;
 use std::thread;
 
 // 1. The Request Format
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone, Default)]
 pub struct SimulationRequest {
     pub scenario: String,        
-    pub product_name: String,    
+    pub product_name: ProductNames, // One product, or a product line run through the same panel
     pub context: String,         
     pub target_audience: String, 
     pub agent_count: usize,
     pub image_data: Option<String>, 
     pub pdf_data: Option<String>,   
+    // Focus groups only: add a moderator who opens each round with a steering question
+    pub moderator: Option<bool>,
+    // Seed for reproducible runs: the focus-group speaking order and the fallback persona panel
+    pub seed: Option<u64>,
+    // Focus groups only: debate turns rebut a named prior speaker (sets reply_to)
+    pub reply_targeting: Option<bool>,
+    // Focus groups only: how many of the most skeptical agents play devil's advocate in the debate
+    // round (default 0), and how hard they push: "mild", "firm" (default) or "aggressive"
+    pub devils_advocates: Option<usize>,
+    pub advocate_intensity: Option<AdvocateIntensity>,
+    // Focus groups only: custom round script, one round per phase (default: reactions / debate / verdict).
+    // e.g. [{"name": "Brainstorm", "instruction": "Suggest one improvement"}, ...]; `is_debate` marks conflict rounds
+    pub phases: Option<Vec<PhaseConfig>>,
+    // Focus groups only: room-history lines shown verbatim (older ones are summarized); default 2 per agent
+    pub history_window: Option<usize>,
+    // Focus groups only: importance sum that triggers agent reflections (e.g. 2.0); None disables
+    pub reflection_threshold: Option<f32>,
+    // Name of a saved persona panel (see panel_path) to reuse instead of generating
+    pub reuse_personas: Option<String>,
+    // Panel name to save the generated personas under, for later `reuse_personas` runs
+    pub save_personas: Option<String>,
+    // Intended retail price (₹) tested by the price_sensitivity scenario
+    pub price_anchor: Option<f32>,
+    // User-defined prompt template (raw string or {"prompt", "verdict_tags"} JSON), see TemplateScenario
+    pub custom_template: Option<String>,
+    // Extra sentiment words (word/phrase -> polarity), merged over the built-in + Hinglish lexicons
+    pub sentiment_lexicon: Option<HashMap<String, f32>>,
+    // Skill ids given to every persona, replacing the role-based defaults ([] disables skills)
+    pub skills: Option<Vec<String>>,
+    // Sampling temperatures (clamped to 0.0..=2.0); defaults: personas 0.8, responses 0.7
+    pub persona_temperature: Option<f32>,
+    pub response_temperature: Option<f32>,
+    // Token budget per agent reply; defaults to the scenario's own (Scenario::max_response_tokens)
+    pub max_tokens: Option<usize>,
+    // Response language (e.g. "Hindi", "Tamil") for every non-focus-group scenario, incl. custom
+    // templates (see LocalizedScenario). Non-English runs score sentiment with the user lexicon only.
+    pub language: Option<String>,
+    // Persona archetype mix, e.g. {"skeptics": 0.6, "optimists": 0.4} (keys: PersonaGenerator ARCHETYPES).
+    // Batches sample archetypes by weight instead of rotating through all four.
+    pub archetype_weights: Option<HashMap<String, f32>>,
+    // Inference engine for single-turn scenarios: "parallel" (default, one worker call per agent) or
+    // "batch" (BatchInferenceEngine: one call per `batch_size` prompts, default 8; text only)
+    pub engine: Option<String>,
+    pub batch_size: Option<usize>,
+    // Share of the panel (0.0 < f < 1.0) run as blank-slate control agents: no persona detail,
+    // medium skepticism, no skills. Results carry `cohort` and the analyst compares the two arms.
+    pub control_fraction: Option<f32>,
+    // Single-turn scenarios: cap each answer at this many sentences / chars (sentence-aware);
+    // the full text is kept in `response_full`
+    pub max_response_sentences: Option<usize>,
+    pub max_response_chars: Option<usize>,
+    // Research lookups to run: any of "reddit", "wiki", "openfoodfacts" (default: all three).
+    // Drop OpenFoodFacts for non-food products, Reddit for B2B; [] skips all three.
+    pub sources: Option<Vec<String>>,
+}
+
+/// `product_name` accepts "Maggi" or a product line: ["Maggi", "Maggi Atta", "Maggi Oats"].
+/// Personas are generated once; each product then runs through the same panel (tagged `product`).
+#[derive(Deserialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum ProductNames {
+    One(String),
+    Line(Vec<String>),
+}
+
+impl Default for ProductNames {
+    fn default() -> Self {
+        ProductNames::One(String::new())
+    }
+}
+
+impl ProductNames {
+    /// Every product, in request order (blank names dropped).
+    pub fn all(&self) -> Vec<String> {
+        let names: Vec<&String> = match self {
+            ProductNames::One(name) => vec![name],
+            ProductNames::Line(names) => names.iter().collect(),
+        };
+        names.into_iter().map(|n| n.trim().to_string()).filter(|n| !n.is_empty()).collect()
+    }
+
+    /// The first product: research for persona generation, focus groups, previews.
+    pub fn primary(&self) -> String {
+        self.all().into_iter().next().unwrap_or_default()
+    }
+}
+
+
+// Longest product line per request; every product is a full panel pass
+const MAX_PRODUCTS: usize = 10;
+
+// Longest custom focus-group script (SimulationRequest::phases); every round is a full panel pass
+const MAX_FOCUS_GROUP_PHASES: usize = 8;
+
+// Bounds for SimulationRequest::max_tokens (below ~50 the [Thinking]/[Verdict] format can't fit)
+const MIN_RESPONSE_TOKENS: usize = 50;
+const MAX_RESPONSE_TOKENS: usize = 4096;
+
+// 1b. Input Limits (ORACULUM_MAX_AGENTS / ORACULUM_MAX_ATTACHMENT_BYTES / ORACULUM_MAX_ACTIVE_JOBS)
+#[derive(Clone, Copy)]
+pub struct SimulationLimits {
+    pub max_agents: usize,
+    pub max_attachment_bytes: usize, // Decoded size of each image/pdf
+    pub max_active_jobs: usize,      // Jobs allowed in "processing" at once; more get a 429
+}
+
+impl SimulationLimits {
+    pub fn from_env() -> Self {
+        let read = |key: &str, default: usize| {
+            std::env::var(key).ok().and_then(|v| v.parse::<usize>().ok()).unwrap_or(default)
+        };
+        Self {
+            max_agents: read("ORACULUM_MAX_AGENTS", 200).max(1),
+            max_attachment_bytes: read("ORACULUM_MAX_ATTACHMENT_BYTES", 10 * 1024 * 1024),
+            max_active_jobs: read("ORACULUM_MAX_ACTIVE_JOBS", 2).max(1),
+        }
+    }
+
+    /// Body limit for the JSON extractor: two base64 attachments (4/3 overhead) plus slack for text.
+    pub fn json_body_limit(&self) -> usize {
+        self.max_attachment_bytes / 3 * 4 * 2 + 1024 * 1024
+    }
+}
+
+impl SimulationRequest {
+    /// Rejects input that would otherwise panic or produce garbage deep in the pipeline.
+    pub fn validate(&self, limits: &SimulationLimits) -> Result<(), String> {
+        if self.agent_count == 0 || self.agent_count > limits.max_agents {
+            return Err(format!("agent_count must be between 1 and {} (got {})", limits.max_agents, self.agent_count));
+        }
+        let products = self.product_name.all();
+        if products.is_empty() {
+            return Err("product_name must not be empty".to_string());
+        }
+        if products.len() > MAX_PRODUCTS {
+            return Err(format!("product_name lists {} products; the limit is {}", products.len(), MAX_PRODUCTS));
+        }
+        if products.len() > 1 && self.scenario == "focus_group" {
+            return Err("focus_group runs one product at a time; product_name must be a single product".to_string());
+        }
+
+        let attachments = [("image_data", &self.image_data), ("pdf_data", &self.pdf_data)];
+        for (field, payload) in attachments {
+            // base64 carries 3 bytes per 4 chars
+            let decoded_len = payload.as_ref().map_or(0, |p| p.len() / 4 * 3);
+            if decoded_len > limits.max_attachment_bytes {
+                return Err(format!(
+                    "{} is ~{} bytes; the limit is {} bytes",
+                    field, decoded_len, limits.max_attachment_bytes
+                ));
+            }
+        }
+
+        if let Some(max_tokens) = self.max_tokens {
+            if !(MIN_RESPONSE_TOKENS..=MAX_RESPONSE_TOKENS).contains(&max_tokens) {
+                return Err(format!(
+                    "max_tokens must be between {} and {} (got {})",
+                    MIN_RESPONSE_TOKENS, MAX_RESPONSE_TOKENS, max_tokens
+                ));
+            }
+        }
+
+        for name in [&self.reuse_personas, &self.save_personas].into_iter().flatten() {
+            panel_path(name)?;
+        }
+
+        if let Some(language) = &self.language {
+            if !language.trim().chars().all(|c| c.is_alphabetic() || matches!(c, ' ' | '-' | '_')) || language.len() > 40 {
+                return Err(format!("language must be a language name like 'Hindi' (got '{}')", language));
+            }
+            if self.scenario == "focus_group" && !is_english(language) {
+                return Err("language is not supported for focus_group yet".to_string());
+            }
+        }
+
+        if let Some(weights) = &self.archetype_weights {
+            let keys: Vec<&str> = ARCHETYPES.iter().map(|(key, _)| *key).collect();
+            if let Some(unknown) = weights.keys().find(|k| !keys.contains(&k.as_str())) {
+                return Err(format!("Unknown archetype '{}'. Valid archetypes: {}", unknown, keys.join(", ")));
+            }
+            if weights.values().any(|w| !w.is_finite() || *w < 0.0) || weights.values().sum::<f32>() <= 0.0 {
+                return Err("archetype_weights must be non-negative and not all zero".to_string());
+            }
+        }
+
+        match self.engine.as_deref() {
+            None | Some("parallel") => {}
+            Some("batch") => {
+                if self.scenario == "focus_group" {
+                    return Err("engine 'batch' covers single-turn scenarios, not focus_group".to_string());
+                }
+                if self.image_data.is_some() || self.pdf_data.is_some() {
+                    return Err("engine 'batch' is text only; drop image_data/pdf_data or use 'parallel'".to_string());
+                }
+            }
+            Some(other) => return Err(format!("Unknown engine '{}'. Valid engines: parallel, batch", other)),
+        }
+        if let Some(batch_size) = self.batch_size {
+            if !(1..=MAX_BATCH_SIZE).contains(&batch_size) {
+                return Err(format!("batch_size must be between 1 and {} (got {})", MAX_BATCH_SIZE, batch_size));
+            }
+        }
+
+        if self.max_response_sentences == Some(0) || self.max_response_chars == Some(0) {
+            return Err("max_response_sentences and max_response_chars must be at least 1".to_string());
+        }
+
+        if let Some(phases) = &self.phases {
+            if phases.is_empty() || phases.len() > MAX_FOCUS_GROUP_PHASES {
+                return Err(format!("phases must list between 1 and {} rounds (got {})", MAX_FOCUS_GROUP_PHASES, phases.len()));
+            }
+            if let Some(phase) = phases.iter().find(|p| p.name.trim().is_empty() || p.instruction.trim().is_empty()) {
+                return Err(format!("Every phase needs a name and an instruction (phase '{}')", phase.name));
+            }
+            if phases.iter().any(|p| p.temperature.is_some_and(|t| !(0.0..=2.0).contains(&t))) {
+                return Err("phase temperatures must be between 0.0 and 2.0".to_string());
+            }
+        }
+
+        if let Some(advocates) = self.devils_advocates {
+            if advocates > self.agent_count {
+                return Err(format!("devils_advocates ({}) can't exceed agent_count ({})", advocates, self.agent_count));
+            }
+        }
+
+        if let Some(fraction) = self.control_fraction {
+            if !(fraction > 0.0 && fraction < 1.0) {
+                return Err(format!("control_fraction must be between 0.0 and 1.0, exclusive (got {})", fraction));
+            }
+            if self.agent_count < 2 {
+                return Err("control_fraction needs at least 2 agents (one per cohort)".to_string());
+            }
+        }
+
+        ResearchSources::from_names(self.sources.as_deref())?;
+
+        if let Some(skills) = &self.skills {
+            let mut available = SkillRegistry::new().list_available();
+            if let Some(unknown) = skills.iter().find(|id| !available.contains(id)) {
+                available.sort();
+                return Err(format!("Unknown skill '{}'. Valid skills: {}", unknown, available.join(", ")));
+            }
+        }
+        Ok(())
+    }
+
+    /// The research lookups this request asked for (all of them when `sources` is omitted).
+    pub fn research_sources(&self) -> ResearchSources {
+        ResearchSources::from_names(self.sources.as_deref()).unwrap_or_default()
+    }
+
+    pub fn response_limit(&self) -> ResponseLimit {
+        ResponseLimit {
+            max_sentences: self.max_response_sentences,
+            max_chars: self.max_response_chars,
+        }
+    }
 }
 
 // 2. The Response Format
@@ -33,20 +304,273 @@ pub struct JobCreatedResponse {
     pub status: String,
 }
 
+// 2a. Dry-run output of POST /api/preview (no inference)
+#[derive(Serialize)]
+pub struct PromptPreview {
+    pub agent_id: u32,
+    pub agent_name: String,
+    pub prompt: String,  // Scenario prompt only; skill knowledge is added when the job runs
+    pub skills: Vec<String>, // Skills that would run for this agent and extend the prompt
+}
+
+#[derive(Serialize)]
+pub struct PreviewResponse {
+    pub scenario: String,
+    pub research_cached: bool, // false: research was skipped, so the context block is a placeholder
+    pub prompts: Vec<PromptPreview>,
+}
+
+// 2b. The Error Format
+// Every handler error serializes to {"error_code": "...", "message": "..."} with a matching
+// HTTP status, so the frontend can branch on `error_code` instead of matching strings.
+#[derive(Debug)]
+pub enum ApiError {
+    JobNotFound,
+    AgentNotFound,
+    NoResults(String),
+    BrainUnavailable(String),
+    InvalidRequest(String),
+    TooManyJobs(usize), // Active-job cap reached (SimulationLimits::max_active_jobs)
+    Internal(String),
+}
+
+// Suggested wait for a client that hit the active-job cap
+const RETRY_AFTER_SECS: u64 = 30;
+
+#[derive(Serialize)]
+struct ApiErrorBody<'a> {
+    error_code: &'a str,
+    message: String,
+}
+
+impl ApiError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::JobNotFound => "JOB_NOT_FOUND",
+            ApiError::AgentNotFound => "AGENT_NOT_FOUND",
+            ApiError::NoResults(_) => "NO_RESULTS",
+            ApiError::BrainUnavailable(_) => "BRAIN_UNAVAILABLE",
+            ApiError::InvalidRequest(_) => "INVALID_REQUEST",
+            ApiError::TooManyJobs(_) => "TOO_MANY_JOBS",
+            ApiError::Internal(_) => "INTERNAL_ERROR",
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::JobNotFound => write!(f, "Job not found"),
+            ApiError::AgentNotFound => write!(f, "Agent not found in this job"),
+            ApiError::TooManyJobs(max) => write!(f, "{} simulations are already running; retry later", max),
+            ApiError::NoResults(msg)
+            | ApiError::BrainUnavailable(msg)
+            | ApiError::InvalidRequest(msg)
+            | ApiError::Internal(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::JobNotFound | ApiError::AgentNotFound => StatusCode::NOT_FOUND,
+            ApiError::NoResults(_) | ApiError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::BrainUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::TooManyJobs(_) => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let mut response = HttpResponse::build(self.status_code());
+        if let ApiError::TooManyJobs(_) = self {
+            response.insert_header(("Retry-After", RETRY_AFTER_SECS.to_string()));
+        }
+        response.json(ApiErrorBody {
+            error_code: self.code(),
+            message: self.to_string(),
+        })
+    }
+}
+
+// Extractor failures (malformed JSON, wrong field types, oversized bodies, bad query or path
+// parameters) answer in the same {error_code, message} shape as the handlers' own errors.
+pub fn json_config(limit: usize) -> web::JsonConfig {
+    web::JsonConfig::default()
+        .limit(limit)
+        .error_handler(|err, _| ApiError::InvalidRequest(err.to_string()).into())
+}
+
+pub fn query_config() -> web::QueryConfig {
+    web::QueryConfig::default().error_handler(|err, _| ApiError::InvalidRequest(err.to_string()).into())
+}
+
+pub fn path_config() -> web::PathConfig {
+    web::PathConfig::default().error_handler(|err, _| ApiError::InvalidRequest(err.to_string()).into())
+}
+
+// 2c. One persona's memory stream (GET /api/jobs/{id}/agents/{agent_id}/memory)
+#[derive(Serialize)]
+pub struct MemoryEntry {
+    pub id: String,
+    pub content: String,
+    pub memory_type: MemoryType,
+    pub importance: f32,
+    pub score: f32, // Query-free retrieval score (see MemoryStream::ranked)
+    pub creation_timestamp: String,
+    pub last_accessed: String,
+}
+
+#[derive(Serialize)]
+pub struct AgentMemoryResponse {
+    pub agent_id: u32,
+    pub agent_name: String,
+    pub persisted: bool, // true: read from the PersonaMemoryStore (the job's live stream was empty)
+    pub memories: Vec<MemoryEntry>,
+}
+
+// What POST .../rerun needs to rebuild an agent's prompt exactly as the job did.
+// Held in memory (AppState::reruns) for the last MAX_RERUN_CONTEXTS jobs of this server session.
+#[derive(Clone)]
+pub struct RerunContext {
+    pub request: SimulationRequest,
+    pub product_contexts: Vec<(String, String)>, // (product, enriched context) per pass
+    pub stored_at: std::time::Instant,
+}
+
+// Jobs that stay rerunnable; each context holds the request (attachments included), so the
+// oldest is dropped beyond this
+const MAX_RERUN_CONTEXTS: usize = 64;
+
+fn remember_rerun(reruns: &DashMap<String, RerunContext>, job_id: &str, request: SimulationRequest, product_contexts: Vec<(String, String)>) {
+    if reruns.len() >= MAX_RERUN_CONTEXTS && !reruns.contains_key(job_id) {
+        let oldest = reruns.iter().min_by_key(|entry| entry.value().stored_at).map(|entry| entry.key().clone());
+        if let Some(oldest) = oldest {
+            reruns.remove(&oldest);
+        }
+    }
+    reruns.insert(job_id.to_string(), RerunContext { request, product_contexts, stored_at: std::time::Instant::now() });
+}
+
+#[derive(Deserialize)]
+pub struct RerunQuery {
+    pub product: Option<String>, // Product-line jobs: whose answer to regenerate
+}
+
 // 3. The Job Status
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct JobStatus {
     pub id: String,
     pub status: String, 
     pub progress: f32,  
+    pub scenario: String,
+    pub created_at: String,
     pub agents: Vec<Agent>,
     pub results: Vec<SimulationResult>,
+    // Realized persona archetypes (agents per archetype); None for reused panels / older jobs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archetype_mix: Option<ArchetypeMix>,
+    // Share of near-identical responses once completed (see AgentSwarm::duplicate_ratio)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duplicate_ratio: Option<f32>,
+    // Packaging text read from `image_data` (AgentBrain::ocr), run once per job and merged into the context
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pack_text: Option<String>,
+    // Per-agent timing roll-up (turn / inference / per-skill total, avg, p95) once completed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timing: Option<TimingSummary>,
+    // Yes/No/Maybe purchase-intent tally once completed (product launch only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verdicts: Option<VerdictDistribution>,
+    // Bumped on every published change (see `publish`); served as the status ETag
+    #[serde(default)]
+    pub version: u64,
+}
+
+// 3b. Lightweight Job Listing (no agents/results payload)
+#[derive(Serialize)]
+pub struct JobSummary {
+    pub id: String,
+    pub status: String,
+    pub progress: f32,
+    pub agent_count: usize,
+    pub scenario: String,
+    pub created_at: String,
+    pub diversity: f32, // See PersonaGenerator::diversity
+}
+
+impl From<&JobStatus> for JobSummary {
+    fn from(job: &JobStatus) -> Self {
+        Self {
+            id: job.id.clone(),
+            status: job.status.clone(),
+            progress: job.progress,
+            agent_count: job.agents.len(),
+            scenario: job.scenario.clone(),
+            created_at: job.created_at.clone(),
+            diversity: PersonaGenerator::diversity(&job.agents),
+        }
+    }
+}
+
+// 3c. Self-describing catalogue (GET /api/scenarios, GET /api/skills)
+#[derive(Serialize)]
+pub struct ScenarioInfo {
+    pub key: String,
+    pub name: String,
+}
+
+#[derive(Serialize)]
+pub struct SkillInfo {
+    pub id: String, // Registry key, as used in SimulationRequest::skills
+    pub name: String,
+    pub description: String,
+}
+
+#[derive(Deserialize)]
+pub struct JobStatusQuery {
+    pub results_offset: Option<usize>,
+    pub results_limit: Option<usize>, // Omitted: every result from the offset on
+}
+
+// 3c. Status response: the job, with `results` sliced per JobStatusQuery
+#[derive(Serialize)]
+pub struct JobStatusPage {
+    #[serde(flatten)]
+    pub job: JobStatus,
+    pub total_results: usize,
+}
+
+#[derive(Deserialize)]
+pub struct JobListQuery {
+    pub status: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    pub format: Option<String>, // "csv" (default) | "json" | "jsonl"
+}
+
+#[derive(Serialize)]
+pub struct HealthResponse {
+    pub status: String, // "ok" | "degraded"
+    pub brain_ready: bool,
+    pub active_jobs: usize,
 }
 
 // 4. Analysis Payloads
 #[derive(Deserialize)]
 pub struct AnalyzeRequest {
     pub job_id: String,
+    // Report sampling temperature (clamped to 0.0..=2.0); default 0.4
+    pub analysis_temperature: Option<f32>,
+    // Analyst persona (e.g. "CFO") and questions; default to the scenario's own
+    pub analyst_role: Option<String>,
+    pub focus_questions: Option<Vec<String>>,
+    // Also report skeptic-weighted sentiment/consensus: {} for High 1.5 / Medium 1.0 / Low 0.7,
+    // or override any of {"high", "medium", "low"}. Omitted: unweighted figures only
+    pub skepticism_weights: Option<SkepticismWeights>,
 }
 
 #[derive(Serialize)]
@@ -54,200 +578,1382 @@ pub struct AnalysisResponse {
     pub report: String,
 }
 
+#[derive(Deserialize)]
+pub struct CompareRequest {
+    pub job_id_a: String,
+    pub job_id_b: String,
+}
+
+#[derive(Deserialize)]
+pub struct AnalyzeQuery {
+    pub format: Option<String>, // "markdown" (default) | "json"
+}
+
+enum AnalysisOutput {
+    Markdown(String),
+    Structured(AnalysisReport),
+}
+
+// Client-supplied sampling temperature, clamped to the range the brain handles sensibly
+fn temperature(requested: Option<f32>, default: f32) -> f32 {
+    requested.unwrap_or(default).clamp(0.0, 2.0)
+}
+
+// Call after every change to a job: bumps its version (ETag) and pushes it to SSE subscribers.
+// NOTE: Call only after the `get_mut` guard on the job has been dropped.
+fn publish(
+    jobs: &DashMap<String, JobStatus>,
+    streams: &DashMap<String, watch::Sender<JobStatus>>,
+    job_id: &str,
+) {
+    if let Some(mut job) = jobs.get_mut(job_id) {
+        job.version += 1;
+    }
+    if let (Some(job), Some(tx)) = (jobs.get(job_id), streams.get(job_id)) {
+        tx.send_replace(job.clone());
+    }
+    // Subscribers still receive the final snapshot; later readers fall back to the stored job
+    if jobs.get(job_id).is_some_and(|job| job.status != "processing") {
+        streams.remove(job_id);
+    }
+}
+
+// --- STEP 0 of a job: research voices + fact sheet for one product ---
+// Served from the ResearchCache when the same product + context + sources ran recently.
+// Only the requested `sources` are queried.
+fn gather_research(
+    brain: &Arc<AgentBrain>,
+    research_cache: &ResearchCache,
+    product: &str,
+    context: &str,
+    sources: &ResearchSources,
+) -> (Vec<String>, String) {
+    if let Some(cached) = research_cache.get(product, context, sources) {
+        println!("⚡ SCOUT: Reusing cached research for '{}'", product);
+        return (cached.voices, cached.fact_sheet);
+    }
+
+    let web = sources.web();
+    let research_data = if web.is_empty() {
+        println!("🕵️ SCOUT: Reddit + Wiki not requested; skipping web research");
+        Vec::new()
+    } else {
+        println!("🕵️ SCOUT: Initiating Federated Research ({})...", web.join(" + "));
+        brain.research(product, context, &web)
+    };
+
+    // --- STEP 0.5: MEMORY PRIMING (The "Self-Healing" Trigger) ---
+    // We query the new memory system once here.
+    // If the topic is new, the Python worker will go online, fetch data, and save it to LanceDB.
+    // This ensures that when the 25 agents run in parallel later, they hit the Local Cache, not the Web.
+    println!("🧠 MEMORY: Priming Cognitive Graph for '{}'...", product);
+    let _ = brain.query_memory(product); 
+
+    let fact_sheet = if sources.openfoodfacts {
+        println!("📦 SCOUT: Fetching Product Specifications...");
+        brain.get_facts(product)
+    } else {
+        "(OpenFoodFacts not requested for this job)".to_string()
+    };
+
+    research_cache.insert(product, context, sources, CachedResearch {
+        voices: research_data.clone(),
+        fact_sheet: fact_sheet.clone(),
+    });
+    (research_data, fact_sheet)
+}
+
+// The single-turn scenario for one product pass. A custom template wins; unknown keys fall back
+// to a Product Launch (historic behaviour). None only if even that isn't registered.
+fn scenario_for_product(
+    scenarios: &ScenarioRegistry,
+    custom_template: Option<&TemplateScenario>,
+    request: &SimulationRequest,
+    product: &str,
+    product_context: &str,
+) -> Option<Box<dyn Scenario>> {
+    let product_request = SimulationRequest {
+        product_name: ProductNames::One(product.to_string()),
+        ..request.clone()
+    };
+    let scenario: Box<dyn Scenario> = match custom_template {
+        Some(template) => Box::new(template.clone().with_context(product, product_context)),
+        None => scenarios
+            .create(&request.scenario, &product_request, product_context)
+            .or_else(|| scenarios.create("product_launch", &product_request, product_context))?,
+    };
+    Some(LocalizedScenario::wrap(scenario, request.language.as_deref()))
+}
+
+// The swarm a job's agents answer in, with the request's sentiment lexicon
+fn job_swarm(agents: Vec<Agent>, request: &SimulationRequest) -> AgentSwarm {
+    // English keywords say little about e.g. Tamil answers: there only the user lexicon counts
+    let lexicon = if request.language.as_deref().is_some_and(|l| !is_english(l)) {
+        SentimentLexicon::default().user_only()
+    } else {
+        SentimentLexicon::default()
+    };
+    AgentSwarm {
+        agents: Arc::new(std::sync::Mutex::new(agents)),
+        results: Arc::new(std::sync::Mutex::new(Vec::new())),
+        lexicon,
+    }
+    .with_lexicon(SentimentLexicon::hinglish())
+    .with_lexicon(request.sentiment_lexicon.clone().unwrap_or_default())
+}
+
+// The context block every scenario is built with: product, facts and research voices
+fn build_enriched_context(product: &str, context: &str, fact_sheet: &str, research_data: &[String], pack_text: &str) -> String {
+    let voices_text = if research_data.is_empty() {
+        "No direct consumer discussions found online.".to_string()
+    } else {
+        research_data.join("\n---\n")
+    };
+
+    let mut enriched = format!(
+        "PRODUCT: {}\nUSER CONTEXT: {}\n\n--- FACTUAL SPECS (Open Database) ---\n{}\n\n--- MARKET RESEARCH (Reddit Voices & Wiki) ---\n{}", 
+        product, 
+        context, 
+        fact_sheet, 
+        voices_text
+    );
+    // What the uploaded packaging claims, so it can be checked against the specs above
+    if !pack_text.trim().is_empty() {
+        enriched.push_str(&format!("\n\n--- ON-PACK CLAIMS (read from the uploaded image) ---\n{}", pack_text.trim()));
+    }
+    enriched
+}
+
+// A running job's id plus the handles its background task updates it through
+#[derive(Clone)]
+struct JobHandle {
+    id: String,
+    brain: Arc<AgentBrain>,
+    jobs: Arc<DashMap<String, JobStatus>>,
+    streams: Arc<DashMap<String, watch::Sender<JobStatus>>>,
+    research_cache: Arc<ResearchCache>,
+}
+
+impl JobHandle {
+    // Applies `change` to the job (if it still exists), then publishes it
+    fn update(&self, change: impl FnOnce(&mut JobStatus)) {
+        if let Some(mut job) = self.jobs.get_mut(&self.id) {
+            change(&mut job);
+        }
+        publish(&self.jobs, &self.streams, &self.id);
+    }
+
+    // Progress only moves forward, even if a late callback reports an earlier step
+    fn advance(&self, progress: f32) {
+        self.update(|job| job.progress = job.progress.max(progress));
+    }
+
+    fn fail(&self) {
+        self.update(|job| job.status = "failed".to_string());
+    }
+}
+
+// What steps 0-2 of a job hand to the execution step
+struct PreparedJob {
+    swarm: Arc<AgentSwarm>,
+    enriched_context: String,
+    pack_text: String, // Reused by the other products of a product line
+    memory_store: PersonaMemoryStore,
+}
+
+// --- STEPS 0-2 of a job: research, on-pack text, personas (or a saved panel), remembered sessions ---
+// Blocking (brain calls): start_simulation runs it on the blocking pool.
+fn prepare_job(job: &JobHandle, request: &SimulationRequest, reused_agents: Option<Vec<Agent>>) -> PreparedJob {
+    let brain = &job.brain;
+    let product = request.product_name.primary();
+
+    // --- STEP 0: FEDERATED INTELLIGENCE GATHERING (The Triad) ---
+    // For a product line, the first product's research seeds the shared persona panel.
+    let (research_data, fact_sheet) = gather_research(brain, &job.research_cache, &product, &request.context, &request.research_sources());
+
+    // Packaging photo: read its text once; every product pass reuses it
+    let pack_text = match request.image_data.as_deref() {
+        Some(image) => {
+            let text = brain.ocr(image);
+            println!("🔎 API: Read {} chars of on-pack text for Job {}", text.len(), job.id);
+            job.update(|status| status.pack_text = Some(text.clone()).filter(|t| !t.trim().is_empty()));
+            text
+        }
+        None => String::new(),
+    };
+
+    let enriched_context = build_enriched_context(&product, &request.context, &fact_sheet, &research_data, &pack_text);
+
+    // --- STEP 1: DOPPELGÄNGER GENERATION (or reuse of a saved panel) ---
+    let (mut agents, archetype_mix) = match reused_agents {
+        Some(agents) => (agents, None),
+        None => {
+            let (agents, mix) = PersonaGenerator::generate_from_voices(
+                request.agent_count,
+                &request.target_audience,
+                research_data,
+                brain,
+                temperature(request.persona_temperature, DEFAULT_PERSONA_TEMPERATURE),
+                request.archetype_weights.as_ref(),
+                request.seed,
+            );
+            if let Some(name) = request.save_personas.as_deref() {
+                if let Err(e) = PersonaGenerator::save_panel(&agents, name) {
+                    println!("❌ API Error: Could not save persona panel '{}': {}", name, e);
+                }
+            }
+            (agents, Some(mix))
+        }
+    };
+
+    // A request-level skill list overrides what the generator (or saved panel) assigned
+    if let Some(skills) = &request.skills {
+        for agent in agents.iter_mut() {
+            agent.skills = skills.clone();
+        }
+    }
+
+    // Baseline arm: blank-slate agents show how much of the verdict the personas themselves drive
+    if let Some(fraction) = request.control_fraction {
+        let controls = PersonaGenerator::assign_control_cohort(&mut agents, fraction);
+        println!("🧪 API: {} of {} agents run as the control cohort", controls, agents.len());
+    }
+
+    // Personas seen in earlier jobs get their memories back (longitudinal studies)
+    let memory_store = PersonaMemoryStore::from_env();
+    let remembered = memory_store.load_into(&agents);
+    if remembered > 0 {
+        println!("🧠 MEMORY: {} personas remembered previous sessions", remembered);
+    }
+
+    job.update(|status| {
+        status.agents = agents.clone();
+        status.archetype_mix = archetype_mix;
+        status.progress = 0.25;
+    });
+
+    let swarm = Arc::new(job_swarm(agents, request));
+    PreparedJob { swarm, enriched_context, pack_text, memory_store }
+}
+
+// --- STEP 3 (single-turn scenarios): one pass of the panel per product ---
+// Returns each pass's (product, enriched context), or None when the scenario isn't registered.
+// Blocking: start_simulation runs it on the blocking pool.
+fn run_product_passes(
+    job: &JobHandle,
+    swarm: &Arc<AgentSwarm>,
+    request: &SimulationRequest,
+    scenarios: &ScenarioRegistry,
+    custom_template: Option<&TemplateScenario>,
+    prepared_context: &str,
+    pack_text: &str,
+) -> Option<Vec<(String, String)>> {
+    let brain = &job.brain;
+    let research_sources = request.research_sources();
+    // A product line runs every product through the same panel, one pass per product
+    let products = request.product_name.all();
+    let passes = products.len() as f32;
+    let mut product_contexts = Vec::with_capacity(products.len());
+    for (idx, product) in products.iter().enumerate() {
+        let product_context = if idx == 0 {
+            prepared_context.to_string()
+        } else {
+            let (voices, facts) = gather_research(brain, &job.research_cache, product, &request.context, &research_sources);
+            build_enriched_context(product, &request.context, &facts, &voices, pack_text)
+        };
+        product_contexts.push((product.clone(), product_context.clone()));
+
+        let scenario: Box<dyn Scenario> = match scenario_for_product(scenarios, custom_template, request, product, &product_context) {
+            Some(scenario) => scenario,
+            None => {
+                println!("❌ API: Scenario '{}' is not registered. Aborting Job {}", request.scenario, job.id);
+                return None;
+            }
+        };
+
+        // Call the updated main logic
+        // We pass the product name as the context string so agents know what to query in the DB
+        let settings = RunSettings {
+            product_context: product.clone(),
+            temperature: temperature(request.response_temperature, crate::DEFAULT_RESPONSE_TEMPERATURE),
+            max_tokens: request.max_tokens,
+            response_limit: request.response_limit(),
+            image_data: request.image_data.clone(),
+            pdf_data: request.pdf_data.clone(),
+        };
+        let on_progress = |done: usize, total: usize| {
+            job.advance(0.25 + 0.75 * ((idx as f32 + done as f32 / total as f32) / passes));
+        };
+        if request.engine.as_deref() == Some("batch") {
+            let batch_size = request.batch_size.unwrap_or(DEFAULT_BATCH_SIZE);
+            crate::run_simulation_batch(brain, swarm, scenario.as_ref(), settings, batch_size, on_progress);
+        } else {
+            crate::run_simulation_parallel(brain, swarm, scenario.as_ref(), settings, on_progress);
+        }
+    }
+    Some(product_contexts)
+}
+
+// --- STEPS 4-5 of a job: persist memories, archive, complete ---
+// Blocking: start_simulation runs it on the blocking pool.
+fn finish_job(job: &JobHandle, swarm: &AgentSwarm, memory_store: &PersonaMemoryStore) {
+    // 4. Persist what each persona now remembers
+    let saved = memory_store.save_all(&swarm.get_agents());
+    println!("💾 MEMORY: Persisted {} persona memory streams", saved);
+
+    // 5. Complete Job (flagging mode collapse: many near-identical answers)
+    let results = swarm.get_results();
+    let participants: Vec<&SimulationResult> = results.iter().filter(|r| !FocusGroupSession::is_moderator(r) && !AnalystEngine::is_pipe_error(r)).collect();
+    let duplicate_ratio = AgentSwarm::duplicate_ratio(&participants);
+    if duplicate_ratio >= LOW_DIVERSITY_RATIO {
+        println!("⚠️ API: Job {} has {:.0}% near-duplicate responses (low diversity)", job.id, duplicate_ratio * 100.0);
+    }
+    // Optional on-disk archive of every finished run
+    if let Ok(dir) = std::env::var("ORACULUM_EXPORT_DIR") {
+        match Reporter::export_all(&dir, &job.id, &swarm.get_agents(), &results) {
+            Ok(paths) => println!("🗄️ API: Job {} archived to {}", job.id, paths.dir.display()),
+            Err(e) => println!("⚠️ API: Could not archive job {}: {}", job.id, e),
+        }
+    }
+    swarm.refresh_agent_stats();
+    job.update(|status| {
+        status.agents = swarm.get_agents();
+        status.timing = TimingSummary::from_results(&results);
+        status.verdicts = VerdictDistribution::from_results(&results);
+        status.results = results;
+        status.duplicate_ratio = Some(duplicate_ratio);
+        status.status = "completed".to_string();
+        status.progress = 1.0;
+    });
+}
+
 // POST /api/simulate
 pub async fn start_simulation(
     data: web::Data<AppState>,
     req: web::Json<SimulationRequest>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
+    // Reject bad input up-front, before any job is created
+    if let Err(e) = req.validate(&data.limits) {
+        return Err(ApiError::InvalidRequest(e));
+    }
+
+    // Each job is dozens of brain calls: protect the worker from a client loop.
+    // Taking a permit (not counting "processing" jobs) keeps concurrent requests from both passing.
+    let Ok(slot) = data.job_slots.clone().try_acquire_owned() else {
+        println!("🚦 API: Rejecting simulation, {} jobs already processing", data.limits.max_active_jobs);
+        return Err(ApiError::TooManyJobs(data.limits.max_active_jobs));
+    };
+
+    let custom_template = match req.custom_template.as_deref().map(TemplateScenario::parse).transpose() {
+        Ok(template) => template,
+        Err(e) => return Err(ApiError::InvalidRequest(e)),
+    };
+
+    // Load a reused persona panel up-front so a missing panel / size mismatch is a 400, not a failed job
+    let reused_agents = match req.reuse_personas.as_deref() {
+        Some(name) => match PersonaGenerator::load_panel(name) {
+            Ok(agents) if agents.len() == req.agent_count => Some(agents),
+            Ok(agents) => {
+                return Err(ApiError::InvalidRequest(format!(
+                    "Saved persona set has {} agents but agent_count is {}",
+                    agents.len(),
+                    req.agent_count
+                )))
+            }
+            Err(_) => return Err(ApiError::InvalidRequest(format!("Could not load persona panel '{}'", name))),
+        },
+        None => None,
+    };
+
     let job_id = Uuid::new_v4().to_string();
     let brain = data.brain.clone();
     let jobs = data.jobs.clone();
+    let streams = data.streams.clone();
+    let questions = data.questions.clone();
+    let scenarios = data.scenarios.clone();
+    let research_cache = data.research_cache.clone();
+    let reruns = data.reruns.clone();
 
     // Create initial empty job state
     let initial_status = JobStatus {
         id: job_id.clone(),
         status: "processing".to_string(),
         progress: 0.0,
+        scenario: req.scenario.clone(),
+        created_at: AgentSwarm::get_timestamp(),
         agents: Vec::new(),
         results: Vec::new(),
+        archetype_mix: None,
+        duplicate_ratio: None,
+        pack_text: None,
+        timing: None,
+        verdicts: None,
+        version: 0,
     };
+    let (stream_tx, _) = watch::channel(initial_status.clone());
+    streams.insert(job_id.clone(), stream_tx);
     jobs.insert(job_id.clone(), initial_status);
+    // Focus groups accept follow-up questions over /api/ws while they run
+    if req.scenario == "focus_group" {
+        let rounds = FocusGroupSession::new(3).with_phases(req.phases.clone().unwrap_or_default()).rounds;
+        questions.insert(job_id.clone(), InjectedQuestions::for_rounds(rounds));
+    }
 
-    // Prepare variables for thread
-    let job_id_clone = job_id.clone();
-    let req_scenario = req.scenario.clone();
-    let req_count = req.agent_count;
-    let req_target = req.target_audience.clone();
-    let req_product = req.product_name.clone();
-    let req_context = req.context.clone();
-    let req_image = req.image_data.clone(); 
-    let req_pdf = req.pdf_data.clone(); 
-
-    // SPAWN THREAD
-    thread::spawn(move || {
-        println!("🚀 API: Starting Job {} [Scenario: {}]", job_id_clone, req_scenario);
-
-        // --- STEP 0: FEDERATED INTELLIGENCE GATHERING (The Triad) ---
-        println!("🕵️ SCOUT: Initiating Federated Research (Reddit + Wiki)...");
-        let research_data = brain.research(&req_product, &req_context);
-        
-        // --- STEP 0.5: MEMORY PRIMING (The "Self-Healing" Trigger) ---
-        // We query the new memory system once here.
-        // If the topic is new, the Python worker will go online, fetch data, and save it to LanceDB.
-        // This ensures that when the 25 agents run in parallel later, they hit the Local Cache, not the Web.
-        println!("🧠 MEMORY: Priming Cognitive Graph for '{}'...", req_product);
-        let _ = brain.query_memory(&req_product); 
+    let request = req.into_inner();
+    let job = JobHandle { id: job_id.clone(), brain, jobs, streams, research_cache };
 
-        println!("📦 SCOUT: Fetching Product Specifications...");
-        let fact_sheet = brain.get_facts(&req_product);
+    // The job runs on the server's runtime; its synchronous brain work goes to the blocking pool
+    actix_web::rt::spawn(async move {
+        // Released when this task ends, whether the job completed, failed or panicked
+        let _slot = slot;
+        println!("🚀 API: Starting Job {} [Scenario: {}]", job.id, request.scenario);
 
-        let voices_text = if research_data.is_empty() {
-            "No direct consumer discussions found online.".to_string()
-        } else {
-            research_data.join("\n---\n")
+        // --- STEPS 0-2: research, on-pack text, personas ---
+        let prepared = {
+            let (job, request) = (job.clone(), request.clone());
+            tokio::task::spawn_blocking(move || prepare_job(&job, &request, reused_agents)).await
+        };
+        let Ok(PreparedJob { swarm, enriched_context, pack_text, memory_store }) = prepared else {
+            println!("❌ API: Setup of Job {} failed", job.id);
+            questions.remove(&job.id);
+            job.fail();
+            return;
         };
-
-        let enriched_context = format!(
-            "PRODUCT: {}\nUSER CONTEXT: {}\n\n--- FACTUAL SPECS (Open Database) ---\n{}\n\n--- MARKET RESEARCH (Reddit Voices & Wiki) ---\n{}", 
-            req_product, 
-            req_context, 
-            fact_sheet, 
-            voices_text
-        );
-
-        // --- STEP 1: DOPPELGÄNGER GENERATION ---
-        let agents = PersonaGenerator::generate_from_voices(req_count, &req_target, research_data, &brain);
-        
-        if let Some(mut job) = jobs.get_mut(&job_id_clone) {
-            job.agents = agents.clone();
-            job.progress = 0.25; 
-        }
-
-        let swarm = Arc::new(AgentSwarm {
-            agents: Arc::new(std::sync::Mutex::new(agents)),
-            results: Arc::new(std::sync::Mutex::new(Vec::new())),
-        });
 
         // 3. EXECUTION BRANCHING
-        if req_scenario == "focus_group" {
+        let executed = if request.scenario == "focus_group" {
             // --- ASYNC DEBATE MODE (Blackboard Architecture) ---
-            
-            // Create a temporary Tokio runtime for the async debate execution
-            let rt = tokio::runtime::Runtime::new().unwrap();
-            
-            let debate_results = rt.block_on(async {
-                // Call the new async blackboard engine
-                // We pass enriched_context so the agents know the full picture (Wiki + Reddit)
-                crate::focus_group::FocusGroupSession::run_debate(
-                    &brain, 
-                    &swarm.get_agents(), 
-                    &enriched_context, 
-                    3 // 3 Rounds
-                ).await
-            });
-
-            // Store results in the Swarm & Job Store
-            for res in debate_results {
-                swarm.add_result(res);
-            }
-            
-            // Update Job Progress
-            if let Some(mut job) = jobs.get_mut(&job_id_clone) {
-                 job.results = swarm.get_results(); 
-                 job.progress = 0.90;
+            // We pass enriched_context so the agents know the full picture (Wiki + Reddit)
+            let mut session = FocusGroupSession::new(3) // 3 Rounds
+                .with_moderator(request.moderator.unwrap_or(false))
+                .with_seed(request.seed)
+                .with_reply_targeting(request.reply_targeting.unwrap_or(false))
+                .with_reflection(request.reflection_threshold)
+                .with_history_window(request.history_window)
+                .with_devils_advocates(request.devils_advocates.unwrap_or(0), request.advocate_intensity.unwrap_or_default())
+                .with_phases(request.phases.clone().unwrap_or_default())
+                .with_attachments(request.image_data.clone(), request.pdf_data.clone());
+            if let Some(queue) = questions.get(&job.id) {
+                session = session.with_question_queue(queue.clone());
             }
+            session.lexicon = swarm.lexicon.clone();
+            let rounds = session.rounds;
+            let debate_results = session
+                .run_debate(&job.brain, &swarm.get_agents(), &enriched_context, |round, round_results| {
+                    // Live transcript: append this round's messages as soon as they exist
+                    job.update(|status| {
+                        status.results.extend_from_slice(round_results);
+                        status.progress = status.progress.max(0.25 + 0.75 * (round as f32 / rounds as f32));
+                    });
+                })
+                .await;
+
+            // Questions can't be injected once the debate is over
+            questions.remove(&job.id);
 
+            let (brain, swarm, remember) = (job.brain.clone(), swarm.clone(), memory_store.is_enabled());
+            let product = request.product_name.primary();
+            tokio::task::spawn_blocking(move || {
+                // Each participant remembers where they landed (persisted below with the other memories)
+                if remember {
+                    let remembered = FocusGroupSession::remember_final_positions(&brain, &swarm.get_agents(), &debate_results, &product);
+                    println!("🧠 MEMORY: {} participants reflected on their final position", remembered);
+                }
+
+                // Store results in the Swarm
+                for mut res in debate_results {
+                    res.product = Some(product.clone());
+                    swarm.add_result(res);
+                }
+            })
+            .await
+            .is_ok()
         } else {
             // --- STANDARD PARALLEL MODE (Now with Skills) ---
-            let scenario: Box<dyn Scenario> = match req_scenario.as_str() {
-                "creative_test" => Box::new(crate::scenarios::CreativeTestScenario::new(
-                    &req_product, 
-                    &enriched_context,
-                    "Ad Campaign"
-                )),
-                "ab_messaging" => Box::new(crate::scenarios::ABMessagingScenario::new(
-                    &req_product, 
-                    &enriched_context,
-                    "Brand Positioning"
-                )),
-                "cx_flow" => Box::new(crate::scenarios::CXFlowScenario::new(
-                    "consideration", 
-                    &format!("{} - {}", req_product, enriched_context)
-                )),
-                _ => Box::new(crate::scenarios::ProductLaunchScenario::new(
-                    &req_product,
-                    "Consumer Product",
-                    vec![&enriched_context]
-                )),
-            };
-
-            // Call the updated main logic
-            // We pass 'req_product' as the context string so agents know what to query in the DB
-            crate::run_simulation_parallel(&brain, &swarm, &scenario, req_image, req_pdf, req_product.clone());
+            let (job, swarm, request) = (job.clone(), swarm.clone(), request.clone());
+            tokio::task::spawn_blocking(move || {
+                let product_contexts = run_product_passes(&job, &swarm, &request, &scenarios, custom_template.as_ref(), &enriched_context, &pack_text)?;
+                // Kept so single agents can be regenerated later (POST .../rerun)
+                remember_rerun(&reruns, &job.id, request, product_contexts);
+                Some(())
+            })
+            .await
+            .is_ok_and(|done| done.is_some())
+        };
+        if !executed {
+            job.fail();
+            return;
         }
-        
-        // 5. Complete Job
-        if let Some(mut job) = jobs.get_mut(&job_id_clone) {
-            job.results = swarm.get_results();
-            job.status = "completed".to_string();
-            job.progress = 1.0;
+
+        // 4-5. Persist memories, archive and complete
+        let finished = {
+            let job = job.clone();
+            tokio::task::spawn_blocking(move || finish_job(&job, &swarm, &memory_store)).await
+        };
+        match finished {
+            Ok(()) => println!("✅ API: Job {} Finished", job.id),
+            Err(e) => {
+                println!("❌ API: Job {} failed while finishing: {}", job.id, e);
+                job.fail();
+            }
         }
-        println!("✅ API: Job {} Finished", job_id_clone);
     });
 
-    HttpResponse::Ok().json(JobCreatedResponse {
+    Ok(HttpResponse::Ok().json(JobCreatedResponse {
         job_id,
         status: "processing".to_string(),
+    }))
+}
+
+// Personas used by /api/preview when no saved panel is given (generating them costs inference)
+const PREVIEW_MAX_AGENTS: usize = 5;
+
+// POST /api/preview
+// The parallel runner's scenario prompts, with no inference and no skill execution (each agent's
+// skills are listed instead).
+// Research is only used if it's already cached; personas come from `reuse_personas` or a fixed panel.
+pub async fn preview_prompts(
+    data: web::Data<AppState>,
+    req: web::Json<SimulationRequest>,
+) -> Result<HttpResponse, ApiError> {
+    if let Err(e) = req.validate(&data.limits) {
+        return Err(ApiError::InvalidRequest(e));
+    }
+    if req.scenario == "focus_group" {
+        return Err(ApiError::InvalidRequest(
+            "Preview covers single-turn scenarios; focus_group prompts depend on earlier rounds".to_string(),
+        ));
+    }
+
+    let custom_template = match req.custom_template.as_deref().map(TemplateScenario::parse).transpose() {
+        Ok(template) => template,
+        Err(e) => return Err(ApiError::InvalidRequest(e)),
+    };
+
+    let mut agents = match req.reuse_personas.as_deref() {
+        Some(name) => PersonaGenerator::load_panel(name)
+            .map_err(|_| ApiError::InvalidRequest(format!("Could not load persona panel '{}'", name)))?,
+        None => PersonaGenerator::fallback_panel(req.agent_count.min(PREVIEW_MAX_AGENTS), &req.target_audience, req.seed),
+    };
+    if let Some(skills) = &req.skills {
+        for agent in agents.iter_mut() {
+            agent.skills = skills.clone();
+        }
+    }
+    if let Some(fraction) = req.control_fraction {
+        PersonaGenerator::assign_control_cohort(&mut agents, fraction);
+    }
+
+    // A product line previews its first product
+    let product = req.product_name.primary();
+    let cached = data.research_cache.get(&product, &req.context, &req.research_sources());
+    let research_cached = cached.is_some();
+    let (voices, fact_sheet) = match cached {
+        Some(cached) => (cached.voices, cached.fact_sheet),
+        None => (Vec::new(), "(Preview: research not run)".to_string()),
+    };
+    let enriched_context = build_enriched_context(&product, &req.context, &fact_sheet, &voices, "");
+
+    let scenario: Box<dyn Scenario> = match custom_template {
+        Some(template) => Box::new(template.with_context(&product, &enriched_context)),
+        None => data.scenarios
+            .create(&req.scenario, &req, &enriched_context)
+            .or_else(|| data.scenarios.create("product_launch", &req, &enriched_context))
+            .ok_or_else(|| ApiError::InvalidRequest(format!("Scenario '{}' is not registered", req.scenario)))?,
+    };
+    let scenario = LocalizedScenario::wrap(scenario, req.language.as_deref());
+
+    // Skills have side effects (web crawls, worker calls), so they are listed rather than run
+    let prompts = agents
+        .iter()
+        .map(|agent| PromptPreview {
+            agent_id: agent.id,
+            agent_name: agent.name.clone(),
+            prompt: crate::base_agent_prompt(agent, scenario.as_ref()),
+            skills: agent.skills.clone(),
+        })
+        .collect();
+    let scenario_key = scenario.scenario_key().to_string();
+
+    Ok(HttpResponse::Ok().json(PreviewResponse { scenario: scenario_key, research_cached, prompts }))
+}
+
+// GET /api/health
+pub async fn health(data: web::Data<AppState>) -> impl Responder {
+    let brain_ready = data.brain.run_blocking(|brain| brain.ping()).await.unwrap_or(false);
+    let active_jobs = data.jobs.iter().filter(|job| job.status == "processing").count();
+
+    HttpResponse::Ok().json(HealthResponse {
+        status: if brain_ready { "ok" } else { "degraded" }.to_string(),
+        brain_ready,
+        active_jobs,
     })
 }
 
-// GET /api/status/{job_id}
+// GET /api/status/{job_id}?results_offset=0&results_limit=50
 pub async fn get_job_status(
+    http: HttpRequest,
     data: web::Data<AppState>,
     path: web::Path<String>,
-) -> impl Responder {
+    query: web::Query<JobStatusQuery>,
+) -> Result<HttpResponse, ApiError> {
     let job_id = path.into_inner();
     
-    if let Some(job) = data.jobs.get(&job_id) {
-        HttpResponse::Ok().json(job.clone())
-    } else {
-        HttpResponse::NotFound().body("Job not found")
+    match data.jobs.get(&job_id) {
+        Some(job) => {
+            let total_results = job.results.len();
+            let start = query.results_offset.unwrap_or(0).min(total_results);
+            let end = query.results_limit.map_or(total_results, |limit| start.saturating_add(limit).min(total_results));
+
+            // Cheap polling: nothing published since the client's copy of this page -> 304, no body
+            let etag = format!("\"{}-{}-{}\"", job.version, start, end);
+            let unchanged = http
+                .headers()
+                .get(header::IF_NONE_MATCH)
+                .and_then(|v| v.to_str().ok())
+                .is_some_and(|tags| tags.split(',').any(|t| {
+                    let t = t.trim();
+                    t == "*" || t.trim_start_matches("W/") == etag
+                }));
+            if unchanged {
+                return Ok(HttpResponse::NotModified().insert_header((header::ETAG, etag)).finish());
+            }
+
+            // Slice before cloning, so large jobs don't copy every result per poll
+            let page = JobStatus {
+                id: job.id.clone(),
+                status: job.status.clone(),
+                progress: job.progress,
+                scenario: job.scenario.clone(),
+                created_at: job.created_at.clone(),
+                agents: job.agents.clone(),
+                results: job.results[start..end].to_vec(),
+                archetype_mix: job.archetype_mix.clone(),
+                duplicate_ratio: job.duplicate_ratio,
+                pack_text: job.pack_text.clone(),
+                timing: job.timing.clone(),
+                verdicts: job.verdicts.clone(),
+                version: job.version,
+            };
+            Ok(HttpResponse::Ok()
+                .insert_header((header::ETAG, etag))
+                .json(JobStatusPage { job: page, total_results }))
+        }
+        None => Err(ApiError::JobNotFound),
     }
 }
 
-// POST /api/analyze
+// GET /api/jobs?status=completed
+pub async fn list_jobs(
+    data: web::Data<AppState>,
+    query: web::Query<JobListQuery>,
+) -> impl Responder {
+    let mut summaries: Vec<JobSummary> = data.jobs
+        .iter()
+        .filter(|entry| query.status.as_deref().is_none_or(|s| entry.status == s))
+        .map(|entry| JobSummary::from(entry.value()))
+        .collect();
+
+    // Newest first (timestamps are ISO-8601, so lexical order == chronological order)
+    summaries.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+
+    HttpResponse::Ok().json(summaries)
+}
+
+// GET /api/scenarios
+// Scenario keys accepted by /api/simulate, with display names (custom templates are
+// selected via `custom_template`, not a key, so they aren't listed).
+pub async fn list_scenarios(data: web::Data<AppState>) -> impl Responder {
+    let mut scenarios = data.scenarios.describe();
+    // Focus groups are a separate execution mode, not a registry entry
+    scenarios.push(("focus_group".to_string(), FocusGroupSession::NAME.to_string()));
+
+    let infos: Vec<ScenarioInfo> = scenarios
+        .into_iter()
+        .map(|(key, name)| ScenarioInfo { key, name })
+        .collect();
+    HttpResponse::Ok().json(infos)
+}
+
+// GET /api/skills
+pub async fn list_skills() -> impl Responder {
+    let registry = SkillRegistry::new();
+    let mut ids = registry.list_available();
+    ids.sort();
+
+    let infos: Vec<SkillInfo> = ids
+        .into_iter()
+        .filter_map(|id| {
+            let skill = registry.get(&id)?;
+            Some(SkillInfo { name: skill.name(), description: skill.description(), id })
+        })
+        .collect();
+    HttpResponse::Ok().json(infos)
+}
+
+// GET /api/stream/{job_id}
+// Server-Sent Events: emits a `progress` event with the JobStatus snapshot whenever it changes,
+// then a final `completed`, `failed` or `interrupted` event (named after the status) before closing.
+pub async fn stream_job(
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let job_id = path.into_inner();
+
+    let rx = if let Some(tx) = data.streams.get(&job_id) {
+        tx.subscribe()
+    } else if let Some(job) = data.jobs.get(&job_id) {
+        // Job restored from disk (no live channel): replay the stored snapshot once and close.
+        watch::channel(job.clone()).1
+    } else {
+        return Err(ApiError::JobNotFound);
+    };
+
+    // State: (receiver, is_first_event, finished)
+    let events = stream::unfold((rx, true, false), |(mut rx, first, finished)| async move {
+        if finished {
+            return None;
+        }
+        // The first event is the current snapshot; afterwards we wait for the next change.
+        if !first && rx.changed().await.is_err() {
+            return None;
+        }
+
+        let job = rx.borrow_and_update().clone();
+        let is_terminal = job.status != "processing";
+        let event_name = match job.status.as_str() {
+            "processing" => "progress",
+            terminal => terminal,
+        };
+        let payload = serde_json::to_string(&job).unwrap_or_default();
+        let frame = web::Bytes::from(format!("event: {}\ndata: {}\n\n", event_name, payload));
+
+        Some((Ok::<_, actix_web::Error>(frame), (rx, false, is_terminal)))
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .streaming(events))
+}
+
+// Inbound WebSocket message: a follow-up question for the next focus-group round
+#[derive(Deserialize)]
+struct SocketCommand {
+    inject_question: String,
+}
+
+// Outbound WebSocket frame: job state plus the messages produced since the previous frame
+#[derive(Serialize)]
+struct SocketFrame<'a> {
+    status: &'a str,
+    progress: f32,
+    messages: &'a [SimulationResult],
+}
+
+// GET /api/ws/{job_id}
+// Bidirectional live transcript: pushes new messages as they are produced and accepts
+// {"inject_question": "..."}, which a running focus group asks at the start of its next round.
+// Replies {"queued", "position"}, or {"error"} once no remaining round is free to ask it.
+// The watch channel only keeps the latest snapshot, so a slow client skips intermediate
+// states but still receives every message (frames carry everything since the last one).
+pub async fn job_socket(
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+    req: HttpRequest,
+    body: web::Payload,
+) -> Result<HttpResponse, ApiError> {
+    let job_id = path.into_inner();
+
+    let mut updates = if let Some(tx) = data.streams.get(&job_id) {
+        tx.subscribe()
+    } else if let Some(job) = data.jobs.get(&job_id) {
+        // Job restored from disk (no live channel): send the stored snapshot once
+        watch::channel(job.clone()).1
+    } else {
+        return Err(ApiError::JobNotFound);
+    };
+
+    let (response, mut session, mut inbound) = match actix_ws::handle(&req, body) {
+        Ok(parts) => parts,
+        Err(e) => return Ok(e.error_response()),
+    };
+    let questions = data.questions.clone();
+
+    actix_web::rt::spawn(async move {
+        let mut sent = 0;
+        let mut pending_frame = true; // The first frame is the current snapshot
+
+        loop {
+            if pending_frame {
+                let (frame, finished) = {
+                    let job = updates.borrow_and_update();
+                    let new_messages = job.results.get(sent..).unwrap_or_default();
+                    sent = job.results.len();
+                    let frame = SocketFrame { status: &job.status, progress: job.progress, messages: new_messages };
+                    (serde_json::to_string(&frame).unwrap_or_default(), job.status != "processing")
+                };
+                if session.text(frame).await.is_err() || finished {
+                    break;
+                }
+                pending_frame = false;
+            }
+
+            tokio::select! {
+                changed = updates.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+                    pending_frame = true;
+                }
+                message = inbound.next() => match message {
+                    Some(Ok(actix_ws::Message::Text(text))) => {
+                        let reply = match serde_json::from_str::<SocketCommand>(&text) {
+                            Ok(command) if !command.inject_question.trim().is_empty() => {
+                                let question = command.inject_question.trim().to_string();
+                                let queued = match questions.get(&job_id) {
+                                    Some(queue) => queue.lock().map_err(|_| "Question queue unavailable".to_string()).and_then(|mut queue| queue.push(question.clone())),
+                                    None => Err("This job is not a running focus group".to_string()),
+                                };
+                                match queued {
+                                    Ok(position) => serde_json::json!({ "queued": question, "position": position }),
+                                    Err(e) => serde_json::json!({ "error": e }),
+                                }
+                            }
+                            _ => serde_json::json!({ "error": "Expected {\"inject_question\": \"...\"}" }),
+                        };
+                        if session.text(reply.to_string()).await.is_err() {
+                            break;
+                        }
+                    }
+                    Some(Ok(actix_ws::Message::Ping(bytes))) => {
+                        if session.pong(&bytes).await.is_err() {
+                            break;
+                        }
+                    }
+                    Some(Ok(actix_ws::Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => {}
+                },
+            }
+        }
+
+        let _ = session.close(None).await;
+    });
+
+    Ok(response)
+}
+
+// POST /api/analyze?format=json
 pub async fn analyze_job(
     data: web::Data<AppState>,
     req: web::Json<AnalyzeRequest>,
-) -> impl Responder {
+    query: web::Query<AnalyzeQuery>,
+) -> Result<HttpResponse, ApiError> {
     let job_id = req.job_id.clone();
     println!("📊 API: Analysis requested for Job {}", job_id);
 
     // 1. Retrieve Job Data safely
-    let (results, scenario_key) = if let Some(job) = data.jobs.get(&job_id) {
+    if let Some(Err(e)) = req.skepticism_weights.as_ref().map(SkepticismWeights::validate) {
+        return Err(ApiError::InvalidRequest(e));
+    }
+    let (results, scenario_key, agents) = if let Some(job) = data.jobs.get(&job_id) {
         let results = job.results.clone();
         let scenario = results.first().map(|r| r.scenario.clone()).unwrap_or_else(|| "unknown".to_string());
-        (results, scenario)
+        (results, scenario, job.agents.clone())
     } else {
-        return HttpResponse::NotFound().body("Job not found");
+        return Err(ApiError::JobNotFound);
     };
 
     if results.is_empty() {
-        return HttpResponse::BadRequest().body("No results available to analyze");
+        return Err(ApiError::NoResults("No results available to analyze".to_string()));
     }
 
     // 2. Call the Analyst Engine
-    let brain = data.brain.clone();
-    let report_result = web::block(move || {
-        AnalystEngine::generate_report(&brain, &scenario_key, &results)
+    let structured = query.format.as_deref() == Some("json");
+    let temperature = temperature(req.analysis_temperature, DEFAULT_ANALYSIS_TEMPERATURE);
+    let lens = AnalystLens {
+        role: req.analyst_role.as_ref().map(|r| r.trim().to_string()).filter(|r| !r.is_empty()),
+        questions: req.focus_questions.iter().flatten().map(|q| q.trim().to_string()).filter(|q| !q.is_empty()).collect(),
+        agent_weights: req.skepticism_weights.map(|weights| weights.agent_weights(&agents)),
+    };
+    let report_result = data.brain.run_blocking(move |brain| {
+        if structured {
+            AnalysisOutput::Structured(AnalystEngine::generate_report_structured(brain, &scenario_key, &results, temperature, &lens))
+        } else {
+            AnalysisOutput::Markdown(AnalystEngine::generate_report(brain, &scenario_key, &results, temperature, &lens))
+        }
     }).await;
 
     // 3. Return the Report
     match report_result {
-        Ok(report) => HttpResponse::Ok().json(AnalysisResponse { report }),
+        Ok(AnalysisOutput::Markdown(report)) => Ok(HttpResponse::Ok().json(AnalysisResponse { report })),
+        Ok(AnalysisOutput::Structured(report)) => Ok(HttpResponse::Ok().json(report)),
         Err(e) => {
             println!("❌ API Error: Analysis generation failed: {}", e);
-            HttpResponse::InternalServerError().body("Failed to generate report")
+            Err(ApiError::BrainUnavailable("Failed to generate report".to_string()))
+        }
+    }
+}
+
+// POST /api/compare
+pub async fn compare_jobs(
+    data: web::Data<AppState>,
+    req: web::Json<CompareRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let CompareRequest { job_id_a, job_id_b } = req.into_inner();
+    println!("📊 API: Comparison requested for Jobs {} vs {}", job_id_a, job_id_b);
+
+    let results_for = |id: &str| data.jobs.get(id).map(|job| job.results.clone());
+    let (results_a, results_b) = match (results_for(&job_id_a), results_for(&job_id_b)) {
+        (Some(a), Some(b)) => (a, b),
+        _ => return Err(ApiError::JobNotFound),
+    };
+
+    if results_a.is_empty() || results_b.is_empty() {
+        return Err(ApiError::NoResults("Both jobs need results before they can be compared".to_string()));
+    }
+
+    let comparison = data.brain.run_blocking(move |brain| {
+        AnalystEngine::compare_reports(brain, &job_id_a, &results_a, &job_id_b, &results_b)
+    }).await;
+
+    match comparison {
+        Ok(report) => Ok(HttpResponse::Ok().json(report)),
+        Err(e) => {
+            println!("❌ API Error: Comparison failed: {}", e);
+            Err(ApiError::BrainUnavailable("Failed to generate comparison".to_string()))
+        }
+    }
+}
+
+// io::Write adapter that forwards each chunk to an HTTP streaming body.
+// `blocking_send` on a bounded channel gives us backpressure: a slow client slows the writer down.
+struct ChannelWriter {
+    tx: mpsc::Sender<web::Bytes>,
+}
+
+impl io::Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.tx
+            .blocking_send(web::Bytes::copy_from_slice(buf))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "client disconnected"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// GET /api/jobs/{job_id}/agents/{agent_id}/memory
+// `memory` is #[serde(skip)], so this is the only way to see what a persona remembers.
+// Jobs restored from disk lost their live streams; those fall back to the persisted one.
+pub async fn get_agent_memory(
+    data: web::Data<AppState>,
+    path: web::Path<(String, u32)>,
+) -> Result<HttpResponse, ApiError> {
+    let (job_id, agent_id) = path.into_inner();
+
+    let agent = match data.jobs.get(&job_id) {
+        Some(job) => job.agents.iter().find(|a| a.id == agent_id).cloned().ok_or(ApiError::AgentNotFound)?,
+        None => return Err(ApiError::JobNotFound),
+    };
+
+    let live = agent
+        .memory
+        .lock()
+        .map(|stream| stream.clone())
+        .map_err(|_| ApiError::Internal("Agent memory is unavailable".to_string()))?;
+    let (stream, persisted) = if live.memories.is_empty() {
+        match PersonaMemoryStore::from_env().load(&agent) {
+            Some(stream) => (stream, true),
+            None => (live, false),
+        }
+    } else {
+        (live, false)
+    };
+
+    let memories = stream
+        .ranked()
+        .into_iter()
+        .map(|(mem, score)| MemoryEntry {
+            id: mem.id.clone(),
+            content: mem.content.clone(),
+            memory_type: mem.memory_type.clone(),
+            importance: mem.importance,
+            score,
+            creation_timestamp: mem.creation_timestamp.to_rfc3339(),
+            last_accessed: mem.last_accessed.to_rfc3339(),
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(AgentMemoryResponse {
+        agent_id,
+        agent_name: agent.name.clone(),
+        persisted,
+        memories,
+    }))
+}
+
+// POST /api/jobs/{job_id}/agents/{agent_id}/rerun[?product=...]
+// Regenerates one agent's answer through the parallel engine (same prompt building, skills,
+// retries, scoring) and swaps it into the job. Returns the new result.
+pub async fn rerun_agent(
+    data: web::Data<AppState>,
+    path: web::Path<(String, u32)>,
+    query: web::Query<RerunQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let (job_id, agent_id) = path.into_inner();
+
+    let agent = match data.jobs.get(&job_id) {
+        Some(job) => {
+            if job.status != "completed" {
+                return Err(ApiError::InvalidRequest(format!("Job is {}; only completed jobs can be rerun", job.status)));
+            }
+            if job.scenario == "focus_group" {
+                return Err(ApiError::InvalidRequest(
+                    "Focus-group turns depend on the rest of the room; rerun covers single-turn scenarios".to_string(),
+                ));
+            }
+            job.agents.iter().find(|a| a.id == agent_id).cloned().ok_or(ApiError::AgentNotFound)?
+        }
+        None => return Err(ApiError::JobNotFound),
+    };
+    let RerunContext { request, product_contexts, .. } = data.reruns.get(&job_id).map(|c| c.clone()).ok_or_else(|| {
+        ApiError::InvalidRequest(format!(
+            "This job ran before the last server restart or is older than the last {} jobs, so its prompt context is gone",
+            MAX_RERUN_CONTEXTS
+        ))
+    })?;
+
+    let (product, product_context) = match query.product.as_deref() {
+        Some(name) => product_contexts
+            .iter()
+            .find(|(product, _)| product == name)
+            .cloned()
+            .ok_or_else(|| ApiError::InvalidRequest(format!("Job has no product '{}'", name)))?,
+        None if product_contexts.len() == 1 => product_contexts[0].clone(),
+        None => {
+            let names: Vec<&str> = product_contexts.iter().map(|(product, _)| product.as_str()).collect();
+            return Err(ApiError::InvalidRequest(format!("Job covered several products; pass ?product= one of: {}", names.join(", "))));
+        }
+    };
+
+    let custom_template = request.custom_template.as_deref().map(TemplateScenario::parse).transpose().map_err(ApiError::InvalidRequest)?;
+    let scenario = scenario_for_product(&data.scenarios, custom_template.as_ref(), &request, &product, &product_context)
+        .ok_or_else(|| ApiError::Internal(format!("Scenario '{}' is not registered", request.scenario)))?;
+
+    println!("🔁 API: Rerunning agent {} of Job {} ({})", agent_id, job_id, product);
+    let rerun = data.brain.run_blocking(move |brain| {
+        let swarm = Arc::new(job_swarm(vec![agent], &request));
+        let settings = RunSettings {
+            product_context: product,
+            temperature: temperature(request.response_temperature, crate::DEFAULT_RESPONSE_TEMPERATURE),
+            max_tokens: request.max_tokens,
+            response_limit: request.response_limit(),
+            image_data: request.image_data.clone(),
+            pdf_data: request.pdf_data.clone(),
+        };
+        crate::run_simulation_parallel(brain, &swarm, scenario.as_ref(), settings, |_, _| {});
+        swarm.get_results().into_iter().next()
+    }).await;
+    let result = match rerun {
+        Ok(Some(result)) => result,
+        Ok(None) => return Err(ApiError::Internal("The rerun produced no result".to_string())),
+        Err(e) => {
+            println!("❌ API Error: Rerun failed: {}", e);
+            return Err(ApiError::BrainUnavailable("Failed to rerun the agent".to_string()));
+        }
+    };
+
+    // Swap the answer in and refresh what was derived from it
+    match data.jobs.get_mut(&job_id) {
+        Some(mut job) => {
+            let slot = job.results.iter().position(|r| r.agent_id == agent_id && r.product == result.product);
+            match slot {
+                Some(idx) => job.results[idx] = result.clone(),
+                None => job.results.push(result.clone()),
+            }
+
+            let scores: Vec<f32> = job.results.iter().filter(|r| r.agent_id == agent_id && !r.failed).map(|r| r.sentiment_score).collect();
+            if let Some(agent) = job.agents.iter_mut().find(|a| a.id == agent_id) {
+                if !scores.is_empty() {
+                    agent.update_sentiment(scores.iter().sum::<f32>() / scores.len() as f32, scores.len() as u32);
+                }
+            }
+
+            let participants: Vec<&SimulationResult> = job.results.iter().filter(|r| !FocusGroupSession::is_moderator(r) && !AnalystEngine::is_pipe_error(r)).collect();
+            job.duplicate_ratio = Some(AgentSwarm::duplicate_ratio(&participants));
+            job.timing = TimingSummary::from_results(&job.results);
+            job.verdicts = VerdictDistribution::from_results(&job.results);
+        }
+        None => return Err(ApiError::JobNotFound),
+    }
+    publish(&data.jobs, &data.streams, &job_id);
+
+    Ok(HttpResponse::Ok().json(result))
+}
+
+// GET /api/export/{job_id}.csv
+// Streams the job's results as a CSV attachment, row by row.
+pub async fn export_job_csv(
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let job_id = path.into_inner();
+
+    let results = if let Some(job) = data.jobs.get(&job_id) {
+        job.results.clone()
+    } else {
+        return Err(ApiError::JobNotFound);
+    };
+
+    // The csv writer is blocking, so it runs on its own thread and feeds the response body
+    let (tx, rx) = mpsc::channel::<web::Bytes>(16);
+    thread::spawn(move || {
+        if let Err(e) = Reporter::export_csv_to_writer(ChannelWriter { tx }, &results) {
+            println!("❌ API Error: CSV export aborted: {}", e);
+        }
+    });
+
+    let body = stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|chunk| (Ok::<_, actix_web::Error>(chunk), rx))
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/csv; charset=utf-8")
+        .insert_header(("Content-Disposition", format!("attachment; filename=\"{}.csv\"", job_id)))
+        .streaming(body))
+}
+
+// GET /api/export/{job_id}?format=csv|json|jsonl
+// Runs the Reporter into an in-memory buffer and returns it as a download.
+pub async fn export_job(
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+    query: web::Query<ExportQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let job_id = path.into_inner();
+
+    let (agents, results) = if let Some(job) = data.jobs.get(&job_id) {
+        (job.agents.clone(), job.results.clone())
+    } else {
+        return Err(ApiError::JobNotFound);
+    };
+
+    if results.is_empty() {
+        return Err(ApiError::NoResults("No results available to export".to_string()));
+    }
+
+    let format = query.format.as_deref().unwrap_or("csv");
+    let mut buffer: Vec<u8> = Vec::new();
+    let (written, content_type) = match format {
+        "csv" => (Reporter::export_csv_to_writer(&mut buffer, &results), "text/csv; charset=utf-8"),
+        "json" => (Reporter::export_json_to_writer(&mut buffer, &agents, &results), "application/json"),
+        "jsonl" => (Reporter::export_jsonl_to_writer(&mut buffer, &results), "application/x-ndjson"),
+        other => return Err(ApiError::InvalidRequest(format!("Unsupported export format '{}' (use csv, json or jsonl)", other))),
+    };
+
+    match written {
+        Ok(()) => Ok(HttpResponse::Ok()
+            .content_type(content_type)
+            .insert_header(("Content-Disposition", format!("attachment; filename=\"{}.{}\"", job_id, format)))
+            .body(buffer)),
+        Err(e) => {
+            println!("❌ API Error: Export failed for Job {}: {}", job_id, e);
+            Err(ApiError::Internal("Failed to export results".to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{test, App};
+
+    fn test_state() -> web::Data<AppState> {
+        let limits = SimulationLimits::from_env();
+        web::Data::new(AppState {
+            brain: Arc::new(AgentBrain::demo()),
+            jobs: Arc::new(DashMap::new()),
+            streams: Arc::new(DashMap::new()),
+            questions: Arc::new(DashMap::new()),
+            scenarios: Arc::new(ScenarioRegistry::with_builtins()),
+            research_cache: Arc::new(ResearchCache::from_env()),
+            reruns: Arc::new(DashMap::new()),
+            limits,
+            job_slots: Arc::new(tokio::sync::Semaphore::new(limits.max_active_jobs)),
+        })
+    }
+
+    // Polls the job until it leaves "processing" (the demo brain answers instantly)
+    async fn wait_for(state: &web::Data<AppState>, job_id: &str) -> JobStatus {
+        for _ in 0..200 {
+            if let Some(job) = state.jobs.get(job_id).filter(|job| job.status != "processing") {
+                return job.clone();
+            }
+            actix_web::rt::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        panic!("job {} did not finish", job_id);
+    }
+
+    #[actix_web::test]
+    async fn simulations_run_on_the_server_runtime() {
+        std::env::set_var("ORACULUM_MEMORY_STORE", "off");
+        let state = test_state();
+        let app = test::init_service(App::new().app_data(state.clone()).route("/api/simulate", web::post().to(start_simulation))).await;
+
+        for scenario in ["product_launch", "focus_group"] {
+            let body = serde_json::json!({
+                "scenario": scenario,
+                "product_name": "Masala Oats",
+                "context": "Rs 120 for 400g",
+                "target_audience": "Urban students",
+                "agent_count": 3,
+            });
+            let resp: serde_json::Value = test::call_and_read_body_json(&app, test::TestRequest::post().uri("/api/simulate").set_json(body).to_request()).await;
+            let job = wait_for(&state, resp["job_id"].as_str().unwrap()).await;
+
+            assert_eq!(job.status, "completed", "{} job", scenario);
+            assert_eq!(job.agents.len(), 3);
+            assert!(!job.results.is_empty());
         }
     }
-}
\ No newline at end of file
+
+    #[actix_web::test]
+    async fn product_lines_are_capped() {
+        let request = |products: usize| -> SimulationRequest {
+            let names: Vec<String> = (1..=products).map(|i| format!("Masala Oats {}", i)).collect();
+            serde_json::from_value(serde_json::json!({
+                "scenario": "product_launch",
+                "product_name": names,
+                "context": "Rs 120 for 400g",
+                "target_audience": "Urban students",
+                "agent_count": 200,
+            }))
+            .unwrap()
+        };
+        let limits = SimulationLimits::from_env();
+
+        assert!(request(MAX_PRODUCTS).validate(&limits).is_ok());
+        assert!(request(MAX_PRODUCTS + 1).validate(&limits).unwrap_err().contains("limit is 10"));
+    }
+
+    #[actix_web::test]
+    async fn job_slots_are_returned_when_jobs_end() {
+        std::env::set_var("ORACULUM_MEMORY_STORE", "off");
+        let state = test_state();
+        let app = test::init_service(App::new().app_data(state.clone()).route("/api/simulate", web::post().to(start_simulation))).await;
+        let simulate = || {
+            let body = serde_json::json!({
+                "scenario": "product_launch",
+                "product_name": "Masala Oats",
+                "context": "Rs 120 for 400g",
+                "target_audience": "Urban students",
+                "agent_count": 2,
+            });
+            test::TestRequest::post().uri("/api/simulate").set_json(body).to_request()
+        };
+        let max = state.limits.max_active_jobs;
+
+        // Every slot taken: the next request is turned away
+        let held = state.job_slots.clone().try_acquire_many_owned(max as u32).unwrap();
+        assert_eq!(test::call_service(&app, simulate()).await.status(), StatusCode::TOO_MANY_REQUESTS);
+        drop(held);
+
+        let resp: serde_json::Value = test::call_and_read_body_json(&app, simulate()).await;
+        assert_eq!(wait_for(&state, resp["job_id"].as_str().unwrap()).await.status, "completed");
+        actix_web::rt::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert_eq!(state.job_slots.available_permits(), max);
+    }
+
+    #[actix_web::test]
+    async fn non_english_jobs_score_with_the_user_lexicon_only() {
+        let request = |language: Option<&str>| -> SimulationRequest {
+            serde_json::from_value(serde_json::json!({
+                "scenario": "product_launch",
+                "product_name": "Masala Oats",
+                "context": "Rs 120 for 400g",
+                "target_audience": "Urban students",
+                "agent_count": 1,
+                "language": language,
+            }))
+            .unwrap()
+        };
+
+        assert!(job_swarm(Vec::new(), &request(None)).lexicon.score("great value, love it") > 0.0);
+        assert!(job_swarm(Vec::new(), &request(Some("en-IN"))).lexicon.score("great value, love it") > 0.0);
+        assert_eq!(job_swarm(Vec::new(), &request(Some("Tamil"))).lexicon.score("great value, love it"), 0.0);
+    }
+
+    #[actix_web::test]
+    async fn rerun_contexts_keep_only_the_newest_jobs() {
+        let reruns = DashMap::new();
+        for i in 0..MAX_RERUN_CONTEXTS + 3 {
+            remember_rerun(&reruns, &format!("job-{}", i), SimulationRequest::default(), Vec::new());
+            std::thread::sleep(std::time::Duration::from_millis(1)); // Distinct stored_at per job
+        }
+
+        assert_eq!(reruns.len(), MAX_RERUN_CONTEXTS);
+        assert!((0..3).all(|i| !reruns.contains_key(&format!("job-{}", i))));
+        assert!(reruns.contains_key(&format!("job-{}", MAX_RERUN_CONTEXTS + 2)));
+    }
+
+    #[actix_web::test]
+    async fn malformed_requests_get_structured_errors() {
+        let state = test_state();
+        let app = test::init_service(
+            App::new()
+                .app_data(state.clone())
+                .app_data(json_config(1024))
+                .app_data(query_config())
+                .route("/api/simulate", web::post().to(start_simulation))
+                .route("/api/status/{id}", web::get().to(get_job_status)),
+        )
+        .await;
+
+        let requests = [
+            test::TestRequest::post().uri("/api/simulate").insert_header(("content-type", "application/json")).set_payload("{\"scenario\": ").to_request(),
+            test::TestRequest::post().uri("/api/simulate").set_json(serde_json::json!({ "scenario": "product_launch", "agent_count": "three" })).to_request(),
+            test::TestRequest::get().uri("/api/status/job-1?results_limit=ten").to_request(),
+        ];
+        for request in requests {
+            let resp = test::call_service(&app, request).await;
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+            let body: serde_json::Value = test::read_body_json(resp).await;
+            assert_eq!(body["error_code"], "INVALID_REQUEST");
+        }
+    }
+
+    #[actix_web::test]
+    async fn skill_listing_describes_each_skill() {
+        let app = test::init_service(App::new().route("/api/skills", web::get().to(list_skills))).await;
+        let skills: Vec<serde_json::Value> = test::call_and_read_body_json(&app, test::TestRequest::get().uri("/api/skills").to_request()).await;
+
+        let web_scout = skills.iter().find(|s| s["id"] == "web_scout").expect("web_scout listed");
+        assert_eq!(web_scout["name"], "web_scout");
+        assert!(!web_scout["description"].as_str().unwrap_or_default().is_empty());
+    }
+
+    #[actix_web::test]
+    async fn preview_lists_skills_without_running_them() {
+        let state = test_state();
+        let app = test::init_service(App::new().app_data(state.clone()).route("/api/preview", web::post().to(preview_prompts))).await;
+        let body = serde_json::json!({
+            "scenario": "product_launch",
+            "product_name": "Masala Oats",
+            "context": "check https://example.com/oats",
+            "target_audience": "Urban students",
+            "agent_count": 2,
+            "skills": ["web_scout", "fact_check"],
+        });
+        let preview: serde_json::Value = test::call_and_read_body_json(&app, test::TestRequest::post().uri("/api/preview").set_json(body).to_request()).await;
+
+        let prompts = preview["prompts"].as_array().unwrap();
+        assert_eq!(prompts.len(), 2);
+        for prompt in prompts {
+            assert_eq!(prompt["skills"], serde_json::json!(["web_scout", "fact_check"]));
+            assert!(!prompt["prompt"].as_str().unwrap().contains("REAL-WORLD CONTEXT ACQUIRED"));
+        }
+    }
+}
@@ -1,177 +1,142 @@
 // src/batch_inference.rs
 // UPDATED: Compatible with Python Bridge & MAGMA Architecture
+// Reached via SimulationRequest `engine: "batch"` (see run_simulation_batch in main.rs).
+// Prompts, retries and thought/verdict parsing are shared with run_simulation_parallel.
 
-use crate::agent_swarm::{Agent, SimulationResult, AgentSwarm};
+use crate::agent_swarm::{Agent, AgentSwarm, SimulationResult};
 use crate::brain::AgentBrain;
 use crate::scenarios::Scenario;
+use crate::skills::SkillCache;
+use crate::{build_agent_prompt, finish_agent_turn, inference_retries, retry_inference, AgentPrompt};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use rayon::prelude::*;
 
+// Prompts per worker round-trip when the request doesn't set `batch_size`
+pub const DEFAULT_BATCH_SIZE: usize = 8;
+pub const MAX_BATCH_SIZE: usize = 64;
+
 #[derive(Clone, Debug)]
 pub struct InferenceRequest {
     pub agent_id: u32,
-    pub agent_name: String,
-    pub agent_role: String,
-    pub agent_demographic: String, // <--- Added to track demographic info
-    pub scenario_key: String,      // <--- Added for accurate categorization
-    pub prompt: String,
+    pub prompt: AgentPrompt, // Scenario prompt + skill knowledge, as built for the parallel engine
+    pub prepare_ms: u64,     // Time spent building it (skills included)
 }
 
 #[derive(Clone, Debug)]
 pub struct InferenceResult {
     pub agent_id: u32,
-    pub response: String,
-    pub thought_process: Option<String>, // <--- Added to capture hidden thoughts
-    pub sentiment: String,
-    pub category: String,
+    pub raw_response: String, // Unparsed: finish_agent_turn splits thought vs verdict
+    pub inference_ms: u64,    // Chunk round-trip until this reply was final (retries included)
 }
 
 pub struct BatchInferenceEngine {
     pub brain: Arc<AgentBrain>,
     pub batch_size: usize,
+    pub temperature: f32,
+    pub max_tokens: usize,
 }
 
 impl BatchInferenceEngine {
     pub fn new(brain: Arc<AgentBrain>, batch_size: usize) -> Self {
         Self {
             brain,
-            batch_size,
+            batch_size: batch_size.max(1),
+            temperature: 0.7,
+            max_tokens: 300,
         }
     }
 
-    /// Process requests: parallel batch processing using the Python Brain
-    pub fn process_requests(&self, requests: Vec<InferenceRequest>) -> Vec<InferenceResult> {
+    /// Process requests: parallel batch processing using the Python Brain.
+    /// Each chunk is one `generate_batch` call (canned replies under ORACULUM_DEMO); replies that
+    /// come back as errors are retried one prompt at a time. `on_progress(done, total)` fires per chunk.
+    pub fn process_requests<F>(&self, requests: &[InferenceRequest], on_progress: F) -> Vec<InferenceResult>
+    where
+        F: Fn(usize, usize) + Sync,
+    {
         let total = requests.len();
-        let counter = Arc::new(std::sync::Mutex::new(0usize));
+        let finished = AtomicUsize::new(0);
+        let retries = inference_retries();
 
         requests
             .par_chunks(self.batch_size)
             .flat_map(|chunk| {
-                chunk
-                    .iter()
-                    .map(|req| {
-                        // Progress counter
-                        let mut count = counter.lock().unwrap();
-                        *count += 1;
-                        let current = *count;
-                        drop(count);
-
-                        print!(
-                            "\r[{}/{}] {} ({})",
-                            current, total, req.agent_name, req.agent_role
-                        );
-                        std::io::Write::flush(&mut std::io::stdout()).ok();
-
-                        // 1. Generate via Python Bridge
-                        // Using a reasonable token limit for batch responses (e.g., 300)
-                        let raw_response = self.brain.generate(&req.prompt, 300, None, None);
-
-                        // 2. Parse Thought vs Verdict (MAGMA Architecture)
-                        // This ensures we capture the "Hidden Thought" even in batch mode
-                        let (thought, content) = if let Some(verdict_start) = raw_response.find("[Verdict]") {
-                            let verdict = raw_response[verdict_start + 9..].trim().to_string();
-                            let thought_text = if let Some(think_start) = raw_response.find("[Thinking]") {
-                                Some(raw_response[think_start + 10..verdict_start].trim().to_string())
-                            } else {
-                                None
-                            };
-                            (thought_text, verdict)
-                        } else {
-                            // Fallback cleanup if tags are missing
-                            let clean = raw_response
-                                .replace("[Thinking]", "")
-                                .replace("[Verdict]", "")
-                                .trim()
-                                .to_string();
-                            (None, clean)
-                        };
-
-                        let sentiment = AgentSwarm::sentiment_from_response(&content);
-                        let category = AgentSwarm::extract_category(&content, &req.scenario_key);
+                // 1. Generate the whole chunk via one Python Bridge call
+                let started = Instant::now();
+                let prompts = chunk.iter().map(|req| req.prompt.prompt.clone()).collect();
+                let raw_responses = self.brain.generate_batch(prompts, self.max_tokens, self.temperature);
 
+                let results = chunk
+                    .iter()
+                    .zip(raw_responses)
+                    .map(|(req, raw_response)| {
+                        // 2. Same retry policy as the parallel engine
+                        let generate = || self.brain.generate(&req.prompt.prompt, self.max_tokens, None, None, self.temperature);
+                        let raw_response = retry_inference(req.agent_id, raw_response, retries, generate);
                         InferenceResult {
                             agent_id: req.agent_id,
-                            response: content,
-                            thought_process: thought,
-                            sentiment,
-                            category,
+                            raw_response,
+                            inference_ms: started.elapsed().as_millis() as u64,
                         }
                     })
-                    .collect::<Vec<_>>()
-            })
-            .collect()
-    }
+                    .collect::<Vec<_>>();
 
-    /// Demo mode: instant simulated responses with realistic variation
-    pub fn process_requests_demo(&self, requests: Vec<InferenceRequest>) -> Vec<InferenceResult> {
-        let responses = vec![
-            "The product is good quality affordable.",
-            "Like this very much premium value.",
-            "Not worth expensive price point.",
-            "Good product affordable quick delivery.",
-            "Really great quality very good.",
-            "Bad quality not worth buying.",
-            "Like the product affordable price.",
-            "Product is decent very good.",
-            "Good quality worth the price.",
-            "Premium product excellent very worth.",
-        ];
+                // Progress counter (advances a whole chunk at a time)
+                let done = finished.fetch_add(chunk.len(), Ordering::Relaxed) + chunk.len();
+                println!("   📦 [{}/{}] batch of {} agents", done, total, chunk.len());
+                on_progress(done, total);
 
-        requests
-            .iter()
-            .enumerate()
-            .map(|(idx, req)| {
-                let template = responses[idx % responses.len()];
-                InferenceResult {
-                    agent_id: req.agent_id,
-                    response: template.to_string(),
-                    thought_process: None,
-                    sentiment: AgentSwarm::sentiment_from_response(template),
-                    category: "product_quality".to_string(),
-                }
+                results
             })
             .collect()
     }
 }
 
-/// Convert agents to inference requests
+/// Build every agent's prompt (skills included) exactly as `run_simulation_parallel` does
 pub fn prepare_inference_requests(
+    brain: &Arc<AgentBrain>,
     agents: &[Agent],
-    scenario: &Box<dyn Scenario>,
+    scenario: &dyn Scenario,
+    product_context: &str,
+    skill_timeout: Duration,
+    skill_cache: &Arc<SkillCache>,
 ) -> Vec<InferenceRequest> {
     agents
-        .iter()
-        .map(|agent| InferenceRequest {
-            agent_id: agent.id,
-            agent_name: agent.name.clone(),
-            agent_role: agent.role.clone(),
-            agent_demographic: agent.demographic.clone(), // Capture demographic
-            scenario_key: scenario.scenario_key().to_string(), // Capture scenario key
-            prompt: scenario.generate_prompt(agent, None),
+        .par_iter()
+        .map(|agent| {
+            let started = Instant::now();
+            let prompt = build_agent_prompt(brain, agent, scenario, product_context, skill_timeout, skill_cache);
+            InferenceRequest {
+                agent_id: agent.id,
+                prompt,
+                prepare_ms: started.elapsed().as_millis() as u64,
+            }
         })
         .collect()
 }
 
-/// Convert inference results back to simulation results
+/// Convert inference results back to simulation results (parsing, sentiment, categories, memory)
 pub fn convert_to_simulation_results(
+    brain: &AgentBrain,
+    swarm: &AgentSwarm,
+    agents: &[Agent],
     requests: Vec<InferenceRequest>,
     results: Vec<InferenceResult>,
-    scenario_key: &str,
+    scenario: &dyn Scenario,
+    product_context: &str,
 ) -> Vec<SimulationResult> {
     results
-        .into_iter()
-        .zip(requests.into_iter())
-        .map(|(result, req)| SimulationResult {
-            agent_id: result.agent_id,
-            agent_role: req.agent_role,
-            agent_demographic: req.agent_demographic, // Pass through
-            scenario: scenario_key.to_string(),
-            timestamp: AgentSwarm::get_timestamp(),
-            prompt: req.prompt,
-            response: result.response,
-            thought_process: result.thought_process, // Pass through
-            sentiment: result.sentiment,
-            category: result.category,
+        .into_par_iter()
+        .zip(requests)
+        .filter_map(|(result, req)| {
+            let agent = agents.iter().find(|a| a.id == result.agent_id)?;
+            let mut turn = finish_agent_turn(brain, swarm, scenario, agent, req.prompt, &result.raw_response, product_context);
+            if let Some(timing) = turn.timing.as_mut() {
+                timing.inference_ms = result.inference_ms;
+                timing.total_ms = req.prepare_ms + result.inference_ms;
+            }
+            Some(turn)
         })
         .collect()
-}
\ No newline at end of file
+}
@@ -4,14 +4,98 @@
 // Fixes "Cannot drop runtime" panic by using a pure blocking client.
 
 use std::process::{Command, Stdio, Child};
+use std::fmt;
+use std::io::{BufRead, BufReader};
 use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::thread;
 use std::time::Duration;
 use serde::{Deserialize, Serialize};
-// Uses 'ureq' for safe blocking HTTP calls inside Async runtimes
-use ureq; 
+use serde::de::DeserializeOwned;
+use crate::demo_brain;
 
-const PYTHON_API_URL: &str = "http://127.0.0.1:8003";
+const PYTHON_HOST: &str = "http://127.0.0.1";
+const MAX_RESTARTS_PER_WORKER: usize = 3; // Stops a crash-looping worker from restarting forever
+// Health probes must answer fast, regardless of the (long) generation timeout
+const PING_TIMEOUT: Duration = Duration::from_secs(2);
+
+// How to launch and talk to the Python workers.
+// Defaults match the dev setup; `from_env` lets deployments override each field.
+#[derive(Clone, Debug)]
+pub struct BrainConfig {
+    pub python: String,        // ORACULUM_PYTHON
+    pub worker_script: String, // ORACULUM_WORKER_SCRIPT
+    pub workers: usize,        // ORACULUM_BRAIN_WORKERS
+    pub base_port: u16,        // Worker N listens on base_port + N
+    pub timeout: Duration,     // ORACULUM_BRAIN_TIMEOUT_SECS
+    pub demo: bool,            // ORACULUM_DEMO: no Python at all, canned replies (see demo_brain)
+}
+
+impl Default for BrainConfig {
+    fn default() -> Self {
+        Self {
+            python: "python3".to_string(),
+            worker_script: "python_bridge/inference_worker.py".to_string(),
+            workers: 1,
+            base_port: 8003,
+            timeout: Duration::from_secs(300), // 5 minutes for long inferences
+            demo: false,
+        }
+    }
+}
+
+impl BrainConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let env = |key: &str| std::env::var(key).ok().filter(|v| !v.trim().is_empty());
+
+        Self {
+            python: env("ORACULUM_PYTHON").unwrap_or(defaults.python),
+            worker_script: env("ORACULUM_WORKER_SCRIPT").unwrap_or(defaults.worker_script),
+            workers: env("ORACULUM_BRAIN_WORKERS")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.workers),
+            base_port: defaults.base_port,
+            timeout: env("ORACULUM_BRAIN_TIMEOUT_SECS")
+                .and_then(|v| v.parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.timeout),
+            demo: env("ORACULUM_DEMO")
+                .map(|v| matches!(v.trim().to_lowercase().as_str(), "1" | "true" | "yes"))
+                .unwrap_or(defaults.demo),
+        }
+    }
+}
+
+// Recoverable failures when talking to a Python worker
+#[derive(Debug)]
+pub enum BrainError {
+    Timeout(String),
+    Network(String),
+    Parse(String),
+    Task(String), // The blocking task behind an async call panicked or was cancelled
+}
+
+impl fmt::Display for BrainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BrainError::Timeout(e) => write!(f, "Timeout: {}", e),
+            BrainError::Network(e) => write!(f, "Network Error: {}", e),
+            BrainError::Parse(e) => write!(f, "JSON Parse Error: {}", e),
+            BrainError::Task(e) => write!(f, "Brain Task Error: {}", e),
+        }
+    }
+}
+
+impl BrainError {
+    /// True when `text` is one of the error strings `generate` returns in place of a reply
+    /// (a worker-side "Error: ..." or a `BrainError` rendered with `to_string`).
+    pub fn is_error_reply(text: &str) -> bool {
+        const PREFIXES: [&str; 6] = ["System Error:", "Error:", "Timeout:", "Network Error:", "JSON Parse Error:", "Brain Task Error:"];
+        let text = text.trim_start();
+        PREFIXES.iter().any(|p| text.starts_with(p))
+    }
+}
 
 #[derive(Serialize)]
 struct InferenceRequest {
@@ -30,42 +114,180 @@ struct InferenceResponse {
     message: Option<String>,
 }
 
+// One Python inference server (own process, own port, own model copy)
+struct Worker {
+    port: u16,
+    process: Mutex<Child>,
+    base_url: String,
+    in_flight: AtomicUsize,
+    // Cleared when a request times out or the connection drops.
+    // Unhealthy workers are skipped by dispatch while a healthy one exists.
+    healthy: AtomicBool,
+    // Bumped on every respawn, so concurrent callers that all saw the same
+    // crash only trigger ONE restart.
+    generation: AtomicUsize,
+    restarts: AtomicUsize,
+}
+
+// RAII marker: decrements the worker's in-flight counter when the request finishes
+struct Lease<'a> {
+    worker: &'a Worker,
+    generation: usize,
+}
+
+impl Drop for Lease<'_> {
+    fn drop(&mut self) {
+        self.worker.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+// A failed call, plus enough context to decide whether the worker should be recycled
+struct CallFailure<'a> {
+    error: BrainError,
+    lease: Option<Lease<'a>>, // Some(..) when the worker itself stopped answering
+}
+
 pub struct AgentBrain {
-    python_process: Arc<Mutex<Child>>,
+    config: BrainConfig,
+    workers: Vec<Worker>,
+    next_worker: AtomicUsize,
     // ureq uses an Agent to hold connection pools and config
     agent: ureq::Agent,
 }
 
 impl AgentBrain {
     pub fn new() -> Self {
-        println!("🧠 BRAIN: Initializing Neural Engine (HTTP Mode - Safe)...");
+        Self::from_config(BrainConfig::default())
+    }
+
+    /// Spawns a pool of `n` Python workers (ports 8003, 8004, ...).
+    /// Each worker loads its own model copy, so size the pool to the available (V)RAM.
+    pub fn with_workers(n: usize) -> Self {
+        Self::from_config(BrainConfig { workers: n, ..BrainConfig::default() })
+    }
+
+    /// Canned-reply brain (ORACULUM_DEMO) for tests: no Python, deterministic output.
+    #[cfg(test)]
+    pub fn demo() -> Self {
+        Self::from_config(BrainConfig { demo: true, ..BrainConfig::default() })
+    }
+
+    pub fn from_config(config: BrainConfig) -> Self {
+        if config.demo {
+            println!("🧠 BRAIN: DEMO MODE (ORACULUM_DEMO) - no Python workers, canned responses");
+            let agent = Self::build_agent(config.timeout);
+            return Self { config, workers: Vec::new(), next_worker: AtomicUsize::new(0), agent };
+        }
+
+        let n = config.workers.max(1);
+        println!("🧠 BRAIN: Initializing Neural Engine (HTTP Mode - Safe, {} worker(s))...", n);
+        println!("   └── Interpreter: {} | Script: {}", config.python, config.worker_script);
         
-        // 1. Spawn Python Server
-        let child = Command::new("python3")
-            .arg("python_bridge/inference_worker.py")
+        // 1. Spawn Python Servers (all at once, so model loading overlaps)
+        let mut children = Vec::with_capacity(n);
+        for i in 0..n {
+            let port = config.base_port + i as u16;
+            let child = Self::spawn_worker(&config, port).unwrap_or_else(|e| panic!(
+                "Failed to spawn Python worker '{} {}' ({}). Is the virtual env active? Run 'source .venv/bin/activate' or set ORACULUM_PYTHON / ORACULUM_WORKER_SCRIPT",
+                config.python, config.worker_script, e
+            ));
+            children.push((port, child));
+        }
+
+        // Create a persistent agent (keeps connections open)
+        let agent = Self::build_agent(config.timeout);
+
+        // 2. Ready-Handshake: every worker must pass its health check
+        let workers = children
+            .into_iter()
+            .map(|(port, child)| {
+                let base_url = format!("{}:{}", PYTHON_HOST, port);
+                if let Err(e) = Self::wait_until_ready(&agent, &base_url) {
+                    panic!("❌ {}", e);
+                }
+                Worker {
+                    port,
+                    process: Mutex::new(child),
+                    base_url,
+                    in_flight: AtomicUsize::new(0),
+                    healthy: AtomicBool::new(true),
+                    generation: AtomicUsize::new(0),
+                    restarts: AtomicUsize::new(0),
+                }
+            })
+            .collect();
+
+        Self {
+            config,
+            workers,
+            next_worker: AtomicUsize::new(0),
+            agent,
+        }
+    }
+
+    /// Overrides how long a single call may wait for the worker to reply
+    /// before giving up with `BrainError::Timeout` (default: `BrainConfig::timeout`).
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.config.timeout = timeout;
+        self.agent = Self::build_agent(timeout);
+        self
+    }
+
+    fn build_agent(read_timeout: Duration) -> ureq::Agent {
+        ureq::AgentBuilder::new()
+            .timeout_read(read_timeout)
+            .timeout_write(Duration::from_secs(10))
+            .build()
+    }
+
+    fn spawn_worker(config: &BrainConfig, port: u16) -> std::io::Result<Child> {
+        Command::new(&config.python)
+            .arg(&config.worker_script)
+            .arg("--port")
+            .arg(port.to_string())
             .stdout(Stdio::inherit()) 
             .stderr(Stdio::inherit())
             .spawn()
-            .expect("Failed to spawn Python worker. Is the virtual env active? Run 'source .venv/bin/activate'");
+    }
 
-        // Create a persistent agent (keeps connections open)
-        let agent = ureq::AgentBuilder::new()
-            .timeout_read(Duration::from_secs(300)) // 5 minute timeout for long inferences
-            .timeout_write(Duration::from_secs(10))
-            .build();
+    /// True if every worker answered its last request (no timeouts / dropped connections).
+    pub fn is_healthy(&self) -> bool {
+        self.workers.iter().all(|w| w.healthy.load(Ordering::SeqCst))
+    }
+
+    /// Liveness check: GET /health on every worker with a short timeout.
+    /// True if at least one worker answered (i.e. the brain can serve requests right now).
+    pub fn ping(&self) -> bool {
+        if self.config.demo {
+            return true;
+        }
+        self.workers.iter().any(|w| {
+            self.agent
+                .get(&format!("{}/health", w.base_url))
+                .timeout(PING_TIMEOUT)
+                .call()
+                .map(|resp| resp.status() == 200)
+                .unwrap_or(false)
+        })
+    }
+
+    /// Total number of worker respawns since startup.
+    pub fn restart_count(&self) -> usize {
+        self.workers.iter().map(|w| w.restarts.load(Ordering::SeqCst)).sum()
+    }
 
-        // 2. Wait for Health Check
-        println!("   └── Waiting for Python API to come online...");
+    fn wait_until_ready(agent: &ureq::Agent, base_url: &str) -> Result<(), String> {
+        println!("   └── Waiting for Python API at {} to come online...", base_url);
         let mut attempts = 0;
         loop {
-            if attempts > 60 { panic!("❌ Python Brain timed out (60s). Check logs."); }
+            if attempts > 60 { return Err(format!("Python Brain at {} timed out (60s). Check logs.", base_url)); }
             
             // Simple GET request using ureq
-            match agent.get(&format!("{}/health", PYTHON_API_URL)).call() {
+            match agent.get(&format!("{}/health", base_url)).call() {
                 Ok(resp) => {
                     if resp.status() == 200 {
-                        println!("✅ BRAIN: Connection Established on Port 8001");
-                        break;
+                        println!("✅ BRAIN: Connection Established on {}", base_url);
+                        return Ok(());
                     }
                 },
                 Err(_) => {
@@ -74,16 +296,142 @@ impl AgentBrain {
                 }
             }
         }
+    }
 
-        Self {
-            python_process: Arc::new(Mutex::new(child)),
-            agent,
+    // --- SELF-HEALING ---
+    // Kills (if still alive) and respawns a worker, replaying the ready-handshake.
+    // Returns true if the worker is usable afterwards (restarted by us OR by a concurrent caller).
+    fn restart_worker(&self, worker: &Worker, seen_generation: usize) -> bool {
+        let mut child = match worker.process.lock() {
+            Ok(child) => child,
+            Err(_) => return false,
+        };
+
+        // Someone else already recycled this worker while we waited for the lock
+        if worker.generation.load(Ordering::SeqCst) != seen_generation {
+            return worker.healthy.load(Ordering::SeqCst);
+        }
+
+        let restarts = worker.restarts.load(Ordering::SeqCst);
+        if restarts >= MAX_RESTARTS_PER_WORKER {
+            eprintln!("🧠 BRAIN: Worker {} exceeded {} restarts. Giving up on it.", worker.base_url, MAX_RESTARTS_PER_WORKER);
+            return false;
+        }
+
+        println!("🧠 BRAIN: Worker {} stopped responding. Restarting (attempt {}/{})...", worker.base_url, restarts + 1, MAX_RESTARTS_PER_WORKER);
+        let _ = child.kill();
+        let _ = child.wait();
+        worker.restarts.fetch_add(1, Ordering::SeqCst);
+
+        let new_child = match Self::spawn_worker(&self.config, worker.port) {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("🧠 BRAIN: Failed to respawn worker {}: {}", worker.base_url, e);
+                return false;
+            }
+        };
+        *child = new_child;
+        worker.generation.fetch_add(1, Ordering::SeqCst);
+
+        match Self::wait_until_ready(&self.agent, &worker.base_url) {
+            Ok(()) => {
+                worker.healthy.store(true, Ordering::SeqCst);
+                true
+            }
+            Err(e) => {
+                eprintln!("🧠 BRAIN: {}", e);
+                false
+            }
+        }
+    }
+
+    // --- WORKER DISPATCH ---
+    // Prefers the least-busy worker; the rotating start index breaks ties round-robin
+    // so idle workers are used evenly.
+    fn lease(&self) -> Lease<'_> {
+        let n = self.workers.len();
+        let start = self.next_worker.fetch_add(1, Ordering::Relaxed) % n;
+        let worker = (0..n)
+            .map(|offset| &self.workers[(start + offset) % n])
+            .min_by_key(|w| (!w.healthy.load(Ordering::SeqCst), w.in_flight.load(Ordering::SeqCst)))
+            .expect("AgentBrain always has at least one worker");
+
+        worker.in_flight.fetch_add(1, Ordering::SeqCst);
+        Lease { worker, generation: worker.generation.load(Ordering::SeqCst) }
+    }
+
+    // --- TRANSPORT ---
+    // Single choke point for every call to Python: picks a worker, applies the timeout,
+    // and flags the worker unhealthy if it stops answering.
+    // A crashed worker (connection refused/reset) is respawned and the call retried ONCE.
+    // A hung worker (timeout) is recycled too, but the call is not retried (it already waited the full timeout).
+    fn post_json<T: DeserializeOwned>(&self, path: &str, body: impl Serialize) -> Result<T, BrainError> {
+        let body = serde_json::to_value(body).map_err(|e| BrainError::Parse(e.to_string()))?;
+
+        match self.try_post_json(path, &body) {
+            Ok(value) => Ok(value),
+            Err(CallFailure { error, lease: Some(lease) }) => {
+                let worker = lease.worker;
+                let generation = lease.generation;
+                drop(lease);
+
+                let recovered = self.restart_worker(worker, generation);
+                match error {
+                    BrainError::Network(_) if recovered => {
+                        println!("🧠 BRAIN: Retrying {} after worker restart...", path);
+                        self.try_post_json(path, &body).map_err(|f| f.error)
+                    }
+                    _ => Err(error),
+                }
+            }
+            Err(failure) => Err(failure.error),
+        }
+    }
+
+    fn try_post_json<T: DeserializeOwned>(&self, path: &str, body: &serde_json::Value) -> Result<T, CallFailure<'_>> {
+        let lease = self.lease();
+        let url = format!("{}{}", lease.worker.base_url, path);
+
+        let response = match self.agent.post(&url).send_json(body) {
+            Ok(resp) => resp,
+            // Python answered with 4xx/5xx: the worker is alive, the request failed
+            Err(ureq::Error::Status(code, resp)) => {
+                let detail = resp.into_string().unwrap_or_default();
+                return Err(CallFailure {
+                    error: BrainError::Network(format!("{} returned {}: {}", url, code, detail)),
+                    lease: None,
+                });
+            }
+            Err(ureq::Error::Transport(t)) => {
+                lease.worker.healthy.store(false, Ordering::SeqCst);
+                let message = t.to_string();
+                let error = if message.contains("timed out") {
+                    BrainError::Timeout(format!("{} ({})", url, message))
+                } else {
+                    BrainError::Network(format!("{} ({})", url, message))
+                };
+                return Err(CallFailure { error, lease: Some(lease) });
+            }
+        };
+
+        // Reading the body can also hit the read timeout
+        match response.into_json::<T>() {
+            Ok(value) => Ok(value),
+            Err(e) if e.kind() == std::io::ErrorKind::TimedOut || e.kind() == std::io::ErrorKind::WouldBlock => {
+                lease.worker.healthy.store(false, Ordering::SeqCst);
+                Err(CallFailure { error: BrainError::Timeout(format!("{} ({})", url, e)), lease: Some(lease) })
+            }
+            Err(e) => Err(CallFailure { error: BrainError::Parse(e.to_string()), lease: None }),
         }
     }
 
     // --- API METHODS ---
 
     pub fn generate(&self, prompt: &str, max_tokens: usize, image_b64: Option<String>, pdf_b64: Option<String>, temp: f32) -> String {
+        if self.config.demo {
+            return demo_brain::generate(prompt);
+        }
+
         let req_body = InferenceRequest {
             prompt: prompt.to_string(),
             max_tokens,
@@ -92,42 +440,153 @@ impl AgentBrain {
             temperature: temp, 
         };
 
-        match self.agent.post(&format!("{}/generate", PYTHON_API_URL)).send_json(&req_body) {
-            Ok(resp) => {
-                // ureq returns a Reader, we convert to JSON
-                match resp.into_json::<InferenceResponse>() {
-                    Ok(json) => {
-                        if json.status == "success" {
-                            json.text.unwrap_or_default()
-                        } else {
-                            format!("Error: {}", json.message.unwrap_or("Unknown error".to_string()))
-                        }
-                    },
-                    Err(e) => format!("JSON Parse Error: {}", e)
+        match self.post_json::<InferenceResponse>("/generate", &req_body) {
+            Ok(json) => {
+                if json.status == "success" {
+                    json.text.unwrap_or_default()
+                } else {
+                    format!("Error: {}", json.message.unwrap_or("Unknown error".to_string()))
+                }
+            },
+            Err(e) => e.to_string()
+        }
+    }
+
+    /// Like `generate` (text only), but calls `on_token` with each chunk as the worker produces it.
+    /// Returns the full reply, so callers can stream to a UI and still keep the final text.
+    ///
+    /// Line protocol: POST /generate with `"stream": true` answers with newline-delimited JSON,
+    /// one object per line, read until a terminal line:
+    ///   {"token": "<chunk>"}              zero or more, in order
+    ///   {"done": true, "text": "<full>"}  terminal: the cleaned full reply
+    ///   {"error": "<message>"}            terminal: generation failed
+    pub fn generate_streaming(&self, prompt: &str, max_tokens: usize, temp: f32, mut on_token: impl FnMut(&str)) -> String {
+        if self.config.demo {
+            let text = demo_brain::generate(prompt);
+            on_token(&text);
+            return text;
+        }
+
+        #[derive(Deserialize)]
+        struct StreamLine {
+            token: Option<String>,
+            done: Option<bool>,
+            text: Option<String>,
+            error: Option<String>,
+        }
+
+        let body = serde_json::json!({
+            "prompt": prompt,
+            "max_tokens": max_tokens,
+            "temperature": temp,
+            "stream": true,
+        });
+
+        // Not routed through post_json: the body is read incrementally, and a half-streamed
+        // reply can't be retried transparently, so a dead worker is only recycled.
+        let lease = self.lease();
+        let url = format!("{}/generate", lease.worker.base_url);
+        let response = match self.agent.post(&url).send_json(body) {
+            Ok(resp) => resp,
+            Err(ureq::Error::Status(code, resp)) => {
+                let detail = resp.into_string().unwrap_or_default();
+                return BrainError::Network(format!("{} returned {}: {}", url, code, detail)).to_string();
+            }
+            Err(ureq::Error::Transport(t)) => {
+                lease.worker.healthy.store(false, Ordering::SeqCst);
+                let (worker, generation) = (lease.worker, lease.generation);
+                drop(lease);
+                self.restart_worker(worker, generation);
+                return BrainError::Network(format!("{} ({})", url, t)).to_string();
+            }
+        };
+
+        let mut accumulated = String::new();
+        for line in BufReader::new(response.into_reader()).lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    // Read timeout mid-stream: the worker hung
+                    lease.worker.healthy.store(false, Ordering::SeqCst);
+                    return BrainError::Timeout(format!("{} ({})", url, e)).to_string();
                 }
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let message: StreamLine = match serde_json::from_str(&line) {
+                Ok(message) => message,
+                Err(e) => return BrainError::Parse(e.to_string()).to_string(),
+            };
+            if let Some(error) = message.error {
+                return format!("Error: {}", error);
+            }
+            if let Some(token) = message.token {
+                on_token(&token);
+                accumulated.push_str(&token);
+            }
+            if message.done.unwrap_or(false) {
+                return message.text.unwrap_or(accumulated);
+            }
+        }
+
+        // Stream closed without a terminal line: keep whatever arrived
+        accumulated
+    }
+
+    /// Generates all `prompts` in one round-trip (text only), returning one reply per prompt.
+    /// Falls back to per-prompt `generate` if the worker rejects the batch (e.g. an older worker
+    /// without `/generate_batch`). A timeout is not retried prompt-by-prompt.
+    pub fn generate_batch(&self, prompts: Vec<String>, max_tokens: usize, temp: f32) -> Vec<String> {
+        #[derive(Deserialize)]
+        struct BatchResp {
+            status: String,
+            texts: Option<Vec<String>>,
+            message: Option<String>,
+        }
+
+        if prompts.is_empty() {
+            return Vec::new();
+        }
+        if self.config.demo {
+            return prompts.iter().map(|prompt| demo_brain::generate(prompt)).collect();
+        }
+
+        let body = serde_json::json!({ "prompts": prompts, "max_tokens": max_tokens, "temperature": temp });
+
+        match self.post_json::<BatchResp>("/generate_batch", body) {
+            Ok(json) if json.status == "success" => match json.texts {
+                Some(texts) if texts.len() == prompts.len() => return texts,
+                _ => eprintln!("🧠 BATCH: Worker returned a malformed batch, falling back to single prompts"),
             },
-            Err(e) => format!("Network Error: {}", e)
+            Ok(json) => eprintln!("🧠 BATCH: Worker rejected batch ({}), falling back to single prompts", json.message.unwrap_or_default()),
+            Err(e @ BrainError::Timeout(_)) => return vec![e.to_string(); prompts.len()],
+            Err(e) => eprintln!("🧠 BATCH: {}, falling back to single prompts", e),
         }
+
+        prompts
+            .iter()
+            .map(|prompt| self.generate(prompt, max_tokens, None, None, temp))
+            .collect()
     }
 
     pub fn query_memory(&self, query: &str) -> Vec<String> {
         #[derive(Deserialize)]
         struct QueryResp {
-            status: String,
+            #[allow(dead_code)] status: String,
             data: Option<Vec<String>>,
             #[allow(dead_code)] message: Option<String>,
         }
 
+        if self.config.demo {
+            return demo_brain::memory(query);
+        }
+
         let body = serde_json::json!({ "query": query });
 
-        match self.agent.post(&format!("{}/query_memory", PYTHON_API_URL)).send_json(body) {
-            Ok(resp) => {
-                if let Ok(json) = resp.into_json::<QueryResp>() {
-                    json.data.unwrap_or_default()
-                } else {
-                    Vec::new()
-                }
-            },
+        match self.post_json::<QueryResp>("/query_memory", body) {
+            Ok(json) => json.data.unwrap_or_default(),
             Err(e) => {
                 eprintln!("🧠 MEMORY NETWORK ERROR: {}", e);
                 Vec::new()
@@ -135,24 +594,23 @@ impl AgentBrain {
         }
     }
 
-    pub fn research(&self, product: &str, context: &str) -> Vec<String> {
+    /// Consumer voices for `product` from the `sources` ("reddit", "wiki") the job asked for.
+    pub fn research(&self, product: &str, context: &str, sources: &[&str]) -> Vec<String> {
         #[derive(Deserialize)]
         struct ResearchResp {
-            status: String,
+            #[allow(dead_code)] status: String,
             research_data: Option<Vec<String>>,
             #[allow(dead_code)] message: Option<String>,
         }
 
-        let body = serde_json::json!({ "product": product, "context": context });
+        if self.config.demo {
+            return demo_brain::research(product, sources);
+        }
 
-        match self.agent.post(&format!("{}/research", PYTHON_API_URL)).send_json(body) {
-            Ok(resp) => {
-                if let Ok(json) = resp.into_json::<ResearchResp>() {
-                    json.research_data.unwrap_or_default()
-                } else {
-                    Vec::new()
-                }
-            },
+        let body = serde_json::json!({ "product": product, "context": context, "sources": sources });
+
+        match self.post_json::<ResearchResp>("/research", body) {
+            Ok(json) => json.research_data.unwrap_or_default(),
             Err(e) => {
                 eprintln!("🧠 RESEARCH NETWORK ERROR: {}", e);
                 Vec::new()
@@ -160,37 +618,144 @@ impl AgentBrain {
         }
     }
 
+    /// Sentence embedding (MiniLM) of `text`. Empty when the worker can't embed it.
+    pub fn embed(&self, text: &str) -> Vec<f32> {
+        #[derive(Deserialize)]
+        struct EmbedResp {
+            #[allow(dead_code)] status: String,
+            embedding: Option<Vec<f32>>,
+            #[allow(dead_code)] message: Option<String>,
+        }
+
+        if self.config.demo {
+            return demo_brain::embed(text);
+        }
+
+        let body = serde_json::json!({ "text": text });
+
+        match self.post_json::<EmbedResp>("/embed", body) {
+            Ok(json) => json.embedding.unwrap_or_default(),
+            Err(e) => {
+                eprintln!("🧠 EMBED NETWORK ERROR: {}", e);
+                Vec::new()
+            }
+        }
+    }
+
     pub fn get_facts(&self, query: &str) -> String {
         #[derive(Deserialize)]
         struct FactResp {
-            status: String,
+            #[allow(dead_code)] status: String,
             fact_sheet: Option<String>,
             #[allow(dead_code)] message: Option<String>,
         }
 
+        if self.config.demo {
+            return demo_brain::facts(query);
+        }
+
         let body = serde_json::json!({ "query": query });
 
-        match self.agent.post(&format!("{}/get_facts", PYTHON_API_URL)).send_json(body) {
-            Ok(resp) => {
-                if let Ok(json) = resp.into_json::<FactResp>() {
-                    json.fact_sheet.unwrap_or_default()
-                } else {
-                    String::new()
-                }
-            },
+        match self.post_json::<FactResp>("/get_facts", body) {
+            Ok(json) => json.fact_sheet.unwrap_or_default(),
             Err(e) => {
                 eprintln!("🧠 FACT NETWORK ERROR: {}", e);
                 String::new()
             }
         }
     }
+
+    /// Text printed on the packaging in `image_b64` (claims, ingredients, badges), read by the vision
+    /// model. Empty when the pack has no readable text or the worker fails.
+    pub fn ocr(&self, image_b64: &str) -> String {
+        #[derive(Deserialize)]
+        struct OcrResp {
+            #[allow(dead_code)] status: String,
+            text: Option<String>,
+            message: Option<String>,
+        }
+
+        if self.config.demo {
+            return demo_brain::ocr();
+        }
+
+        let body = serde_json::json!({ "image": image_b64 });
+
+        match self.post_json::<OcrResp>("/ocr", body) {
+            Ok(json) => {
+                if let Some(message) = json.message {
+                    eprintln!("🧠 OCR ERROR: {}", message);
+                }
+                json.text.unwrap_or_default()
+            }
+            Err(e) => {
+                eprintln!("🧠 OCR NETWORK ERROR: {}", e);
+                String::new()
+            }
+        }
+    }
+
+    /// Wikipedia intro for `query` (relaxed to the brand name if needed). Empty when nothing matches.
+    pub fn get_wiki_summary(&self, query: &str) -> String {
+        #[derive(Deserialize)]
+        struct WikiResp {
+            #[allow(dead_code)] status: String,
+            summary: Option<String>,
+            #[allow(dead_code)] message: Option<String>,
+        }
+
+        if self.config.demo {
+            return demo_brain::wiki(query);
+        }
+
+        let body = serde_json::json!({ "query": query });
+
+        match self.post_json::<WikiResp>("/get_wiki", body) {
+            Ok(json) => json.summary.unwrap_or_default(),
+            Err(e) => {
+                eprintln!("🧠 WIKI NETWORK ERROR: {}", e);
+                String::new()
+            }
+        }
+    }
+}
+
+// --- ASYNC API ---
+// Brain calls are blocking (ureq). These run them on Tokio's blocking pool, so async code can
+// `.await` them (and wrap them in `tokio::time::timeout`) without stalling the reactor.
+// Dropping the future only stops the wait; the call itself still runs to completion.
+impl AgentBrain {
+    /// Runs any blocking brain work (e.g. an AnalystEngine report) off the async reactor.
+    pub async fn run_blocking<T, F>(self: &Arc<Self>, f: F) -> Result<T, BrainError>
+    where
+        F: FnOnce(&Arc<AgentBrain>) -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let brain = self.clone();
+        tokio::task::spawn_blocking(move || f(&brain))
+            .await
+            .map_err(|e| BrainError::Task(e.to_string()))
+    }
+
+    pub async fn generate_async(
+        self: &Arc<Self>,
+        prompt: String,
+        max_tokens: usize,
+        image_b64: Option<String>,
+        pdf_b64: Option<String>,
+        temp: f32,
+    ) -> Result<String, BrainError> {
+        self.run_blocking(move |brain| brain.generate(&prompt, max_tokens, image_b64, pdf_b64, temp)).await
+    }
 }
 
 impl Drop for AgentBrain {
     fn drop(&mut self) {
-        if let Ok(mut child) = self.python_process.lock() {
-            println!("🧠 BRAIN: Shutting down Python Server...");
-            let _ = child.kill();
+        for worker in &self.workers {
+            if let Ok(mut child) = worker.process.lock() {
+                println!("🧠 BRAIN: Shutting down Python Server ({})...", worker.base_url);
+                let _ = child.kill();
+            }
         }
     }
 }
\ No newline at end of file
@@ -2,157 +2,912 @@
 // SOCIETY ENGINE V4.0: "Free-MAD" Blackboard Architecture
 // Fixes: Metadata Loss (Undefined IDs) & Consensus Collapse (Echo Chambers)
 
-use std::sync::Arc;
+use std::collections::VecDeque;
+use std::sync::{Arc, OnceLock};
 use tokio::sync::Mutex; 
 use rayon::prelude::*;
-use crate::brain::AgentBrain;
-use crate::agent_swarm::{Agent, SimulationResult};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use crate::brain::{AgentBrain, BrainError};
+use crate::agent_swarm::{Agent, SentimentLexicon, SimulationResult};
+use crate::parsing::{parse_cognitive_response, ResponseTags};
+use crate::memory::{score_importance, Memory, MemoryType};
 use chrono::Local;
+use serde::Deserialize;
 
-pub struct FocusGroupSession;
+// Participant IDs start at 1 (PersonaGenerator), so 0 is reserved for the moderator
+pub const MODERATOR_ID: u32 = 0;
+pub const MODERATOR_ROLE: &str = "Moderator";
+
+// Follow-up questions injected while a session runs (e.g. from /api/ws), asked one per round
+pub type QuestionQueue = Arc<std::sync::Mutex<InjectedQuestions>>;
+
+#[derive(Default)]
+pub struct InjectedQuestions {
+    pending: VecDeque<String>,
+    rounds_left: usize, // Rounds not started yet, i.e. slots a queued question can still take
+}
+
+impl InjectedQuestions {
+    pub fn for_rounds(rounds: usize) -> QuestionQueue {
+        Arc::new(std::sync::Mutex::new(Self { pending: VecDeque::new(), rounds_left: rounds }))
+    }
+
+    /// Queues `question` for the next free round and returns its place in the queue.
+    /// Errs when no remaining round is free, since the question would never be asked.
+    pub fn push(&mut self, question: String) -> Result<usize, String> {
+        if self.rounds_left == 0 {
+            return Err("No round is left to ask this question in".to_string());
+        }
+        if self.pending.len() >= self.rounds_left {
+            return Err(format!("All {} remaining round(s) already have a queued question", self.rounds_left));
+        }
+        self.pending.push_back(question);
+        Ok(self.pending.len())
+    }
+
+    // A round is starting: it takes the next question and is no longer open to new ones
+    fn start_round(&mut self, rounds_after: usize) -> Option<String> {
+        self.rounds_left = rounds_after;
+        self.pending.pop_front()
+    }
+}
+
+// The room history behind the prompts: every spoken line, with the oldest ones folded
+// into `summary` once they leave the verbatim window.
+#[derive(Default)]
+struct Blackboard {
+    lines: Vec<String>,
+    summarized: usize, // lines[..summarized] are covered by `summary`
+    summary: String,
+}
+
+impl Blackboard {
+    fn push(&mut self, speaker: &str, text: &str) {
+        self.lines.push(format!("{}: \"{}\"", speaker, text));
+    }
+
+    // Unsummarized lines older than the last `window`
+    fn stale_lines(&self, window: usize) -> Vec<String> {
+        let cutoff = self.lines.len().saturating_sub(window);
+        self.lines.get(self.summarized..cutoff).map(<[String]>::to_vec).unwrap_or_default()
+    }
+
+    fn fold(&mut self, count: usize, summary: String) {
+        self.summarized += count;
+        self.summary = summary;
+    }
+
+    fn render(&self, window: usize) -> String {
+        let mut out = String::from("--- DEBATE START ---\n");
+        if !self.summary.is_empty() {
+            out.push_str(&format!("(Earlier discussion, summarized): {}\n", self.summary));
+        }
+        let start = self.summarized.max(self.lines.len().saturating_sub(window));
+        for line in &self.lines[start..] {
+            out.push_str(line);
+            out.push('\n');
+        }
+        out
+    }
+}
+
+// How hard a devil's advocate pushes against the room (SimulationRequest `advocate_intensity`)
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AdvocateIntensity {
+    Mild,
+    #[default]
+    Firm,
+    Aggressive,
+}
+
+impl AdvocateIntensity {
+    fn instruction(&self) -> &'static str {
+        match self {
+            AdvocateIntensity::Mild => "Raise the strongest doubt the room has overlooked. Stay polite, but don't let it go.",
+            AdvocateIntensity::Firm => "Challenge the prevailing opinion in the room and argue the opposite side with concrete reasons.",
+            AdvocateIntensity::Aggressive => "Attack the prevailing opinion relentlessly. Concede nothing and tear apart every argument in its favour.",
+        }
+    }
+}
+
+/// One round of the focus-group protocol. The default script is opening / debate / verdict
+/// (see FocusGroupSession::new); studies can supply their own, e.g. a "brainstorm improvements" round.
+#[derive(Deserialize, Clone, Debug)]
+pub struct PhaseConfig {
+    pub name: String,        // e.g. "Initial Reactions"; shown to agents as "PHASE n: NAME."
+    pub instruction: String, // What agents should do this round
+    // Debate rounds get the conflict temperature and the devil's advocates
+    #[serde(default)]
+    pub is_debate: bool,
+    // Overrides the phase's default temperature (opening / debate / verdict)
+    #[serde(default)]
+    pub temperature: Option<f32>,
+}
+
+impl PhaseConfig {
+    fn new(name: &str, instruction: &str, is_debate: bool) -> Self {
+        Self { name: name.to_string(), instruction: instruction.to_string(), is_debate, temperature: None }
+    }
+}
+
+// Everything one round of participant turns needs, owned so the round can leave the async task
+struct RoundTurns {
+    round: usize,
+    speakers: Vec<Agent>, // In speaking order
+    previous_round: Vec<SimulationResult>, // Reply targets
+    advocates: Vec<u32>,
+    history_snapshot: String,
+    stage_instruction: String,
+    temp: f32,
+}
+
+#[derive(Clone)]
+pub struct FocusGroupSession {
+    pub rounds: usize,
+    // One entry per round; `rounds` always equals phases.len()
+    pub phases: Vec<PhaseConfig>,
+    // When true, a moderator opens every round with a steering question
+    pub moderator: bool,
+    // Speaking order is reshuffled every round; a seed makes that reproducible
+    pub seed: Option<u64>,
+    // When true, debate-phase turns are asked to rebut one named speaker from the previous round
+    pub reply_targeting: bool,
+    // When set, agents remember each round and reflect once accumulated importance crosses this
+    pub reflection_threshold: Option<f32>,
+    // Room-history lines shown verbatim; older ones are condensed into a running summary.
+    // None = 2 per participant (see history_window_for)
+    pub history_window: Option<usize>,
+    // Debate rounds: this many of the most skeptical agents get a secret contrarian objective
+    pub devils_advocates: usize,
+    pub advocate_intensity: AdvocateIntensity,
+    // When set, a queued question replaces the moderator's own at the start of the next round
+    pub injected_questions: Option<QuestionQueue>,
+    // Product image / PDF (base64). Sent with each agent's first reaction only; later rounds
+    // reference it in text, so the large payload isn't re-sent for every agent every round.
+    pub image_data: Option<String>,
+    pub pdf_data: Option<String>,
+    // Sampling temperature per phase: debate rounds run hotter than the final verdict
+    pub opening_temperature: f32,
+    pub debate_temperature: f32,
+    pub verdict_temperature: f32,
+    pub lexicon: SentimentLexicon,
+}
 
 impl FocusGroupSession {
+    // Display name for the scenario catalogue (GET /api/scenarios)
+    pub const NAME: &'static str = "Focus Group (Multi-Agent Debate)";
+
+    pub fn new(rounds: usize) -> Self {
+        let rounds = rounds.max(1);
+        Self {
+            rounds,
+            phases: Self::default_phases(rounds),
+            moderator: false,
+            seed: None,
+            reply_targeting: false,
+            reflection_threshold: None,
+            history_window: None,
+            devils_advocates: 0,
+            advocate_intensity: AdvocateIntensity::default(),
+            injected_questions: None,
+            image_data: None,
+            pdf_data: None,
+            opening_temperature: 0.6, // Moderate creativity
+            debate_temperature: 0.8,  // High Entropy for conflict
+            verdict_temperature: 0.5, // Stable
+            lexicon: SentimentLexicon::default(),
+        }
+    }
+
+    // --- "Free-MAD" CONFLICT INJECTION ---
+    // Research suggests escalating conflict in middle rounds to prevent "polite consensus":
+    // reactions first, every middle round a debate, the last round a verdict.
+    fn default_phases(rounds: usize) -> Vec<PhaseConfig> {
+        (1..=rounds)
+            .map(|round| match round {
+                1 => PhaseConfig::new(
+                    "Initial Reactions",
+                    "Give your raw, unfiltered first impression. Be honest but brief.",
+                    false,
+                ),
+                _ if round == rounds => PhaseConfig::new(
+                    "Final Verdict",
+                    "Did the discussion change your mind? Give a final Yes/No decision.",
+                    false,
+                ),
+                _ => PhaseConfig::new(
+                    "The Debate (Conflict Mode)",
+                    "Review the ROOM HISTORY. \
+                    If you disagree with a previous point, ATTACK it. \
+                    If you are Skeptical, find flaws in the Optimists' logic. \
+                    Do NOT be polite. We need critical analysis.",
+                    true,
+                ),
+            })
+            .collect()
+    }
+
+    /// Replaces the round script; the session then runs one round per phase.
+    /// An empty list keeps the current script.
+    pub fn with_phases(mut self, phases: Vec<PhaseConfig>) -> Self {
+        if !phases.is_empty() {
+            self.rounds = phases.len();
+            self.phases = phases;
+        }
+        self
+    }
+
+    pub fn with_moderator(mut self, enabled: bool) -> Self {
+        self.moderator = enabled;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: Option<u64>) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn with_reply_targeting(mut self, enabled: bool) -> Self {
+        self.reply_targeting = enabled;
+        self
+    }
+
+    pub fn with_reflection(mut self, threshold: Option<f32>) -> Self {
+        self.reflection_threshold = threshold;
+        self
+    }
+
+    pub fn with_history_window(mut self, window: Option<usize>) -> Self {
+        self.history_window = window.map(|w| w.max(1));
+        self
+    }
+
+    pub fn with_devils_advocates(mut self, count: usize, intensity: AdvocateIntensity) -> Self {
+        self.devils_advocates = count;
+        self.advocate_intensity = intensity;
+        self
+    }
+
+    // Verbatim lines of room history: explicit setting, else two per participant (min 8)
+    fn history_window_for(&self, agent_count: usize) -> usize {
+        self.history_window.unwrap_or_else(|| (2 * agent_count).max(8))
+    }
+
+    pub fn with_question_queue(mut self, queue: QuestionQueue) -> Self {
+        self.injected_questions = Some(queue);
+        self
+    }
+
+    pub fn with_attachments(mut self, image_data: Option<String>, pdf_data: Option<String>) -> Self {
+        self.image_data = image_data;
+        self.pdf_data = pdf_data;
+        self
+    }
+
+    // Round 1 shows the attachments; later rounds remind the agent what they saw.
+    fn attachment_note(&self, round: usize) -> &'static str {
+        match (round, self.image_data.is_some(), self.pdf_data.is_some()) {
+            (1, _, _) | (_, false, false) => "",
+            (_, true, false) => "(You were shown the product image in Round 1. Refer back to it if relevant.)\n",
+            (_, false, true) => "(You were shown the product document in Round 1. Refer back to it if relevant.)\n",
+            (_, true, true) => "(You were shown the product image and document in Round 1. Refer back to them if relevant.)\n",
+        }
+    }
+
+    // Skeptics rebut the most enthusiastic statement; everyone else answers the harshest critic.
+    fn pick_reply_target<'a>(agent: &Agent, previous_round: &'a [SimulationResult]) -> Option<&'a SimulationResult> {
+        // A failed turn holds error text, not an argument anyone could answer
+        let others = previous_round.iter().filter(|r| r.agent_id != agent.id && !r.failed);
+        let by_score = |a: &&SimulationResult, b: &&SimulationResult| {
+            a.sentiment_score.partial_cmp(&b.sentiment_score).unwrap_or(std::cmp::Ordering::Equal)
+        };
+
+        if agent.skepticism_level.eq_ignore_ascii_case("high") {
+            others.max_by(by_score)
+        } else {
+            others.min_by(by_score)
+        }
+    }
+
+    /// Per-round speaking orders (indices into `agents`). The first speaker "sets the room
+    /// temperature" on the Blackboard, so we rotate who that is instead of always agent #1.
+    pub fn speaking_orders(&self, agent_count: usize) -> Vec<Vec<usize>> {
+        let mut rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+
+        (0..self.rounds)
+            .map(|_| {
+                let mut order: Vec<usize> = (0..agent_count).collect();
+                order.shuffle(&mut rng);
+                order
+            })
+            .collect()
+    }
+
+    /// Agent ids playing devil's advocate in `round` (debate rounds only): the `devils_advocates`
+    /// most skeptical agents. Ties go to whoever speaks first in `order`, the round's (reshuffled)
+    /// speaking order, so equally skeptical agents take turns instead of the same one every time.
+    pub fn devils_advocates_for_round(&self, agents: &[Agent], order: &[usize], round: usize) -> Vec<u32> {
+        if self.devils_advocates == 0 || !self.is_debate_round(round) {
+            return Vec::new();
+        }
+        let skepticism_rank = |agent: &Agent| match agent.skepticism_level.to_lowercase().as_str() {
+            "high" => 2,
+            "medium" => 1,
+            _ => 0,
+        };
+
+        let mut ranked: Vec<&Agent> = order.iter().map(|&i| &agents[i]).collect();
+        ranked.sort_by_key(|agent| std::cmp::Reverse(skepticism_rank(agent))); // Stable: keeps speaking order within a tie
+        ranked.into_iter().take(self.devils_advocates).map(|agent| agent.id).collect()
+    }
+
+    // Round (1-based) runs a debate phase: devil's advocates and reply targeting only apply there
+    fn is_debate_round(&self, round: usize) -> bool {
+        self.phases.get(round.wrapping_sub(1)).is_some_and(|phase| phase.is_debate)
+    }
+
+    /// Moderator turns are part of the transcript but not participant opinions,
+    /// so they must be excluded from sentiment / consensus statistics.
+    pub fn is_moderator(result: &SimulationResult) -> bool {
+        result.agent_id == MODERATOR_ID && result.agent_role == MODERATOR_ROLE
+    }
+
+    /// Stage instruction and sampling temperature for `round` (1-based), from its PhaseConfig.
+    /// Without an explicit temperature: debate phases run hot, the last round cool, others moderate.
+    pub fn phase_for_round(&self, round: usize) -> (String, f32) {
+        let Some(phase) = self.phases.get(round.wrapping_sub(1)) else {
+            return (String::new(), self.opening_temperature);
+        };
+        let default_temperature = if phase.is_debate {
+            self.debate_temperature
+        } else if round == self.rounds && round > 1 {
+            self.verdict_temperature
+        } else {
+            self.opening_temperature
+        };
+        (
+            format!("PHASE {}: {}. {}", round, phase.name.to_uppercase(), phase.instruction),
+            phase.temperature.unwrap_or(default_temperature),
+        )
+    }
     
     // --- THE BLACKBOARD ARCHITECTURE ---
     // We maintain a shared "Room History" string that evolves.
     // We do NOT ask the LLM to generate the speaker names/IDs. We force the identity from Rust.
-    pub async fn run_debate(
+    // `on_round` is invoked after every round with (round_number, that round's results)
+    // so callers can surface the live transcript before the whole debate finishes.
+    pub async fn run_debate<F>(
+        &self,
         brain: &Arc<AgentBrain>,
-        agents: &Vec<Agent>,
+        agents: &[Agent],
         product_context: &str,
-        rounds: usize
-    ) -> Vec<SimulationResult> {
+        on_round: F,
+    ) -> Vec<SimulationResult>
+    where
+        F: Fn(usize, &[SimulationResult]),
+    {
+        let rounds = self.rounds;
         let mut results = Vec::new();
+        // Shared with each round's blocking task
+        let session = Arc::new(self.clone());
         // The Blackboard: Shared memory of the conversation
-        let room_history = Arc::new(Mutex::new(String::from("--- DEBATE START ---\n")));
+        let room_history = Arc::new(Mutex::new(Blackboard::default()));
+        let history_window = self.history_window_for(agents.len());
+        let speaking_orders = self.speaking_orders(agents.len());
+        // Participant messages from the last round (reply targets)
+        let mut previous_round: Vec<SimulationResult> = Vec::new();
 
         println!("🗣️ MODE: Starting Multi-Agent Focus Group ({} Rounds)...", rounds);
 
         for round in 1..=rounds {
             println!("   ⟳ Running Round {}...", round);
             
-            // --- "Free-MAD" CONFLICT INJECTION ---
-            // Research suggests escalating conflict in middle rounds to prevent "polite consensus".
-            let (stage_instruction, temp) = match round {
-                1 => (
-                    "PHASE 1: INITIAL REACTIONS. \
-                    Give your raw, unfiltered first impression. Be honest but brief.", 
-                    0.6 // Moderate creativity
-                ),
-                _ if round == rounds => (
-                    "PHASE 3: FINAL VERDICT. \
-                    Did the discussion change your mind? Give a final Yes/No decision.", 
-                    0.5 // Stable
-                ),
-                _ => (
-                    "PHASE 2: THE DEBATE (CONFLICT MODE). \
-                    Review the ROOM HISTORY. \
-                    If you disagree with a previous point, ATTACK it. \
-                    If you are Skeptical, find flaws in the Optimists' logic. \
-                    Do NOT be polite. We need critical analysis.", 
-                    0.8 // High Entropy for conflict
-                ), 
+            let (stage_instruction, temp) = self.phase_for_round(round);
+
+            // 0. Moderator speaks first and steers the round (written straight to the Blackboard).
+            // A question injected by the client takes the moderator's slot for this round.
+            let injected = self
+                .injected_questions
+                .as_ref()
+                .and_then(|queue| queue.lock().ok().and_then(|mut queue| queue.start_round(rounds - round)));
+            let moderator_turn = if let Some(question) = injected {
+                println!("   ❓ Injected question for Round {}: {}", round, question);
+                let turn = Self::moderator_result(question, Some("Injected by the research team".to_string()), round, "Injected Question");
+                room_history.lock().await.push(MODERATOR_ROLE, &turn.response);
+                Some(turn)
+            } else if self.moderator {
+                let history_snapshot = room_history.lock().await.render(history_window);
+                let turn = self.moderator_turn(brain, history_snapshot, product_context, round, &stage_instruction).await;
+                room_history.lock().await.push(MODERATOR_ROLE, &turn.response);
+                Some(turn)
+            } else {
+                None
             };
 
             // 1. Snapshot the Blackboard (Read-Only access for this batch)
-            let history_snapshot = room_history.lock().await.clone();
+            let history_snapshot = room_history.lock().await.render(history_window);
 
             // 2. Parallel Inference (Rayon)
             // We map existing agents -> results. 
-            let round_results: Vec<SimulationResult> = agents.par_iter().map(|agent| {
-                
-                // Construct Prompt with Blackboard Context
-                let prompt = format!(
-                    "<|user|>You are participating in a focus group.\n\
-                    --- YOUR IDENTITY ---\n\
-                    Name: {}\n\
-                    Role: {}\n\
-                    Traits: {}\n\
-                    \n\
-                    --- ROOM HISTORY (What others have said) ---\n\
-                    {}\n\
-                    \n\
-                    --- YOUR TURN ---\n\
-                    Topic: {}\n\
-                    Current Round: {}\n\
-                    \n\
-                    INSTRUCTION: {}\n\
-                    Based on your personality, speak to the group. \n\
-                    Reference specific points from the history if they exist.\n\
-                    \n\
-                    MANDATORY FORMAT:\n\
-                    [Thinking]\n\
-                    (Internal Monologue: specific reaction to the history)\n\
-                    [Verdict]\n\
-                    (Spoken Response: 1-2 sentences)\n\
-                    <|end|>\n<|assistant|>",
-                    agent.name, agent.role, agent.speaking_style,
-                    history_snapshot, // <--- Injection of shared state
-                    product_context,
-                    round,
-                    stage_instruction
-                );
-
-                // Inference
-                let raw = brain.generate(&prompt, 400, None, None, temp);
-                
-                // Parse (Using robust parser logic)
-                let (response, thought) = Self::parse_response(&raw);
-
-                // Return Result linked to ORIGINAL AGENT ID
-                SimulationResult {
-                    agent_id: agent.id, 
-                    agent_name: Some(agent.name.clone()), 
-                    agent_role: agent.name.clone(), 
-                    agent_demographic: format!("{} ({})", agent.role, agent.demographic),
-                    scenario: "focus_group".to_string(),
-                    timestamp: Local::now().to_rfc3339(),
-                    prompt: "Context Injection".to_string(),
-                    response: response,
-                    thought_process: thought,
-                    
-                    // --- FIXED: Initialize sources as None ---
-                    // Focus groups use shared context (product_context), not individual skills per turn.
-                    sources: None, 
-                    // ----------------------------------------
-                    
-                    sentiment: "neutral".to_string(),
-                    category: Some(format!("Round {}", round)),
-                }
-            }).collect();
+            // Results come back in speaking order, which is the order they hit the Blackboard.
+            let speakers: Vec<&Agent> = speaking_orders[round - 1].iter().map(|&i| &agents[i]).collect();
+            let advocates = self.devils_advocates_for_round(agents, &speaking_orders[round - 1], round);
+            if !advocates.is_empty() {
+                let names: Vec<&str> = speakers.iter().filter(|a| advocates.contains(&a.id)).map(|a| a.name.as_str()).collect();
+                println!("   😈 Devil's advocates for Round {} ({:?}): {}", round, self.advocate_intensity, names.join(", "));
+            }
+            let turns = RoundTurns {
+                round,
+                speakers: speakers.into_iter().cloned().collect(),
+                previous_round: std::mem::take(&mut previous_round),
+                advocates,
+                history_snapshot,
+                stage_instruction: stage_instruction.clone(),
+                temp,
+            };
+            // The turns block on the brain (Rayon inside), so they run on the blocking pool
+            let participant_results = {
+                let (session, brain, context) = (Arc::clone(&session), Arc::clone(brain), product_context.to_string());
+                tokio::task::spawn_blocking(move || session.participant_turns(&brain, &context, turns))
+                    .await
+                    .unwrap_or_else(|e| {
+                        println!("   ❌ Round {} turns failed: {}", round, e);
+                        Vec::new()
+                    })
+            };
+            previous_round = participant_results.clone();
+
+            // The moderator's question leads the round's transcript
+            let round_results: Vec<SimulationResult> = moderator_turn.into_iter().chain(participant_results).collect();
 
             // 3. Update Blackboard (Write access)
             // We append the new responses to the history so the next round sees them.
             let mut history_guard = room_history.lock().await;
             for res in &round_results {
+                results.push(res.clone());
+                if Self::is_moderator(res) || res.failed {
+                    continue; // Already on the Blackboard / error text the panel shouldn't debate
+                }
                 // Use agent_name for the transcript history so agents know who said what
                 let speaker = res.agent_name.clone().unwrap_or("Participant".to_string());
-                history_guard.push_str(&format!("{}: \"{}\"\n", speaker, res.response));
-                results.push(res.clone());
+                history_guard.push(&speaker, &res.response);
             }
+            let stale = history_guard.stale_lines(history_window);
+            drop(history_guard);
+
+            // 4. Lines leaving the window are condensed, so later rounds keep the earlier arguments
+            if !stale.is_empty() && round < rounds {
+                let previous = room_history.lock().await.summary.clone();
+                match self.condense_history(brain, &previous, &stale).await {
+                    Some(summary) => room_history.lock().await.fold(stale.len(), summary),
+                    None => println!("   ⚠️ Could not condense {} history lines; showing the recent window only", stale.len()),
+                }
+            }
+
+            on_round(round, &round_results);
         }
 
         results
     }
 
-    // Helper: Parse [Thinking] and [Verdict] tags
-    fn parse_response(raw: &str) -> (String, Option<String>) {
-        let clean = raw.replace("---", "").replace("ROOM CONTEXT", "").trim().to_string();
-        
-        if let Some(v_idx) = clean.find("[Verdict]") {
-            let verdict = clean[v_idx+9..].trim().to_string();
-            
-            let thought = if let Some(t_idx) = clean.find("[Thinking]") {
-                 // Extract text between [Thinking] and [Verdict]
-                 Some(clean[t_idx+10..v_idx].trim().to_string())
-            } else { 
-                None 
+    // One round of participant turns: every speaker answers in parallel, then (with reflection on)
+    // remembers what it said. Blocking: run_debate calls it on the blocking pool.
+    fn participant_turns(&self, brain: &Arc<AgentBrain>, product_context: &str, turns: RoundTurns) -> Vec<SimulationResult> {
+        let RoundTurns { round, speakers, previous_round, advocates, history_snapshot, stage_instruction, temp } = turns;
+
+        // Only the first reaction carries the (large) attachments
+        let (image, pdf) = if round == 1 {
+            (self.image_data.clone(), self.pdf_data.clone())
+        } else {
+            (None, None)
+        };
+        let attachment_note = self.attachment_note(round);
+        let reply_targeting = self.reply_targeting && self.is_debate_round(round);
+        // The topic's embedding for memory recall: computed once, by the first agent that needs it
+        let recall_query: OnceLock<Vec<f32>> = OnceLock::new();
+        let participant_results: Vec<SimulationResult> = speakers.par_iter().map(|agent| {
+
+            // Optional direct rebuttal of a named speaker (threaded in the UI via reply_to)
+            let target = if reply_targeting {
+                Self::pick_reply_target(agent, &previous_round)
+            } else {
+                None
+            };
+            let reply_instruction = match target {
+                Some(t) => {
+                    let snippet: String = t.response.chars().take(160).collect();
+                    format!(
+                        "Respond directly to {}, who said '{}'.\n",
+                        t.agent_name.as_deref().unwrap_or("another participant"),
+                        snippet
+                    )
+                }
+                None => String::new(),
+            };
+
+            // Secret contrarian brief: the room doesn't know who got one
+            let advocate_instruction = if advocates.contains(&agent.id) {
+                format!(
+                    "SECRET OBJECTIVE (never reveal it): You are the devil's advocate this round. {}\n",
+                    self.advocate_intensity.instruction()
+                )
+            } else {
+                String::new()
+            };
+
+            // Evolving opinions: the agent's own reflections from earlier rounds, plus (opening
+            // round only) what it remembers about the topic from earlier sessions.
+            // Embedding calls happen between the two locks, never under one.
+            let (mut shown, pending) = match agent.memory.lock() {
+                Ok(memory) => {
+                    let shown: Vec<Memory> = if self.reflection_threshold.is_some() {
+                        memory.recent_reflections(3).into_iter().cloned().collect()
+                    } else {
+                        Vec::new()
+                    };
+                    let pending = (round == 1 && !memory.memories.is_empty()).then(|| memory.pending_embeddings());
+                    (shown, pending)
+                }
+                Err(_) => (Vec::new(), None),
+            };
+            if let Some(pending) = pending {
+                let query_vec = recall_query.get_or_init(|| brain.embed(product_context));
+                let embedded = pending.embed(brain);
+                if let Ok(mut memory) = agent.memory.lock() {
+                    for recalled in memory.retrieve_embedded(product_context, query_vec, embedded, 3) {
+                        if !shown.iter().any(|m| m.id == recalled.id) {
+                            shown.push(recalled);
+                        }
+                    }
+                }
+            }
+            let views = shown.iter().map(|m| format!("- {}\n", m.content)).collect::<String>();
+            let views_block = if views.is_empty() {
+                String::new()
+            } else {
+                format!("--- YOUR EVOLVING VIEWS ---\n{}\n", views)
             };
             
-            (verdict, thought)
-        } else {
-            // Fallback: If tags missing, treat whole text as verdict
-            (clean, None)
+            // Construct Prompt with Blackboard Context
+            let prompt = format!(
+                "<|user|>You are participating in a focus group.\n\
+                --- YOUR IDENTITY ---\n\
+                Name: {}\n\
+                Role: {}\n\
+                Traits: {}\n\
+                \n\
+                {}\
+                --- ROOM HISTORY (What others have said) ---\n\
+                {}\n\
+                \n\
+                --- YOUR TURN ---\n\
+                Topic: {}\n\
+                Current Round: {}\n\
+                \n\
+                INSTRUCTION: {}\n\
+                {}\
+                {}\
+                {}\
+                Based on your personality, speak to the group. \n\
+                Reference specific points from the history if they exist.\n\
+                \n\
+                MANDATORY FORMAT:\n\
+                [Thinking]\n\
+                (Internal Monologue: specific reaction to the history)\n\
+                [Verdict]\n\
+                (Spoken Response: 1-2 sentences)\n\
+                <|end|>\n<|assistant|>",
+                agent.name, agent.role, agent.speaking_style,
+                views_block,
+                history_snapshot, // <--- Injection of shared state
+                product_context,
+                round,
+                stage_instruction,
+                attachment_note,
+                reply_instruction,
+                advocate_instruction
+            );
+
+            // Inference
+            let raw = brain.generate(&prompt, 400, image.clone(), pdf.clone(), temp);
+            
+            // Parse (Using robust parser logic)
+            let failed = BrainError::is_error_reply(&raw);
+            let (response, thought) = Self::parse_response(&raw);
+            // A failed turn is not an opinion: keep it neutral (stats skip it anyway)
+            let (sentiment, sentiment_score) = if failed {
+                ("neutral".to_string(), 0.0)
+            } else {
+                (self.lexicon.classify(&response), self.lexicon.score(&response))
+            };
+
+            // Return Result linked to ORIGINAL AGENT ID
+            SimulationResult {
+                agent_id: agent.id, 
+                agent_name: Some(agent.name.clone()), 
+                agent_role: agent.name.clone(), 
+                agent_demographic: format!("{} ({})", agent.role, agent.demographic),
+                scenario: "focus_group".to_string(),
+                timestamp: Local::now().to_rfc3339(),
+                prompt: "Context Injection".to_string(),
+                response,
+                response_full: None,
+                thought_process: thought,
+                
+                // --- FIXED: Initialize sources as None ---
+                // Focus groups use shared context (product_context), not individual skills per turn.
+                sources: None, 
+                source_refs: Vec::new(),
+                // ----------------------------------------
+                
+                sentiment,
+                sentiment_score,
+                category: Some(format!("Round {}", round)),
+                intent: None,
+                reply_to: target.map(|t| t.agent_id),
+                product: None,
+                failed,
+                cohort: agent.cohort.clone(),
+                timing: None,
+            }
+        }).collect();
+
+        // Memory + reflection: each participant remembers what they said this round
+        if let Some(threshold) = self.reflection_threshold {
+            speakers.par_iter().zip(participant_results.par_iter()).for_each(|(agent, res)| {
+                let importance = score_importance(brain, &res.response, 0.3 + 0.7 * res.sentiment_score.abs());
+                let content = format!("Round {} of the focus group on {}: I said \"{}\"", round, product_context, res.response);
+                let embedding = brain.embed(&content);
+                let pending = agent.memory.lock().ok().and_then(|mut memory| {
+                    memory.add_memory_embedded(content, MemoryType::Observation, importance, embedding);
+                    memory.pending_reflection(threshold)
+                });
+                // The brain is asked with the lock released; the insights are stored afterwards
+                let insights = pending.map(|pending| pending.reflect(brain)).unwrap_or_default();
+                if !insights.is_empty() {
+                    if let Ok(mut memory) = agent.memory.lock() {
+                        memory.add_reflections(&insights);
+                    }
+                    println!("   💭 {} reflected: {} new insight(s)", agent.name, insights.len());
+                }
+            });
         }
+
+        participant_results
+    }
+
+    // Running summary of the room: the previous summary plus the lines that just left the window.
+    async fn condense_history(&self, brain: &Arc<AgentBrain>, previous: &str, stale: &[String]) -> Option<String> {
+        let prompt = format!(
+            "<|user|>Summarize this focus-group discussion so far in 3-5 sentences.\n\
+            Keep who argued what (use names) and where participants disagree. No opinions of your own.\n\
+            \n\
+            EARLIER SUMMARY:\n{}\n\
+            \n\
+            NEW MESSAGES:\n{}\n\
+            <|end|>\n<|assistant|>",
+            if previous.is_empty() { "(none)" } else { previous },
+            stale.join("\n")
+        );
+
+        let summary = brain.generate_async(prompt, 250, None, None, self.verdict_temperature).await.ok()?;
+        usable_summary(&summary)
+    }
+
+    // The moderator reads the room and asks ONE steering question for the coming round.
+    async fn moderator_turn(
+        &self,
+        brain: &Arc<AgentBrain>,
+        history: String,
+        product_context: &str,
+        round: usize,
+        stage_instruction: &str,
+    ) -> SimulationResult {
+        let prompt = format!(
+            "<|user|>You are the MODERATOR of a consumer focus group. You never give your own opinion.\n\
+            \n\
+            --- ROOM HISTORY (What participants have said) ---\n\
+            {}\n\
+            \n\
+            --- NEXT ROUND ---\n\
+            Topic: {}\n\
+            Current Round: {}\n\
+            Round Goal: {}\n\
+            \n\
+            TASK: Ask the group ONE short follow-up question that steers this round. \
+            Probe disagreements, vague claims or anything left unexplored. \
+            If the history is empty, open the discussion.\n\
+            \n\
+            MANDATORY FORMAT:\n\
+            [Thinking]\n\
+            (What the discussion is missing)\n\
+            [Verdict]\n\
+            (Your question to the group: 1 sentence)\n\
+            <|end|>\n<|assistant|>",
+            history, product_context, round, stage_instruction
+        );
+
+        let raw = brain
+            .generate_async(prompt, 200, None, None, self.opening_temperature)
+            .await
+            .unwrap_or_else(|e| e.to_string());
+        let (response, thought) = Self::parse_response(&raw);
+        Self::moderator_result(response, thought, round, "Moderator Steering")
+    }
+
+    fn moderator_result(response: String, thought: Option<String>, round: usize, prompt_label: &str) -> SimulationResult {
+        SimulationResult {
+            agent_id: MODERATOR_ID,
+            agent_name: Some(MODERATOR_ROLE.to_string()),
+            agent_role: MODERATOR_ROLE.to_string(),
+            agent_demographic: MODERATOR_ROLE.to_string(),
+            scenario: "focus_group".to_string(),
+            timestamp: Local::now().to_rfc3339(),
+            prompt: prompt_label.to_string(),
+            response,
+            response_full: None,
+            thought_process: thought,
+            sources: None,
+            source_refs: Vec::new(),
+            // Not an opinion: neutral and excluded from stats (see is_moderator)
+            sentiment: "neutral".to_string(),
+            sentiment_score: 0.0,
+            category: Some(format!("Round {}", round)),
+            intent: None,
+            reply_to: None,
+            product: None,
+            failed: false,
+            cohort: None,
+            timing: None,
+        }
+    }
+
+    /// After the session: each participant keeps their final-round statement as a Reflection,
+    /// so a returning persona can recall where they landed last time. Importance is the brain's
+    /// rating (score_importance), but never below a floor that grows with how often others
+    /// replied to or named them (a contested or echoed view is memorable).
+    /// Returns how many agents got a memory.
+    pub fn remember_final_positions(brain: &AgentBrain, agents: &[Agent], results: &[SimulationResult], topic: &str) -> usize {
+        let mut remembered = 0;
+        for agent in agents {
+            let final_statement = match results.iter().rev().find(|r| r.agent_id == agent.id && !Self::is_moderator(r)) {
+                Some(r) => r,
+                None => continue,
+            };
+
+            let first_name = agent.name.split_whitespace().next().unwrap_or(&agent.name);
+            let mentions = results
+                .iter()
+                .filter(|r| r.agent_id != agent.id && !Self::is_moderator(r))
+                .filter(|r| r.reply_to == Some(agent.id) || r.response.contains(first_name))
+                .count();
+            let social = (0.4 + 0.15 * mentions as f32).min(1.0);
+            let importance = score_importance(brain, &final_statement.response, social).max(social);
+
+            let content = format!("[Focus Group] About {}: my final position was \"{}\"", topic, final_statement.response);
+            let embedding = brain.embed(&content);
+            if let Ok(mut memory) = agent.memory.lock() {
+                memory.add_memory_embedded(content, MemoryType::Reflection, importance, embedding);
+                remembered += 1;
+            }
+        }
+        remembered
+    }
+
+    // Helper: Parse [Thinking] and [Verdict] tags (shared, UTF-8 safe parser)
+    fn parse_response(raw: &str) -> (String, Option<String>) {
+        let tags = ResponseTags { action: None, ..ResponseTags::default() };
+        let (thought, answer) = parse_cognitive_response(raw, &tags);
+        (answer, thought)
+    }
+}
+
+// A summary replaces the stale messages for every later round, so an empty or error reply
+// (worker down, timeout) must keep the full history instead.
+fn usable_summary(reply: &str) -> Option<String> {
+    let summary = reply.trim();
+    if summary.is_empty() || BrainError::is_error_reply(summary) { None } else { Some(summary.to_string()) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debate_rounds_run_hotter_than_the_verdict() {
+        let session = FocusGroupSession::new(3);
+        let (opening, opening_temp) = session.phase_for_round(1);
+        let (debate, debate_temp) = session.phase_for_round(2);
+        let (verdict, verdict_temp) = session.phase_for_round(3);
+
+        assert!(opening.starts_with("PHASE 1: INITIAL REACTIONS."));
+        assert!(debate.starts_with("PHASE 2: THE DEBATE"));
+        assert!(verdict.starts_with("PHASE 3: FINAL VERDICT."));
+        assert_eq!(opening_temp, session.opening_temperature);
+        assert_eq!(debate_temp, 0.8);
+        assert_eq!(verdict_temp, 0.5);
+    }
+
+    #[test]
+    fn phase_temperature_overrides_the_default() {
+        let mut debate = PhaseConfig::new("Debate", "Argue.", true);
+        debate.temperature = Some(1.1);
+        let session = FocusGroupSession::new(1).with_phases(vec![PhaseConfig::new("Open", "React.", false), debate]);
+
+        assert_eq!(session.rounds, 2);
+        assert_eq!(session.phase_for_round(2).1, 1.1);
+        assert_eq!(session.phase_for_round(1).1, session.opening_temperature);
+    }
+
+    #[test]
+    fn seeded_speaking_orders_are_reproducible() {
+        let orders = |seed| FocusGroupSession::new(4).with_seed(Some(seed)).speaking_orders(12);
+
+        assert_eq!(orders(7), orders(7));
+        assert_ne!(orders(7), orders(8));
+        for order in orders(7) {
+            let mut sorted = order.clone();
+            sorted.sort_unstable();
+            assert_eq!(sorted, (0..12).collect::<Vec<_>>()); // Every agent speaks exactly once
+        }
+    }
+
+    #[test]
+    fn only_debate_rounds_count_as_debate() {
+        let session = FocusGroupSession::new(4).with_reply_targeting(true);
+        let debate_rounds: Vec<usize> = (1..=4).filter(|&round| session.is_debate_round(round)).collect();
+        assert_eq!(debate_rounds, vec![2, 3]); // Not the opening reactions, not the final verdict
+    }
+
+    #[test]
+    fn injected_questions_need_a_round_left() {
+        let queue = InjectedQuestions::for_rounds(3);
+        let mut questions = queue.lock().unwrap();
+        assert_eq!(questions.push("Would you pay Rs 99?".to_string()), Ok(1));
+
+        // Round 2 of 3 starts: only round 3 is still open, and it already has a question waiting
+        assert!(questions.start_round(3 - 1).is_some());
+        assert_eq!(questions.start_round(3 - 2), None);
+        assert_eq!(questions.push("What about the pack?".to_string()), Ok(1));
+        assert!(questions.push("And the taste?".to_string()).is_err());
+
+        // Last round running: nothing can be asked anymore
+        assert!(questions.start_round(0).is_some());
+        assert!(questions.push("Too late?".to_string()).is_err());
+    }
+
+    #[test]
+    fn error_replies_never_become_the_summary() {
+        assert_eq!(usable_summary("  Ravi liked it; Asha found it pricey.\n"), Some("Ravi liked it; Asha found it pricey.".to_string()));
+        for reply in ["", "   ", "Error: CUDA out of memory", "Timeout: worker did not answer", "Network Error: connection refused"] {
+            assert_eq!(usable_summary(reply), None, "{:?}", reply);
+        }
+    }
+
+    #[test]
+    fn failed_turns_are_never_reply_targets() {
+        let turn = |agent_id: u32, score: f32, failed: bool| {
+            let mut res = FocusGroupSession::moderator_result("Error: worker timed out".to_string(), None, 1, "Round 1");
+            res.agent_id = agent_id;
+            res.sentiment_score = score;
+            res.failed = failed;
+            res
+        };
+        // The failed turns carry the extreme scores, so they'd win without the filter
+        let previous = vec![turn(2, 0.9, true), turn(3, 0.4, false), turn(4, -0.9, true), turn(5, -0.2, false)];
+
+        let mut skeptic = Agent::new(1, "Analyst");
+        skeptic.skepticism_level = "High".to_string();
+        assert_eq!(FocusGroupSession::pick_reply_target(&skeptic, &previous).map(|r| r.agent_id), Some(3));
+
+        let mut fan = Agent::new(1, "Student");
+        fan.skepticism_level = "Low".to_string();
+        assert_eq!(FocusGroupSession::pick_reply_target(&fan, &previous).map(|r| r.agent_id), Some(5));
     }
-}
\ No newline at end of file
+}
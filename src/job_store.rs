@@ -0,0 +1,177 @@
+// src/job_store.rs
+// ORACULUM CORE - JOB PERSISTENCE
+// Flushes the in-memory Job Store (DashMap) to disk so results survive a server restart.
+// Layout: one pretty-printed JSON file per job -> {ORACULUM_JOB_STORE}/{job_id}.json
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use dashmap::DashMap;
+use crate::api::JobStatus;
+
+const DEFAULT_STORE_DIR: &str = "job_store";
+const DEFAULT_FLUSH_SECS: u64 = 5;
+
+pub struct JobStore {
+    dir: PathBuf,
+    pub flush_interval: Duration,
+    // Fingerprint of what we last wrote per job (status, progress, result count, version).
+    // Lets the background flusher skip files that haven't changed.
+    flushed: Mutex<HashMap<String, (String, u32, usize, u64)>>,
+}
+
+impl JobStore {
+    /// Reads `ORACULUM_JOB_STORE` (directory) and `ORACULUM_JOB_FLUSH_SECS` (interval).
+    pub fn from_env() -> Self {
+        let dir = std::env::var("ORACULUM_JOB_STORE").unwrap_or_else(|_| DEFAULT_STORE_DIR.to_string());
+        let secs = std::env::var("ORACULUM_JOB_FLUSH_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_FLUSH_SECS);
+
+        Self::new(dir, Duration::from_secs(secs.max(1)))
+    }
+
+    pub fn new(dir: impl AsRef<Path>, flush_interval: Duration) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            flush_interval,
+            flushed: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Scans the store directory and repopulates the job map.
+    /// Jobs that were still `processing` when the server died are NOT re-run;
+    /// they are marked `interrupted` (and re-saved) so the UI can tell them apart.
+    pub fn load_all(&self, jobs: &DashMap<String, JobStatus>) -> usize {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(_) => return 0, // First boot: nothing persisted yet
+        };
+
+        let mut restored = 0;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+
+            let parsed = fs::read_to_string(&path)
+                .ok()
+                .and_then(|raw| serde_json::from_str::<JobStatus>(&raw).ok());
+
+            match parsed {
+                Some(mut job) => {
+                    if job.status == "processing" {
+                        println!("   ⚠️ STORE: Job {} was interrupted mid-run.", job.id);
+                        job.status = "interrupted".to_string();
+                        job.version += 1; // Pollers holding the "processing" ETag must see the change
+                        let _ = self.save(&job);
+                    }
+                    self.mark_flushed(&job);
+                    jobs.insert(job.id.clone(), job);
+                    restored += 1;
+                }
+                None => println!("   ⚠️ STORE: Skipping unreadable job file {}", path.display()),
+            }
+        }
+
+        restored
+    }
+
+    /// Writes every job whose state changed since the last flush. Returns the number written.
+    pub fn flush(&self, jobs: &DashMap<String, JobStatus>) -> usize {
+        // Snapshot first so we never hold DashMap shard locks during disk IO
+        let dirty: Vec<JobStatus> = jobs
+            .iter()
+            .filter(|entry| !self.is_flushed(entry.value()))
+            .map(|entry| entry.value().clone())
+            .collect();
+
+        let mut written = 0;
+        for job in dirty {
+            match self.save(&job) {
+                Ok(()) => {
+                    self.mark_flushed(&job);
+                    written += 1;
+                }
+                Err(e) => eprintln!("💾 STORE ERROR: Failed to persist job {}: {}", job.id, e),
+            }
+        }
+        written
+    }
+
+    fn save(&self, job: &JobStatus) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let json = serde_json::to_string_pretty(job)?;
+
+        // Write-then-rename so a crash mid-write never leaves a truncated file behind
+        let tmp_path = self.dir.join(format!("{}.json.tmp", job.id));
+        fs::write(&tmp_path, json)?;
+        fs::rename(&tmp_path, self.dir.join(format!("{}.json", job.id)))
+    }
+
+    // `version` catches in-place edits (agent reruns, analysis) that keep the result count
+    fn fingerprint(job: &JobStatus) -> (String, u32, usize, u64) {
+        (job.status.clone(), job.progress.to_bits(), job.results.len(), job.version)
+    }
+
+    fn is_flushed(&self, job: &JobStatus) -> bool {
+        self.flushed
+            .lock()
+            .map(|seen| seen.get(&job.id) == Some(&Self::fingerprint(job)))
+            .unwrap_or(false)
+    }
+
+    fn mark_flushed(&self, job: &JobStatus) {
+        if let Ok(mut seen) = self.flushed.lock() {
+            seen.insert(job.id.clone(), Self::fingerprint(job));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn in_place_edits_are_flushed_again() {
+        let dir = std::env::temp_dir().join(format!("oraculum-store-{}", uuid::Uuid::new_v4()));
+        let store = JobStore::new(&dir, Duration::from_secs(1));
+        let jobs = DashMap::new();
+        jobs.insert("job-1".to_string(), JobStatus {
+            id: "job-1".to_string(),
+            status: "completed".to_string(),
+            progress: 1.0,
+            scenario: "product_launch".to_string(),
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            agents: Vec::new(),
+            results: Vec::new(),
+            archetype_mix: None,
+            duplicate_ratio: None,
+            pack_text: None,
+            timing: None,
+            verdicts: None,
+            version: 1,
+        });
+
+        assert_eq!(store.flush(&jobs), 1);
+        assert_eq!(store.flush(&jobs), 0);
+
+        // Same status, progress and result count: only the version says the job changed
+        jobs.get_mut("job-1").unwrap().version += 1;
+        assert_eq!(store.flush(&jobs), 1);
+
+        let restored = DashMap::new();
+        assert_eq!(JobStore::new(&dir, Duration::from_secs(1)).load_all(&restored), 1);
+        assert_eq!(restored.get("job-1").unwrap().version, 2);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
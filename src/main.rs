@@ -5,6 +5,7 @@
 use actix_web::{web, App, HttpServer};
 use actix_cors::Cors;
 use std::sync::Arc;
+use std::collections::BTreeMap;
 use dashmap::DashMap;
 
 // --- MODULE REGISTRATION ---
@@ -12,6 +13,7 @@ use dashmap::DashMap;
 mod brain;
 mod agent_swarm;
 mod scenarios;
+mod parsing;  // [Thinking]/[Verdict] extraction shared by scenarios, focus groups and the batch engine
 mod reporter;
 mod persona_generator;
 mod api;
@@ -22,16 +24,39 @@ mod memory;
 mod wiki;
 mod skills;   // Manages the Agents' capabilities (WebScout, etc.)
 mod systems;  // NEW: Manages External Connections (Sensory Cortex -> Python)
+mod job_store; // Persists the Job Store to disk (survives restarts)
+mod research_cache; // TTL cache for per-product research + fact sheets
+mod demo_brain; // Canned brain replies for ORACULUM_DEMO (no Python needed)
+mod batch_inference; // Chunked engine behind SimulationRequest `engine: "batch"`
 
-use brain::AgentBrain;
-use agent_swarm::{AgentSwarm, SimulationResult};
-use scenarios::Scenario;
-use skills::{SkillRegistry, SkillInput};
+use brain::{AgentBrain, BrainConfig, BrainError};
+use agent_swarm::{AgentSwarm, SimulationResult, SourceRef, Timing, SOURCE_SNIPPET_CHARS};
+use scenarios::{Scenario, ScenarioRegistry};
+use skills::{SkillCache, SkillRegistry, SkillInput};
+use job_store::JobStore;
+use research_cache::ResearchCache;
+use memory::MemoryType;
+use batch_inference::{BatchInferenceEngine, convert_to_simulation_results, prepare_inference_requests};
+use parsing::ResponseLimit;
 
 // Shared State for the Server
 pub struct AppState {
     pub brain: Arc<AgentBrain>,
     pub jobs: Arc<DashMap<String, api::JobStatus>>,
+    // Live update channels for SSE subscribers (one per job started this session)
+    pub streams: Arc<DashMap<String, tokio::sync::watch::Sender<api::JobStatus>>>,
+    // Scenario factories keyed by scenario id (see ScenarioRegistry::with_builtins)
+    pub scenarios: Arc<ScenarioRegistry>,
+    // Follow-up questions for running focus groups, injected via /api/ws (see QuestionQueue)
+    pub questions: Arc<DashMap<String, focus_group::QuestionQueue>>,
+    // Recent research / fact sheets keyed by product + context (see ResearchCache)
+    pub research_cache: Arc<ResearchCache>,
+    // Prompt context of jobs run this session, for single-agent reruns (see api::RerunContext)
+    pub reruns: Arc<DashMap<String, api::RerunContext>>,
+    // Request size limits enforced by start_simulation
+    pub limits: api::SimulationLimits,
+    // One permit per running job (SimulationLimits::max_active_jobs), held until the job task ends
+    pub job_slots: Arc<tokio::sync::Semaphore>,
 }
 
 #[actix_web::main]
@@ -39,125 +64,487 @@ async fn main() -> std::io::Result<()> {
     println!("🚀 Oraculum API Server Starting...");
     
     // 1. Initialize Neural Engine (ONCE at startup)
-    let brain = Arc::new(AgentBrain::new());
+    // Interpreter, script path, worker count and timeout come from ORACULUM_* env vars (see BrainConfig).
+    // ORACULUM_DEMO=1 skips Python entirely (frontend development without models).
+    let brain = Arc::new(AgentBrain::from_config(BrainConfig::from_env()));
     
-    // 2. Initialize Job Store
+    // 2. Initialize Job Store (and restore anything persisted by a previous run)
     let jobs = Arc::new(DashMap::new());
+    let store = Arc::new(JobStore::from_env());
+    let restored = store.load_all(&jobs);
+    println!("💾 STORE: Restored {} jobs from '{}'", restored, store.dir().display());
+
+    // 2b. Background Flusher: periodically writes changed jobs to disk
+    {
+        let store = store.clone();
+        let jobs = jobs.clone();
+        std::thread::spawn(move || loop {
+            std::thread::sleep(store.flush_interval);
+            store.flush(&jobs);
+        });
+    }
     
     // 3. Create Shared State
+    let limits = api::SimulationLimits::from_env();
     let app_state = web::Data::new(AppState {
         brain: brain.clone(),
         jobs: jobs.clone(),
+        streams: Arc::new(DashMap::new()),
+        questions: Arc::new(DashMap::new()),
+        scenarios: Arc::new(ScenarioRegistry::with_builtins()),
+        research_cache: Arc::new(ResearchCache::from_env()),
+        reruns: Arc::new(DashMap::new()),
+        limits,
+        job_slots: Arc::new(tokio::sync::Semaphore::new(limits.max_active_jobs)),
     });
 
+    let cors_policy = CorsPolicy::from_env();
+    println!("🛡️ CORS: {}", cors_policy.describe());
+
     println!("🌍 Server running at http://127.0.0.1:8080");
 
     // 4. Start HTTP Server
     HttpServer::new(move || {
-        let cors = Cors::permissive();
+        let cors = cors_policy.build();
 
         App::new()
             .wrap(cors)
             .app_data(app_state.clone())
+            // Default JSON limit (2MB) is smaller than a typical base64 image/pdf
+            .app_data(api::json_config(limits.json_body_limit()))
+            .app_data(api::query_config())
+            .app_data(api::path_config())
+            .route("/api/health", web::get().to(api::health))
             .route("/api/simulate", web::post().to(api::start_simulation))
+            .route("/api/preview", web::post().to(api::preview_prompts))
             .route("/api/status/{id}", web::get().to(api::get_job_status))
+            .route("/api/jobs", web::get().to(api::list_jobs))
+            .route("/api/jobs/{id}/agents/{agent_id}/memory", web::get().to(api::get_agent_memory))
+            .route("/api/jobs/{id}/agents/{agent_id}/rerun", web::post().to(api::rerun_agent))
+            .route("/api/scenarios", web::get().to(api::list_scenarios))
+            .route("/api/skills", web::get().to(api::list_skills))
+            .route("/api/stream/{id}", web::get().to(api::stream_job))
+            .route("/api/ws/{id}", web::get().to(api::job_socket))
+            .route("/api/export/{id}.csv", web::get().to(api::export_job_csv))
+            .route("/api/export/{id}", web::get().to(api::export_job))
             .route("/api/analyze", web::post().to(api::analyze_job))
+            .route("/api/compare", web::post().to(api::compare_jobs))
     })
     .bind(("127.0.0.1", 8080))?
     .run()
-    .await
+    .await?;
+
+    // 5. Final flush on graceful shutdown so the last few seconds aren't lost
+    let written = store.flush(&jobs);
+    println!("💾 STORE: Flushed {} jobs before shutdown", written);
+    Ok(())
+}
+
+// --- CORS ---
+// Browser origins allowed to call the API. ORACULUM_CORS_ORIGINS is a comma-separated
+// allowlist ("*" = any origin); ORACULUM_DEV=1 restores the old fully permissive setup.
+// With neither set, only the local frontend (http://localhost:3000) is allowed.
+#[derive(Clone)]
+pub enum CorsPolicy {
+    Permissive,
+    AnyOrigin,
+    Origins(Vec<String>),
+}
+
+const DEFAULT_CORS_ORIGINS: &[&str] = &["http://localhost:3000", "http://127.0.0.1:3000"];
+
+impl CorsPolicy {
+    pub fn from_env() -> Self {
+        if std::env::var("ORACULUM_DEV").is_ok_and(|v| v.trim() == "1") {
+            return CorsPolicy::Permissive;
+        }
+
+        let configured = std::env::var("ORACULUM_CORS_ORIGINS").unwrap_or_default();
+        let entries: Vec<&str> = configured.split(',').map(str::trim).filter(|o| !o.is_empty()).collect();
+        if entries.contains(&"*") {
+            return CorsPolicy::AnyOrigin;
+        }
+
+        // Origins are scheme://host[:port]; anything unparsable is skipped (actix-cors would panic)
+        let mut origins: Vec<String> = entries
+            .into_iter()
+            .filter_map(|o| match url::Url::parse(o) {
+                Ok(url) if url.has_host() => Some(o.trim_end_matches('/').to_string()),
+                _ => {
+                    println!("⚠️ CORS: Ignoring invalid origin '{}'", o);
+                    None
+                }
+            })
+            .collect();
+        if origins.is_empty() {
+            origins = DEFAULT_CORS_ORIGINS.iter().map(|o| o.to_string()).collect();
+        }
+        CorsPolicy::Origins(origins)
+    }
+
+    pub fn build(&self) -> Cors {
+        let restricted = |cors: Cors| cors.allowed_methods(vec!["GET", "POST"]).allow_any_header().max_age(3600);
+        match self {
+            CorsPolicy::Permissive => Cors::permissive(),
+            CorsPolicy::AnyOrigin => restricted(Cors::default().allow_any_origin()),
+            CorsPolicy::Origins(origins) => {
+                restricted(origins.iter().fold(Cors::default(), |cors, origin| cors.allowed_origin(origin)))
+            }
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            CorsPolicy::Permissive => "permissive (ORACULUM_DEV=1)".to_string(),
+            CorsPolicy::AnyOrigin => "any origin (*)".to_string(),
+            CorsPolicy::Origins(origins) => format!("allowing {}", origins.join(", ")),
+        }
+    }
+}
+
+// Default sampling temperature for agent responses (overridable per request: response_temperature)
+pub const DEFAULT_RESPONSE_TEMPERATURE: f32 = 0.7;
+
+// Per-pass settings of a simulation run (see run_simulation_parallel)
+pub struct RunSettings {
+    pub product_context: String, // Context passed from API (e.g. "Price of Bulbasaur")
+    pub temperature: f32,
+    pub max_tokens: Option<usize>, // Overrides Scenario::max_response_tokens
+    pub response_limit: ResponseLimit,
+    pub image_data: Option<String>,
+    pub pdf_data: Option<String>,
 }
 
 // SHARED HELPER FUNCTION
-// This runs on a separate thread (via Rayon) when the API is called
-pub fn run_simulation_parallel(
+// This runs on a separate thread (via Rayon) when the API is called.
+// `on_progress(done, total)` fires as agents finish (throttled to ~20 calls per run),
+// so callers can drive a smooth progress bar.
+pub fn run_simulation_parallel<F>(
     brain: &Arc<AgentBrain>,
     swarm: &Arc<AgentSwarm>,
-    scenario: &Box<dyn Scenario>,
-    image_data: Option<String>,
-    pdf_data: Option<String>,
-    product_context: String, // Context passed from API (e.g. "Price of Bulbasaur")
-) {
+    scenario: &dyn Scenario,
+    settings: RunSettings,
+    on_progress: F,
+) where
+    F: Fn(usize, usize) + Sync,
+{
     use rayon::prelude::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
     
+    let RunSettings { product_context, temperature, max_tokens, response_limit, image_data, pdf_data } = settings;
     let agents = swarm.get_agents();
+    let total = agents.len();
+    let finished = AtomicUsize::new(0);
+    let report_every = (total / 20).max(1);
+    let max_tokens = max_tokens.unwrap_or_else(|| scenario.max_response_tokens());
+    let skill_timeout = skill_timeout();
+    let retries = inference_retries();
+    // Shared by every agent in this job: identical skill calls run once
+    let skill_cache = Arc::new(SkillCache::new());
     
     // Parallel Agent Execution
     let results: Vec<SimulationResult> = agents
         .par_iter()
         .map(|agent| {
-            // 1. Generate Base Prompt (Who am I?)
-            let mut prompt = scenario.generate_prompt(agent, None);
-
-            // 2. --- SKILL EXECUTION (WEB / RAG) ---
-            let mut acquired_knowledge = String::new();
+            let started = std::time::Instant::now();
+            // 1-3. Prompt + skill knowledge (shared with /api/preview)
+            let agent_prompt = build_agent_prompt(brain, agent, scenario, &product_context, skill_timeout, &skill_cache);
             
-            // If the agent has skills (e.g., ["web_scout"]), execute them
-            if !agent.skills.is_empty() {
-                let registry = SkillRegistry::new();
-                
-                for skill_id in &agent.skills {
-                    if let Some(skill) = registry.get(skill_id) {
-                        
-                        // Pass the Product Context to the skill
-                        let input = SkillInput {
-                            query: product_context.clone(),
-                            context: agent.demographic.clone()
-                        };
-                        
-                        println!("[AGENT] {} is executing skill: {}", agent.name, skill_id);
-                        let output = skill.execute(brain, input);
-                        
-                        if output.success {
-                            acquired_knowledge.push_str(&format!(
-                                "\n### SENSORY OBSERVATION (Source: {})\n{}\n", 
-                                skill_id.to_uppercase(), 
-                                output.data
-                            ));
-                        } else {
-                             println!("[WARN] Skill {} failed for agent {}", skill_id, agent.name);
-                        }
-                    }
-                }
+            // 4. Inference (Using the gathered knowledge), retried while the brain answers with an error
+            let generate = || brain.generate(&agent_prompt.prompt, max_tokens, image_data.clone(), pdf_data.clone(), temperature);
+            let inference_started = std::time::Instant::now();
+            let raw_response = retry_inference(agent.id, generate(), retries, generate);
+            let inference_ms = inference_started.elapsed().as_millis() as u64;
+            
+            // 5-6. Parse, score, remember (shared with the batch engine)
+            let mut result = finish_agent_turn(brain, swarm, scenario, agent, agent_prompt, &raw_response, &product_context);
+            if let Some(timing) = result.timing.as_mut() {
+                timing.inference_ms = inference_ms;
+                timing.total_ms = started.elapsed().as_millis() as u64;
             }
+            let result = limit_response(result, &response_limit);
 
-            // 3. Inject Knowledge into Prompt
-            if !acquired_knowledge.is_empty() {
-                // We inject this BEFORE the final instruction so the agent "knows" it before "speaking"
-                let knowledge_block = format!("\n\n=== REAL-WORLD CONTEXT ACQUIRED ===\n{}\n===================================\nUse the facts above to answer accurately.\n", acquired_knowledge);
-                
-                prompt.push_str(&knowledge_block);
-            }
-            
-            // 4. Inference (Using the gathered knowledge)
-            let raw_response = brain.generate(&prompt, 800, image_data.clone(), pdf_data.clone(), 0.7);
-            
-            // 5. Process & Return
-            let (response_text, thought_process) = scenario.process_response(&raw_response);
-            let sentiment = AgentSwarm::sentiment_from_response(&response_text);
-            let category = AgentSwarm::extract_category(&response_text, scenario.scenario_key());
-
-            SimulationResult {
-                agent_id: agent.id,
-                agent_name: Some(agent.name.clone()),
-                agent_role: agent.name.clone(),
-                agent_demographic: format!("{} ({})", agent.role, agent.demographic),
-                scenario: scenario.scenario_key().to_string(),
-                timestamp: AgentSwarm::get_timestamp(),
-                prompt,
-                response: response_text,
-                thought_process,
-                
-                // IMPORTANT: Populate sources so the UI shows where the data came from
-                sources: if !acquired_knowledge.is_empty() { Some(acquired_knowledge) } else { None },
-                
-                sentiment,
-                category,
+            let done = finished.fetch_add(1, Ordering::Relaxed) + 1;
+            if done.is_multiple_of(report_every) || done == total {
+                on_progress(done, total);
             }
+
+            result
         })
         .collect();
 
     for res in results {
         swarm.add_result(res);
     }
-}
\ No newline at end of file
+}
+
+// Batch counterpart of run_simulation_parallel (SimulationRequest `engine: "batch"`): same prompts,
+// retries and result handling, but inference goes through BatchInferenceEngine, one worker
+// round-trip per `batch_size` prompts. Text only (`settings` attachments are ignored);
+// `on_progress` fires once per finished chunk.
+pub fn run_simulation_batch<F>(
+    brain: &Arc<AgentBrain>,
+    swarm: &Arc<AgentSwarm>,
+    scenario: &dyn Scenario,
+    settings: RunSettings,
+    batch_size: usize,
+    on_progress: F,
+) where
+    F: Fn(usize, usize) + Sync,
+{
+    let RunSettings { product_context, temperature, max_tokens, response_limit, .. } = settings;
+    let agents = swarm.get_agents();
+    let skill_cache = Arc::new(SkillCache::new());
+    let requests = prepare_inference_requests(brain, &agents, scenario, &product_context, skill_timeout(), &skill_cache);
+
+    let mut engine = BatchInferenceEngine::new(Arc::clone(brain), batch_size);
+    engine.temperature = temperature;
+    engine.max_tokens = max_tokens.unwrap_or_else(|| scenario.max_response_tokens());
+    let replies = engine.process_requests(&requests, on_progress);
+
+    for res in convert_to_simulation_results(brain, swarm, &agents, requests, replies, scenario, &product_context) {
+        swarm.add_result(limit_response(res, &response_limit));
+    }
+}
+
+// Extra attempts an agent gets when the brain answers with an error instead of a reply
+pub fn inference_retries() -> usize {
+    std::env::var("ORACULUM_INFERENCE_RETRIES").ok().and_then(|v| v.parse().ok()).unwrap_or(2)
+}
+
+// Re-runs `generate` while the reply is an error string, up to `retries` extra times
+pub fn retry_inference(agent_id: u32, first_reply: String, retries: usize, generate: impl Fn() -> String) -> String {
+    let mut raw_response = first_reply;
+    let mut attempt = 0;
+    while BrainError::is_error_reply(&raw_response) && attempt < retries {
+        attempt += 1;
+        println!("   🔁 Agent {} inference failed ({}), retry {}/{}", agent_id, raw_response, attempt, retries);
+        raw_response = generate();
+    }
+    raw_response
+}
+
+// Applies the request's ResponseLimit to a finished turn; the full answer moves to `response_full`.
+// Failed turns keep their error text as is.
+fn limit_response(mut result: SimulationResult, limit: &ResponseLimit) -> SimulationResult {
+    if result.failed {
+        return result;
+    }
+    if let Some(short) = limit.truncate(&result.response) {
+        result.response_full = Some(std::mem::replace(&mut result.response, short));
+    }
+    result
+}
+
+// Turns one agent's raw reply into its SimulationResult: parse thought/verdict with the scenario,
+// score with the swarm lexicon, remember what was said. Shared by the parallel and batch engines.
+pub fn finish_agent_turn(
+    brain: &AgentBrain,
+    swarm: &AgentSwarm,
+    scenario: &dyn Scenario,
+    agent: &agent_swarm::Agent,
+    agent_prompt: AgentPrompt,
+    raw_response: &str,
+    product_context: &str,
+) -> SimulationResult {
+    let AgentPrompt { prompt, acquired_knowledge, source_refs, skills_ms } = agent_prompt;
+    let failed = BrainError::is_error_reply(raw_response);
+
+    // A failed agent keeps the error text but no opinion
+    let (response_text, thought_process) = if failed {
+        (raw_response.to_string(), None)
+    } else {
+        scenario.process_response(raw_response)
+    };
+    let (sentiment, sentiment_score, category, intent) = if failed {
+        ("neutral".to_string(), 0.0, None, None)
+    } else {
+        (
+            swarm.classify_sentiment(&response_text),
+            swarm.score_sentiment(&response_text),
+            scenario.categorize(&response_text),
+            scenario.purchase_intent(&response_text),
+        )
+    };
+
+    // Remember what was said (persisted across jobs by PersonaMemoryStore)
+    if !failed {
+        // Rated and embedded before locking: both may be brain calls
+        let importance = memory::score_importance(brain, &response_text, 0.3 + 0.7 * sentiment_score.abs());
+        let content = format!("[{}] About {}: I said \"{}\"", scenario.name(), product_context, response_text);
+        let embedding = brain.embed(&content);
+        if let Ok(mut memory) = agent.memory.lock() {
+            memory.add_memory_embedded(content, MemoryType::Observation, importance, embedding);
+        }
+    }
+
+    SimulationResult {
+        agent_id: agent.id,
+        agent_name: Some(agent.name.clone()),
+        agent_role: agent.name.clone(),
+        agent_demographic: format!("{} ({})", agent.role, agent.demographic),
+        scenario: scenario.scenario_key().to_string(),
+        timestamp: AgentSwarm::get_timestamp(),
+        prompt,
+        response: response_text,
+        response_full: None,
+        thought_process,
+        
+        // IMPORTANT: Populate sources so the UI shows where the data came from
+        sources: if !acquired_knowledge.is_empty() { Some(acquired_knowledge) } else { None },
+        source_refs,
+        
+        sentiment,
+        sentiment_score,
+        category,
+        intent,
+        reply_to: None,
+        product: Some(product_context.to_string()),
+        failed,
+        cohort: agent.cohort.clone(),
+        // Skill times are known here; the engine adds inference / total time
+        timing: Some(Timing { skills_ms, ..Timing::default() }),
+    }
+}
+
+// Per-agent budget for all of its skills together (they run concurrently)
+pub fn skill_timeout() -> std::time::Duration {
+    std::time::Duration::from_secs(
+        std::env::var("ORACULUM_SKILL_TIMEOUT_SECS").ok().and_then(|v| v.parse().ok()).unwrap_or(90),
+    )
+}
+
+// One agent's final prompt, plus what its skills contributed to it
+#[derive(Clone, Debug)]
+pub struct AgentPrompt {
+    pub prompt: String,
+    pub acquired_knowledge: String, // Display text; empty when no skill succeeded
+    pub source_refs: Vec<SourceRef>,
+    pub skills_ms: BTreeMap<String, u64>, // Skill id -> wall-clock time (see Timing)
+}
+
+// The scenario prompt for `agent`, biased by what this persona is drawn to. No skills run,
+// so /api/preview can show it without side effects.
+pub fn base_agent_prompt(agent: &agent_swarm::Agent, scenario: &dyn Scenario) -> String {
+    let prompt = scenario.generate_prompt(agent, None);
+    match agent.affinity_hint() {
+        Some(hint) => scenarios::insert_before_response_cue(prompt, &format!("{}\n", hint)),
+        None => prompt,
+    }
+}
+
+// Builds one agent's final prompt: the scenario prompt plus whatever its skills found.
+pub fn build_agent_prompt(
+    brain: &Arc<AgentBrain>,
+    agent: &agent_swarm::Agent,
+    scenario: &dyn Scenario,
+    product_context: &str,
+    skill_timeout: std::time::Duration,
+    skill_cache: &Arc<SkillCache>,
+) -> AgentPrompt {
+    // 1. Generate Base Prompt (Who am I?)
+    let mut prompt = base_agent_prompt(agent, scenario);
+
+    // 2. --- SKILL EXECUTION (WEB / RAG) ---
+    let mut acquired_knowledge = String::new();
+    let mut source_refs = Vec::new();
+    let mut skills_ms = BTreeMap::new();
+    
+    // If the agent has skills (e.g., ["web_scout"]), execute them concurrently
+    if !agent.skills.is_empty() {
+        // Pass the Product Context to the skill
+        let input = SkillInput {
+            query: product_context.to_string(),
+            context: agent.demographic.clone()
+        };
+
+        println!("[AGENT] {} is executing skills: {}", agent.name, agent.skills.join(", "));
+
+        // Outputs come back in the agent's skill order, so the prompt stays stable
+        for (skill_id, output, elapsed) in SkillRegistry::execute_all(brain, &agent.skills, &input, skill_timeout, skill_cache) {
+            skills_ms.insert(skill_id.clone(), elapsed.as_millis() as u64);
+            if output.success {
+                acquired_knowledge.push_str(&format!(
+                    "\n### SENSORY OBSERVATION (Source: {})\n{}\n", 
+                    skill_id.to_uppercase(), 
+                    output.data
+                ));
+                source_refs.push(SourceRef {
+                    source: if output.metadata.source.is_empty() { skill_id.clone() } else { output.metadata.source.clone() },
+                    snippet: output.data.chars().take(SOURCE_SNIPPET_CHARS).collect(),
+                    skill_id,
+                });
+            } else {
+                 println!("[WARN] Skill {} failed for agent {}", skill_id, agent.name);
+            }
+        }
+    }
+
+    // 3. Inject Knowledge into Prompt
+    if !acquired_knowledge.is_empty() {
+        // We inject this BEFORE the final instruction so the agent "knows" it before "speaking"
+        let knowledge_block = format!("\n\n=== REAL-WORLD CONTEXT ACQUIRED ===\n{}\n===================================\nUse the facts above to answer accurately.\n", acquired_knowledge);
+        
+        prompt.push_str(&knowledge_block);
+    }
+
+    AgentPrompt { prompt, acquired_knowledge, source_refs, skills_ms }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scenarios::ProductLaunchScenario;
+
+    #[test]
+    fn skills_query_the_product_context() {
+        let brain = Arc::new(AgentBrain::demo());
+        let mut agent = agent_swarm::Agent::new(1, "Trader");
+        agent.skills = vec!["deep_research".to_string()];
+        let scenario: Box<dyn Scenario> = Box::new(ProductLaunchScenario::new("Masala Oats", "Breakfast", vec![]));
+
+        let built = build_agent_prompt(&brain, &agent, scenario.as_ref(), "Masala Oats 500g", skill_timeout(), &Arc::new(SkillCache::new()));
+
+        // The demo memory echoes its query, so the skill must have been asked about the product
+        assert!(built.acquired_knowledge.contains("compare Masala Oats 500g with"), "{}", built.acquired_knowledge);
+        assert_eq!(built.source_refs.len(), 1);
+        assert!(built.prompt.contains("REAL-WORLD CONTEXT ACQUIRED"));
+    }
+
+    #[test]
+    fn cors_allowlist_comes_from_the_environment() {
+        std::env::remove_var("ORACULUM_DEV");
+        std::env::set_var("ORACULUM_CORS_ORIGINS", "https://app.example.com/, not a url");
+        assert!(matches!(CorsPolicy::from_env(), CorsPolicy::Origins(o) if o == ["https://app.example.com"]));
+
+        std::env::set_var("ORACULUM_DEV", " 1 ");
+        assert!(matches!(CorsPolicy::from_env(), CorsPolicy::Permissive));
+        std::env::remove_var("ORACULUM_DEV");
+        std::env::remove_var("ORACULUM_CORS_ORIGINS");
+    }
+
+    #[test]
+    fn batch_engine_applies_the_run_settings() {
+        let brain = Arc::new(AgentBrain::demo());
+        let swarm = Arc::new(AgentSwarm::new(5));
+        let scenario: Box<dyn Scenario> = Box::new(ProductLaunchScenario::new("Masala Oats", "Breakfast", vec![]));
+        let settings = RunSettings {
+            product_context: "Masala Oats".to_string(),
+            temperature: 0.7,
+            max_tokens: None,
+            response_limit: ResponseLimit { max_sentences: None, max_chars: Some(20) },
+            image_data: None,
+            pdf_data: None,
+        };
+        let chunks = std::sync::atomic::AtomicUsize::new(0);
+
+        run_simulation_batch(&brain, &swarm, scenario.as_ref(), settings, 2, |_, _| {
+            chunks.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        });
+
+        let results = swarm.get_results();
+        assert_eq!(results.len(), 5);
+        assert_eq!(chunks.into_inner(), 3);
+        assert!(results.iter().all(|r| r.response.chars().count() <= 21), "response_limit not applied");
+    }
+}
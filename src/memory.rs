@@ -3,6 +3,13 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use std::cmp::Ordering;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use dashmap::DashMap;
+use crate::brain::{AgentBrain, BrainError};
+use crate::agent_swarm::Agent;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum MemoryType {
@@ -12,6 +19,70 @@ pub enum MemoryType {
     Fact,        
 }
 
+// --- IMPORTANCE SCORING ---
+// Statements with fewer words than this are never worth a brain call
+const TRIVIAL_MEMORY_WORDS: usize = 6;
+const TRIVIAL_IMPORTANCE: f32 = 0.1;
+// Ratings are cached by content; cleared wholesale when it grows past this
+const IMPORTANCE_CACHE_LIMIT: usize = 10_000;
+
+static IMPORTANCE_CACHE: OnceLock<DashMap<String, f32>> = OnceLock::new();
+
+/// How significant `content` is for a persona's later opinions (0.1..=1.0). Returns `fallback`
+/// unless ORACULUM_MEMORY_IMPORTANCE=llm, since rating costs a brain call per memory.
+pub fn score_importance(brain: &AgentBrain, content: &str, fallback: f32) -> f32 {
+    if std::env::var("ORACULUM_MEMORY_IMPORTANCE").is_ok_and(|mode| mode.trim().eq_ignore_ascii_case("llm")) {
+        rate_importance(brain, content, fallback)
+    } else {
+        fallback
+    }
+}
+
+// The `llm` mode: the brain rates `content` 1-10 and the rating is divided by 10. Trivially short
+// contents score low without a call and ratings are cached by content. Error replies and answers
+// without a usable rating return `fallback` and are not cached.
+fn rate_importance(brain: &AgentBrain, content: &str, fallback: f32) -> f32 {
+    let content = content.trim();
+    if content.split_whitespace().count() < TRIVIAL_MEMORY_WORDS {
+        return TRIVIAL_IMPORTANCE;
+    }
+
+    let cache = IMPORTANCE_CACHE.get_or_init(DashMap::new);
+    if let Some(score) = cache.get(content) {
+        return *score;
+    }
+
+    let prompt = format!(
+        "<|user|>On a scale of 1 to 10, where 1 is purely mundane (e.g. a passing remark) and 10 is \
+        extremely significant (e.g. a firm decision to buy or boycott), rate how significant this \
+        statement is for the speaker's future shopping opinions:\n\"{}\"\n\
+        Rate the importance. Answer with a single number.<|end|>\n<|assistant|>",
+        content
+    );
+    match parse_importance(&brain.generate(&prompt, 10, None, None, 0.0)) {
+        Some(score) => {
+            if cache.len() >= IMPORTANCE_CACHE_LIMIT {
+                cache.clear();
+            }
+            cache.insert(content.to_string(), score);
+            score
+        }
+        None => fallback,
+    }
+}
+
+// The first 1-10 number in a rating reply, as 0.1..=1.0. None for error replies: "Error: worker 2
+// is down" must not become a rating of 2.
+fn parse_importance(raw: &str) -> Option<f32> {
+    if BrainError::is_error_reply(raw) {
+        return None;
+    }
+    raw.split(|c: char| !c.is_ascii_digit())
+        .find_map(|token| token.parse::<u32>().ok())
+        .filter(|n| (1..=10).contains(n))
+        .map(|n| n as f32 / 10.0)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Memory {
     pub id: String,
@@ -21,6 +92,9 @@ pub struct Memory {
     pub last_accessed: DateTime<Utc>,
     pub importance: f32, 
     pub related_ids: Vec<String>, 
+    // Cached sentence embedding (see MemoryStream::retrieve_semantic)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embedding: Option<Vec<f32>>,
 }
 
 impl Memory {
@@ -33,7 +107,63 @@ impl Memory {
             last_accessed: Utc::now(),
             importance,
             related_ids: Vec::new(),
+            embedding: None,
+        }
+    }
+}
+
+// Memories untouched for longer than this are subject to `MemoryStream::decay`
+const DECAY_AFTER_HOURS: i64 = 24;
+// Cap applied before a persona's stream is written to the PersonaMemoryStore
+const MAX_PERSISTED_MEMORIES: usize = 200;
+
+// Observations a stream is ready to reflect on, captured under its lock (see MemoryStream::pending_reflection)
+pub struct PendingReflection {
+    prompt: String,
+    source_ids: Vec<String>, // Observations the insights will link back to
+}
+
+// Memory contents to embed, captured under the stream's lock (see MemoryStream::pending_embeddings)
+pub struct PendingEmbeddings {
+    contents: Vec<(String, String)>, // (memory id, content)
+}
+
+impl PendingEmbeddings {
+    /// One `brain.embed` call per memory; run it without holding the stream's lock.
+    /// Memories the brain couldn't embed are left out (and retried on the next retrieval).
+    pub fn embed(self, brain: &AgentBrain) -> Vec<(String, Vec<f32>)> {
+        self.contents
+            .into_iter()
+            .map(|(id, content)| (id, brain.embed(&content)))
+            .filter(|(_, embedding)| !embedding.is_empty())
+            .collect()
+    }
+}
+
+impl PendingReflection {
+    /// Asks the brain for 1-3 insights and turns them into embedded Reflection memories.
+    /// Makes brain calls, so run it without holding the stream's lock. Empty if the brain failed.
+    pub fn reflect(self, brain: &AgentBrain) -> Vec<Memory> {
+        let raw = brain.generate(&self.prompt, 200, None, None, 0.5);
+        if BrainError::is_error_reply(&raw) {
+            return Vec::new();
         }
+
+        raw.lines()
+            .map(|line| line.trim().trim_start_matches(|c: char| c.is_ascii_digit() || matches!(c, '.' | ')' | '-' | '*' | ' ')))
+            .filter(|line| line.len() > 10) // Skip fragments / stray formatting
+            .take(3)
+            .map(|insight| {
+                let importance = score_importance(brain, insight, 0.9);
+                let mut mem = Memory::new(insight.to_string(), MemoryType::Reflection, importance);
+                let embedding = brain.embed(insight);
+                if !embedding.is_empty() {
+                    mem.embedding = Some(embedding);
+                }
+                mem.related_ids = self.source_ids.clone();
+                mem
+            })
+            .collect()
     }
 }
 
@@ -41,11 +171,23 @@ impl Memory {
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct MemoryStream {
     pub memories: Vec<Memory>,
+    // When `decay` last ran, so idle time is only ever charged once
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_decay: Option<DateTime<Utc>>,
 }
 
 impl MemoryStream {
     pub fn new() -> Self {
-        Self { memories: Vec::new() }
+        Self { memories: Vec::new(), last_decay: None }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
     }
 
     pub fn add_memory(&mut self, content: String, kind: MemoryType, importance: f32) {
@@ -53,6 +195,203 @@ impl MemoryStream {
         self.memories.push(mem);
     }
 
+    /// Like `add_memory`, but caches the content's embedding (from `AgentBrain::embed`) so
+    /// semantic retrieval never has to. Callers embed before locking the stream.
+    pub fn add_memory_embedded(&mut self, content: String, kind: MemoryType, importance: f32, embedding: Vec<f32>) {
+        let mut mem = Memory::new(content, kind, importance);
+        if !embedding.is_empty() {
+            mem.embedding = Some(embedding);
+        }
+        self.memories.push(mem);
+    }
+
+    /// Embedding-based retrieval: cosine similarity replaces the keyword hit, blended with the
+    /// same recency/importance weights. Memories added without an embedding are embedded once
+    /// and cached. Falls back to `retrieve` if the brain can't embed the query.
+    /// Calls the brain throughout; a stream behind a lock uses the three steps instead:
+    /// `pending_embeddings` (under the lock) -> `brain.embed` + `PendingEmbeddings::embed`
+    /// (no lock) -> `retrieve_embedded`.
+    pub fn retrieve_semantic(&mut self, brain: &AgentBrain, query: &str, limit: usize) -> Vec<Memory> {
+        let query_vec = brain.embed(query);
+        let embedded = self.pending_embeddings().embed(brain);
+        self.retrieve_embedded(query, &query_vec, embedded, limit)
+    }
+
+    /// Memories still missing an embedding (e.g. loaded from an older memory store).
+    pub fn pending_embeddings(&self) -> PendingEmbeddings {
+        PendingEmbeddings {
+            contents: self.memories
+                .iter()
+                .filter(|m| m.embedding.is_none())
+                .map(|m| (m.id.clone(), m.content.clone()))
+                .collect(),
+        }
+    }
+
+    /// The scoring half of `retrieve_semantic`: caches `embedded` (from `PendingEmbeddings::embed`)
+    /// and ranks against `query_vec`. An empty `query_vec` falls back to keyword `retrieve`.
+    pub fn retrieve_embedded(&mut self, query: &str, query_vec: &[f32], embedded: Vec<(String, Vec<f32>)>, limit: usize) -> Vec<Memory> {
+        for (id, embedding) in embedded {
+            if let Some(mem) = self.memories.iter_mut().find(|m| m.id == id) {
+                mem.embedding = Some(embedding);
+            }
+        }
+        if query_vec.is_empty() {
+            return self.retrieve(query, limit);
+        }
+
+        let now = Utc::now();
+        let mut scored: Vec<(usize, f32)> = self.memories.iter().enumerate().map(|(i, mem)| {
+            let hours_since = (now - mem.creation_timestamp).num_hours() as f32;
+            let recency = 0.99f32.powf(hours_since);
+            let importance = mem.importance;
+            let relevance = mem.embedding.as_deref().map_or(0.0, |e| cosine_similarity(query_vec, e));
+            let score = (recency * 0.5) + (importance * 0.3) + (relevance * 2.0);
+            (i, score)
+        }).collect();
+
+        scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+
+        let mut result = Vec::new();
+        for (idx, _) in scored.into_iter().take(limit) {
+            let mem = &mut self.memories[idx];
+            mem.last_accessed = now;
+            result.push(mem.clone());
+        }
+        result
+    }
+
+    // --- FORGETTING ---
+    // Retention score = recency * 0.5 + importance * 0.3, where recency = 0.99^hours_since_created.
+    // This is `retrieve`'s formula without the query term, so what we forget is what retrieval
+    // would have ranked lowest anyway.
+    fn retention_score(mem: &Memory, now: DateTime<Utc>) -> f32 {
+        let hours_since = (now - mem.creation_timestamp).num_hours() as f32;
+        let recency = 0.99f32.powf(hours_since);
+        (recency * 0.5) + (mem.importance * 0.3)
+    }
+
+    /// Every memory with its query-free retrieval score (recency * 0.5 + importance * 0.3),
+    /// best first. Read-only: unlike `retrieve`, nothing is marked as accessed.
+    pub fn ranked(&self) -> Vec<(&Memory, f32)> {
+        let now = Utc::now();
+        let mut ranked: Vec<(&Memory, f32)> = self.memories.iter().map(|mem| (mem, Self::retention_score(mem, now))).collect();
+        ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+        ranked
+    }
+
+    /// Keeps at most `max_items` memories and returns how many were dropped.
+    /// Facts and Reflections always outrank Observations/Plans; within each group the
+    /// lowest retention score goes first. Survivors keep their original order.
+    pub fn prune(&mut self, max_items: usize) -> usize {
+        if self.memories.len() <= max_items {
+            return 0;
+        }
+
+        let now = Utc::now();
+        let protected = |m: &Memory| matches!(m.memory_type, MemoryType::Fact | MemoryType::Reflection);
+
+        let mut ranked: Vec<(usize, bool, f32)> = self.memories
+            .iter()
+            .enumerate()
+            .map(|(i, m)| (i, protected(m), Self::retention_score(m, now)))
+            .collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then(b.2.partial_cmp(&a.2).unwrap_or(Ordering::Equal)));
+
+        let mut keep = vec![false; self.memories.len()];
+        for (i, _, _) in ranked.into_iter().take(max_items) {
+            keep[i] = true;
+        }
+
+        let before = self.memories.len();
+        let mut idx = 0;
+        self.memories.retain(|_| {
+            let kept = keep[idx];
+            idx += 1;
+            kept
+        });
+        before - self.memories.len()
+    }
+
+    /// Fades memories by how long they have sat unused: importance is multiplied by `factor`
+    /// (e.g. 0.95) per day idle beyond the first `DECAY_AFTER_HOURS`. Idle time already
+    /// charged by an earlier call isn't charged again, so decaying on every load is safe.
+    /// Retrieval refreshes `last_accessed`, so memories that keep being useful don't fade.
+    pub fn decay(&mut self, factor: f32) {
+        let now = Utc::now();
+        let factor = factor.clamp(0.0, 1.0);
+        for mem in self.memories.iter_mut() {
+            let idle_from = mem.last_accessed + chrono::Duration::hours(DECAY_AFTER_HOURS);
+            let charged_until = self.last_decay.map_or(idle_from, |t| t.max(idle_from));
+            let idle_days = (now - charged_until).num_hours() as f32 / 24.0;
+            if idle_days > 0.0 {
+                mem.importance *= factor.powf(idle_days);
+            }
+        }
+        self.last_decay = Some(now);
+    }
+
+    // --- REFLECTION (Generative Agents) ---
+    // Once enough important things have happened since the last reflection, the persona
+    // "steps back" and distils 1-3 higher-level insights. These are stored as Reflection
+    // memories linked (related_ids) to the observations they came from.
+    // Split in two so the brain is never called while the stream's lock is held:
+    // `pending_reflection` (under the lock) -> `PendingReflection::reflect` (no lock) -> `add_reflections`.
+    pub fn pending_reflection(&self, threshold: f32) -> Option<PendingReflection> {
+        let last_reflection = self.memories
+            .iter()
+            .filter(|m| m.memory_type == MemoryType::Reflection)
+            .map(|m| m.creation_timestamp)
+            .max();
+
+        let mut pending: Vec<&Memory> = self.memories
+            .iter()
+            .filter(|m| m.memory_type == MemoryType::Observation)
+            .filter(|m| last_reflection.is_none_or(|t| m.creation_timestamp > t))
+            .collect();
+
+        let accumulated: f32 = pending.iter().map(|m| m.importance).sum();
+        if pending.is_empty() || accumulated < threshold {
+            return None;
+        }
+
+        // Reflect on the most important recent observations
+        pending.sort_by(|a, b| b.importance.partial_cmp(&a.importance).unwrap_or(Ordering::Equal));
+        pending.truncate(8);
+
+        let source_ids: Vec<String> = pending.iter().map(|m| m.id.clone()).collect();
+        let listing: String = pending
+            .iter()
+            .enumerate()
+            .map(|(i, m)| format!("{}. {}\n", i + 1, m.content))
+            .collect();
+
+        let prompt = format!(
+            "<|user|>Here are things you recently said and experienced:\n{}\n\
+            TASK: What 1-3 high-level insights can you infer about your own opinions and priorities?\n\
+            Write each insight on its own line, in first person. No numbering, no extra text.<|end|>\n<|assistant|>",
+            listing
+        );
+
+        Some(PendingReflection { prompt, source_ids })
+    }
+
+    /// Stores reflections produced by `PendingReflection::reflect`.
+    pub fn add_reflections(&mut self, reflections: &[Memory]) {
+        self.memories.extend_from_slice(reflections);
+    }
+
+    /// Most recent reflections first (used to remind a persona of its evolving views).
+    pub fn recent_reflections(&self, limit: usize) -> Vec<&Memory> {
+        let mut reflections: Vec<&Memory> = self.memories
+            .iter()
+            .filter(|m| m.memory_type == MemoryType::Reflection)
+            .collect();
+        reflections.sort_by_key(|m| std::cmp::Reverse(m.creation_timestamp));
+        reflections.truncate(limit);
+        reflections
+    }
+
     pub fn retrieve(&mut self, query: &str, limit: usize) -> Vec<Memory> {
         let now = Utc::now();
         
@@ -79,4 +418,213 @@ impl MemoryStream {
         }
         result
     }
-}
\ No newline at end of file
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 { 0.0 } else { dot / (norm_a * norm_b) }
+}
+
+// --- LONGITUDINAL MEMORY ---
+// Persists each persona's MemoryStream between jobs, keyed by a stable identity
+// (name + demographic), so a regenerated "Priya, Pune, 28y/o" remembers earlier runs.
+// Layout: {ORACULUM_MEMORY_STORE}/{persona_key}.json  (ORACULUM_MEMORY_STORE=off disables it)
+pub struct PersonaMemoryStore {
+    dir: PathBuf,
+    enabled: bool,
+}
+
+impl PersonaMemoryStore {
+    pub fn from_env() -> Self {
+        let dir = std::env::var("ORACULUM_MEMORY_STORE").unwrap_or_else(|_| "memory_store".to_string());
+        let enabled = !matches!(dir.trim().to_lowercase().as_str(), "" | "off" | "none");
+        Self { dir: PathBuf::from(dir), enabled }
+    }
+
+    /// False when persistence is switched off; loading and saving are then no-ops.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// FNV-1a of name + demographic: stable across runs and Rust versions (unlike DefaultHasher).
+    pub fn persona_key(agent: &Agent) -> String {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for byte in agent.name.trim().to_lowercase().bytes()
+            .chain(std::iter::once(b'|'))
+            .chain(agent.demographic.trim().to_lowercase().bytes())
+        {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        format!("{:016x}", hash)
+    }
+
+    /// The persisted stream of one persona, if it has one (no decay applied).
+    pub fn load(&self, agent: &Agent) -> Option<MemoryStream> {
+        if !self.enabled {
+            return None;
+        }
+        MemoryStream::load(self.dir.join(format!("{}.json", Self::persona_key(agent)))).ok()
+    }
+
+    /// Restores remembered streams into matching agents. Returns how many agents had a past.
+    pub fn load_into(&self, agents: &[Agent]) -> usize {
+        if !self.enabled {
+            return 0;
+        }
+        let mut restored = 0;
+        // Control agents are blank slates: no past, and nothing of theirs is kept
+        for agent in agents.iter().filter(|a| !a.is_control()) {
+            let path = self.dir.join(format!("{}.json", Self::persona_key(agent)));
+            if let Ok(mut stream) = MemoryStream::load(&path) {
+                stream.decay(0.95); // Memories fade per idle day unless they keep being recalled
+                if let Ok(mut memory) = agent.memory.lock() {
+                    *memory = stream;
+                    restored += 1;
+                }
+            }
+        }
+        restored
+    }
+
+    pub fn save_all(&self, agents: &[Agent]) -> usize {
+        if !self.enabled {
+            return 0;
+        }
+        if let Err(e) = fs::create_dir_all(&self.dir) {
+            eprintln!("💾 MEMORY ERROR: Cannot create {}: {}", self.dir.display(), e);
+            return 0;
+        }
+
+        let mut saved = 0;
+        for agent in agents.iter().filter(|a| !a.is_control()) {
+            let path = self.dir.join(format!("{}.json", Self::persona_key(agent)));
+            let result = match agent.memory.lock() {
+                Ok(mut memory) if !memory.memories.is_empty() => {
+                    memory.prune(MAX_PERSISTED_MEMORIES);
+                    memory.save(&path)
+                }
+                _ => continue,
+            };
+            match result {
+                Ok(()) => saved += 1,
+                Err(e) => eprintln!("💾 MEMORY ERROR: Failed to persist {}: {}", agent.name, e),
+            }
+        }
+        saved
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn importance_ratings_ignore_error_replies() {
+        assert_eq!(parse_importance("7"), Some(0.7));
+        assert_eq!(parse_importance("I'd say 9/10."), Some(0.9));
+        assert_eq!(parse_importance("Error: worker 2 is down"), None);
+        assert_eq!(parse_importance("Timeout: 3 retries exhausted"), None);
+        assert_eq!(parse_importance("42"), None);
+    }
+
+    #[test]
+    fn llm_importance_scores_through_the_brain() {
+        let brain = AgentBrain::demo();
+        let score = rate_importance(&brain, "I will never buy this brand again after today", 0.05);
+        assert!((0.3..=0.8).contains(&score), "{}", score);
+        assert_eq!(rate_importance(&brain, "Nice pack", 0.05), TRIVIAL_IMPORTANCE);
+    }
+
+    #[test]
+    fn semantic_retrieval_ranks_related_memories_first() {
+        let brain = AgentBrain::demo();
+        let mut stream = MemoryStream::new();
+        for content in ["I loved the mango flavour", "The price felt too high for a snack", "My cousin works in Pune"] {
+            stream.add_memory_embedded(content.to_string(), MemoryType::Observation, 0.5, brain.embed(content));
+        }
+        // Stored without an embedding: embedded on first retrieval and cached
+        stream.add_memory("The flavour was too sweet".to_string(), MemoryType::Observation, 0.5);
+
+        let hits = stream.retrieve_semantic(&brain, "too high a price for a snack", 1);
+        assert_eq!(hits[0].content, "The price felt too high for a snack");
+        assert!(stream.memories.iter().all(|m| m.embedding.is_some()));
+    }
+
+    #[test]
+    fn locked_streams_embed_between_the_locks() {
+        let brain = AgentBrain::demo();
+        let mut stream = MemoryStream::new();
+        stream.add_memory_embedded("I loved the mango flavour".to_string(), MemoryType::Observation, 0.5, brain.embed("I loved the mango flavour"));
+        // As loaded from an older memory store: no embedding yet
+        stream.add_memory("The price felt too high for a snack".to_string(), MemoryType::Observation, 0.5);
+
+        // Under the lock: only the missing embedding is collected, nothing is called
+        let pending = stream.pending_embeddings();
+        // No lock: the brain calls
+        let query_vec = brain.embed("too high a price for a snack");
+        let embedded = pending.embed(&brain);
+        assert_eq!(embedded.len(), 1);
+        // Under the lock again: cache and rank
+        let hits = stream.retrieve_embedded("too high a price for a snack", &query_vec, embedded, 1);
+        assert_eq!(hits[0].content, "The price felt too high for a snack");
+        assert!(stream.memories.iter().all(|m| m.embedding.is_some()));
+        assert!(stream.pending_embeddings().embed(&brain).is_empty());
+
+        // Without a query embedding the keyword ranking answers
+        assert_eq!(stream.retrieve_embedded("mango", &[], Vec::new(), 1)[0].content, "I loved the mango flavour");
+    }
+
+    #[test]
+    fn reflection_waits_for_enough_new_importance() {
+        let mut stream = MemoryStream::new();
+        stream.add_memory("The pack looked premium".to_string(), MemoryType::Observation, 0.4);
+        assert!(stream.pending_reflection(1.0).is_none());
+
+        stream.add_memory("Rs 120 is steep for 40g".to_string(), MemoryType::Observation, 0.7);
+        let pending = stream.pending_reflection(1.0).expect("threshold crossed");
+        assert_eq!(pending.source_ids.len(), 2);
+
+        // Only observations newer than the last reflection count towards the next one
+        let mut insight = Memory::new("I care more about price than packaging".to_string(), MemoryType::Reflection, 0.9);
+        insight.related_ids = pending.source_ids;
+        stream.add_reflections(&[insight]);
+        assert!(stream.pending_reflection(1.0).is_none());
+    }
+
+    #[test]
+    fn prune_keeps_the_most_important_memories() {
+        let mut stream = MemoryStream::new();
+        for (i, importance) in [0.2, 0.9, 0.1, 0.7, 0.4].iter().enumerate() {
+            stream.add_memory(format!("Observation {}", i), MemoryType::Observation, *importance);
+        }
+        stream.add_memory("Prefers local brands".to_string(), MemoryType::Fact, 0.05);
+
+        assert_eq!(stream.prune(3), 3);
+        let kept: Vec<&str> = stream.memories.iter().map(|m| m.content.as_str()).collect();
+        // Facts are protected; survivors keep their original order
+        assert_eq!(kept, vec!["Observation 1", "Observation 3", "Prefers local brands"]);
+    }
+
+    #[test]
+    fn decay_charges_idle_time_once() {
+        let mut stream = MemoryStream::new();
+        stream.add_memory("Old opinion".to_string(), MemoryType::Observation, 0.8);
+        stream.add_memory("Fresh opinion".to_string(), MemoryType::Observation, 0.8);
+        // Three days untouched: one day of grace, two days of decay
+        stream.memories[0].last_accessed = Utc::now() - chrono::Duration::days(3);
+
+        stream.decay(0.5);
+        assert!((stream.memories[0].importance - 0.2).abs() < 0.01);
+        assert_eq!(stream.memories[1].importance, 0.8);
+
+        // Loading the stream again right away must not fade it further
+        stream.decay(0.5);
+        assert!((stream.memories[0].importance - 0.2).abs() < 0.01);
+    }
+}
@@ -0,0 +1,380 @@
+// src/parsing.rs
+// Chain-of-Thought response parsing ([Thinking] / [Verdict] / [Action]), shared by every engine
+
+use serde::{Deserialize, Serialize};
+
+/// Section markers used by `parse_cognitive_response`.
+/// `verdict` is the public answer; `action` is the alternative answer marker (CX Flow).
+#[derive(Clone, Debug)]
+pub struct ResponseTags {
+    pub thought: String,
+    pub verdict: String,
+    pub action: Option<String>,
+}
+
+impl Default for ResponseTags {
+    fn default() -> Self {
+        Self {
+            thought: "[Thinking]".to_string(),
+            verdict: "[Verdict]".to_string(),
+            action: Some("[Action]".to_string()),
+        }
+    }
+}
+
+/// Byte-range slice that never panics on model output (emoji, Devanagari).
+/// `start` is moved forward and `end` backward to the nearest char boundary;
+/// out-of-range or backwards ranges yield "".
+pub fn safe_slice(text: &str, start: usize, end: usize) -> &str {
+    let mut start = start.min(text.len());
+    let mut end = end.min(text.len());
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    if start >= end { "" } else { &text[start..end] }
+}
+
+/// The one thinking/verdict/action parser: `Scenario::process_response`, focus-group turns and
+/// the batch engine all go through it. Returns `(thought, public answer)`.
+/// Markers may come in any order or repeat; without an answer marker the whole (cleaned) text
+/// is the answer. Strips hallucinated artifacts like "---" or "ROOM CONTEXT".
+pub fn parse_cognitive_response(raw: &str, tags: &ResponseTags) -> (Option<String>, String) {
+    let raw_clean = raw.trim();
+    
+    // Helper to clean artifacts
+    let clean = |text: &str| -> String {
+        text.replace("---", "")
+            .replace("ROOM CONTEXT", "")
+            .trim()
+            .to_string()
+    };
+
+    // 1. Locate EVERY marker occurrence. Models don't always respect the order we asked for
+    //    (Phi-3.5 sometimes answers first and "thinks" afterwards), so nothing assumes ordering.
+    let mut markers: Vec<(usize, usize, &str)> = Vec::new(); // (byte idx, tag len, tag)
+    let tag_list = [Some(tags.thought.as_str()), Some(tags.verdict.as_str()), tags.action.as_deref()];
+    for tag in tag_list.into_iter().flatten().filter(|t| !t.is_empty()) {
+        markers.extend(raw_clean.match_indices(tag).map(|(idx, m)| (idx, m.len(), tag)));
+    }
+    markers.sort_by_key(|(idx, _, _)| *idx);
+
+    // 2. Each section runs from the end of its marker to the start of the next one
+    let mut thoughts: Vec<String> = Vec::new();
+    let mut verdict: Option<String> = None;
+    let mut action: Option<String> = None;
+
+    for (i, (idx, len, tag)) in markers.iter().enumerate() {
+        let end = markers.get(i + 1).map(|(next, _, _)| *next).unwrap_or(raw_clean.len());
+        let section = clean(safe_slice(raw_clean, idx + len, end));
+        if section.is_empty() {
+            continue;
+        }
+
+        // Duplicate answer blocks: the first non-empty one is the answer, repeats are ignored
+        if *tag == tags.verdict {
+            verdict.get_or_insert(section);
+        } else if Some(*tag) == tags.action.as_deref() {
+            action.get_or_insert(section);
+        } else {
+            thoughts.push(section);
+        }
+    }
+
+    // 3. The verdict marker wins; the action marker is the fallback (CX Flow)
+    if let Some(answer) = verdict.or(action) {
+        let thought = if thoughts.is_empty() { None } else { Some(thoughts.join("\n")) };
+        return (thought, answer);
+    }
+    
+    // Fallback: Return whole string if tags are missing (Graceful Fail)
+    (None, clean(raw_clean))
+}
+
+/// Optional cap on a public answer (SimulationRequest `max_response_sentences` / `max_response_chars`),
+/// so verbose agents don't dominate the transcript. `None` fields don't limit anything.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ResponseLimit {
+    pub max_sentences: Option<usize>,
+    pub max_chars: Option<usize>,
+}
+
+impl ResponseLimit {
+    /// The shortened answer, or None when `text` already fits. Cuts after a whole sentence where
+    /// possible; a first sentence longer than `max_chars` is cut at a word boundary and gets "…".
+    pub fn truncate(&self, text: &str) -> Option<String> {
+        let text = text.trim();
+        let mut end = text.len();
+        let mut mid_sentence = false;
+
+        if let Some(max) = self.max_sentences {
+            if let Some(cut) = sentence_ends(text).nth(max.max(1) - 1) {
+                end = cut;
+            }
+        }
+        if let Some(max_chars) = self.max_chars {
+            if text[..end].chars().count() > max_chars {
+                let limit = text.char_indices().nth(max_chars).map_or(text.len(), |(i, _)| i);
+                end = match sentence_ends(text).take_while(|&cut| cut <= limit).last() {
+                    Some(cut) => cut,
+                    None => {
+                        mid_sentence = true;
+                        text[..limit].rfind(char::is_whitespace).filter(|&i| i > 0).unwrap_or(limit)
+                    }
+                };
+            }
+        }
+
+        if end >= text.len() {
+            return None;
+        }
+        let mut short = text[..end].trim_end().to_string();
+        if mid_sentence {
+            short.push('…');
+        }
+        Some(short)
+    }
+}
+
+// Byte offsets just past each sentence terminator (. ! ? or the Devanagari danda) that is
+// followed by whitespace; closing quotes/brackets stay with their sentence.
+fn sentence_ends(text: &str) -> impl Iterator<Item = usize> + '_ {
+    let mut chars = text.char_indices().peekable();
+    std::iter::from_fn(move || {
+        while let Some((i, c)) = chars.next() {
+            if !matches!(c, '.' | '!' | '?' | '।') {
+                continue;
+            }
+            let mut end = i + c.len_utf8();
+            while let Some(&(j, next)) = chars.peek() {
+                if matches!(next, '.' | '!' | '?' | '"' | '\'' | ')' | '”' | '’') {
+                    end = j + next.len_utf8();
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            if chars.peek().is_some_and(|(_, next)| next.is_whitespace()) {
+                return Some(end);
+            }
+        }
+        None
+    })
+}
+
+/// Normalized answer to "Would you buy it? (Yes/No/Maybe)" (ProductLaunchScenario verdicts).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PurchaseIntent {
+    Yes,
+    No,
+    Maybe,
+}
+
+// Phrases are matched on whole words after `intent_words` normalization (lowercase, no punctuation).
+// Hedges are checked first ("might not buy" is a Maybe), then refusals ("bilkul nahi" beats "bilkul",
+// "definitely not" beats "definitely"). A bare "no" only counts as the opening answer: mid-sentence
+// it is usually "no doubt" or "no sugar".
+// Words whose polarity depends on what follows ("never go back", "can't skip", "han" as a name,
+// the Hindi tag "ना") only appear inside longer phrases.
+const MAYBE_PHRASES: &[&str] = &[
+    "maybe", "might", "perhaps", "not sure", "unsure", "depends", "it depends", "possibly", "once to try",
+    "try it once", "on the fence", "shayad", "dekhte hain", "dekhenge", "sochna padega", "soch ke", "शायद",
+];
+const NO_PHRASES: &[&str] = &[
+    "nope", "won't", "wont", "would not", "wouldn't", "will not", "not buy", "not buying", "never buy",
+    "never buying", "never again", "i'll pass", "i'd pass", "i'll skip", "i'd skip", "no way", "no thanks",
+    "not for me", "don't think i'd buy", "don't think i'll buy", "definitely not", "absolutely not",
+    "sure but not", "sure just not", "nahi", "nahin", "nai", "kabhi nahi", "bilkul nahi", "nahi lunga",
+    "nahi lungi", "नहीं",
+];
+const YES_PHRASES: &[&str] = &[
+    "yes", "yeah", "yep", "definitely", "absolutely", "sure", "will buy", "would buy", "i'd buy", "i'll buy",
+    "count me in", "haan", "haa", "pakka", "zaroor", "zarur", "bilkul", "lunga", "lungi", "le lunga",
+    "le lungi", "kharidunga", "kharidungi", "chalega", "paisa vasool", "हाँ", "हां", "पक्का", "ज़रूर", "जरूर",
+    "लूँगा", "लूंगा", "लूँगी", "लूंगी",
+];
+const LEADING_ONLY_NO: &[&str] = &["no"];
+// Figures of speech that contain a refusal word but answer nothing ("I won't lie, I'd buy it");
+// masked out before any phrase is matched
+const NEUTRAL_PHRASES: &[&str] = &[
+    "won't lie", "wont lie", "not gonna lie", "not going to lie", "no doubt", "no wonder", "no problem",
+];
+// An explicit answer word this close to the start wins over anything said later ("Yes, though maybe...")
+const LEADING_WORDS: usize = 3;
+
+/// Purchase intent stated in a verdict, or None when it doesn't take a position.
+/// English and Hinglish (romanized or Devanagari) affirmatives, refusals and hedges are recognised.
+pub fn parse_purchase_intent(verdict: &str) -> Option<PurchaseIntent> {
+    let mut text = format!(" {} ", intent_words(verdict).join(" "));
+    if text.trim().is_empty() {
+        return None;
+    }
+    // Each masked word becomes "_", so word positions (and LEADING_WORDS) are unchanged
+    for phrase in NEUTRAL_PHRASES {
+        let pattern = format!(" {} ", phrase);
+        let mask = format!(" {} ", vec!["_"; phrase.split_whitespace().count()].join(" "));
+        while text.contains(&pattern) {
+            text = text.replace(&pattern, &mask);
+        }
+    }
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let buckets = [
+        (PurchaseIntent::Maybe, MAYBE_PHRASES),
+        (PurchaseIntent::No, NO_PHRASES),
+        (PurchaseIntent::Yes, YES_PHRASES),
+    ];
+
+    // 1. The answer the prompt asked for, e.g. "Yes, ..." / "Verdict: Nahi, ...": the earliest
+    //    phrase in the opening words wins, the longer one on a tie ("not sure" over "sure")
+    let lead = format!(" {} ", words.iter().take(LEADING_WORDS + 1).copied().collect::<Vec<_>>().join(" "));
+    let leading = buckets
+        .iter()
+        .chain(std::iter::once(&(PurchaseIntent::No, LEADING_ONLY_NO)))
+        .flat_map(|(intent, phrases)| phrases.iter().map(move |p| (*intent, *p)))
+        .filter_map(|(intent, p)| {
+            let at = lead.find(&format!(" {} ", p))?;
+            (lead[..at].split_whitespace().count() < LEADING_WORDS).then_some((at, std::cmp::Reverse(p.len()), intent))
+        })
+        .min_by_key(|(at, len, _)| (*at, *len))
+        .map(|(_, _, intent)| intent);
+    if leading.is_some() {
+        return leading;
+    }
+
+    // 2. Otherwise the first bucket with a phrase anywhere in the verdict
+    buckets
+        .into_iter()
+        .find(|(_, phrases)| phrases.iter().any(|p| text.contains(&format!(" {} ", p))))
+        .map(|(intent, _)| intent)
+}
+
+// Lowercased words; punctuation splits words except apostrophes ("won't"), so Devanagari
+// vowel signs stay attached to their letters.
+fn intent_words(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .replace('’', "'")
+        .split(|c: char| c.is_whitespace() || c == '।' || (c.is_ascii_punctuation() && c != '\''))
+        .map(|w| w.trim_matches('\''))
+        .filter(|w| !w.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(raw: &str) -> (Option<String>, String) {
+        parse_cognitive_response(raw, &ResponseTags::default())
+    }
+
+    #[test]
+    fn verdict_before_thinking_is_split_correctly() {
+        let (thought, answer) = parse("[Verdict] I'd buy it.\n[Thinking] The price fits my budget.");
+        assert_eq!(thought.as_deref(), Some("The price fits my budget."));
+        assert_eq!(answer, "I'd buy it.");
+    }
+
+    #[test]
+    fn missing_tags_fall_back_to_the_whole_reply() {
+        assert_eq!(parse("  Too pricey for me.\n---\n"), (None, "Too pricey for me.".to_string()));
+        // A lone thinking block has no answer marker, so the whole reply is the answer
+        assert_eq!(parse("[Thinking] Hmm, maybe.").1, "[Thinking] Hmm, maybe.");
+        assert_eq!(parse(""), (None, String::new()));
+    }
+
+    #[test]
+    fn action_answers_when_there_is_no_verdict() {
+        let (thought, answer) = parse("[Action] Ask for a refund.\n[Thinking] It broke in a week.");
+        assert_eq!(thought.as_deref(), Some("It broke in a week."));
+        assert_eq!(answer, "Ask for a refund.");
+        assert_eq!(parse("[Action] Refund.\n[Verdict] Keep it.").1, "Keep it.");
+    }
+
+    #[test]
+    fn reversed_multibyte_sections_keep_their_text() {
+        let (thought, answer) = parse("[Verdict] नहीं, महंगा है 😕\n[Thinking] ₹120 बहुत है");
+        assert_eq!(thought.as_deref(), Some("₹120 बहुत है"));
+        assert_eq!(answer, "नहीं, महंगा है 😕");
+    }
+
+    #[test]
+    fn first_non_empty_verdict_wins() {
+        let (thought, answer) = parse("[Thinking] Hmm.\n[Verdict]\n[Verdict] Yes, once.\n[Verdict] Actually no.");
+        assert_eq!(thought.as_deref(), Some("Hmm."));
+        assert_eq!(answer, "Yes, once.");
+    }
+
+    #[test]
+    fn emoji_next_to_tags_does_not_panic() {
+        let (thought, answer) = parse("🤔[Thinking]😬 pricey🙂[Verdict]👍 maybe");
+        assert_eq!(thought.as_deref(), Some("😬 pricey🙂"));
+        assert_eq!(answer, "👍 maybe");
+    }
+
+    #[test]
+    fn multibyte_text_right_after_a_tag_is_kept() {
+        assert_eq!(parse("[Verdict]🎉 love it").1, "🎉 love it");
+        assert_eq!(parse("[Thinking]सोच रहा हूँ[Verdict]हाँ, लूँगा।").1, "हाँ, लूँगा।");
+    }
+
+    #[test]
+    fn response_limits_cut_after_whole_sentences() {
+        let sentences = |n| ResponseLimit { max_sentences: Some(n), max_chars: None };
+        assert_eq!(sentences(1).truncate("He said \"No way!\" Then he left. Fine."), Some("He said \"No way!\"".to_string()));
+        assert_eq!(sentences(1).truncate("बहुत महंगा है। नहीं लूँगा।"), Some("बहुत महंगा है।".to_string()));
+        // "Rs.120" has no space after the dot, so it isn't a sentence end
+        assert_eq!(sentences(1).truncate("Rs.120 is fair"), None);
+
+        let chars = ResponseLimit { max_sentences: None, max_chars: Some(12) };
+        assert_eq!(chars.truncate("Too pricey for what it is"), Some("Too pricey…".to_string()));
+    }
+
+    #[test]
+    fn purchase_intent_reads_english_and_hinglish_verdicts() {
+        let cases = [
+            ("Yes, though maybe not every week.", Some(PurchaseIntent::Yes)),
+            ("Haan, pakka lunga agar price theek hai", Some(PurchaseIntent::Yes)),
+            ("Paisa vasool, I'd never go back to my old brand", Some(PurchaseIntent::Yes)),
+            ("Can't skip this, I'd buy it", Some(PurchaseIntent::Yes)),
+            ("हाँ, ज़रूर लूँगा।", Some(PurchaseIntent::Yes)),
+            ("No. Too sweet for me.", Some(PurchaseIntent::No)),
+            ("Bilkul nahi, bahut mehenga hai", Some(PurchaseIntent::No)),
+            ("I'll pass, my usual brand is cheaper", Some(PurchaseIntent::No)),
+            ("I would never buy a snack at this price", Some(PurchaseIntent::No)),
+            ("नहीं, ₹120 बहुत है", Some(PurchaseIntent::No)),
+            ("I might not buy it right away", Some(PurchaseIntent::Maybe)),
+            ("Shayad, pehle sample dekhna padega", Some(PurchaseIntent::Maybe)),
+            ("शायद, दाम देखकर", Some(PurchaseIntent::Maybe)),
+            ("Definitely not buying this.", Some(PurchaseIntent::No)),
+            ("Absolutely not.", Some(PurchaseIntent::No)),
+            ("I would definitely not buy it", Some(PurchaseIntent::No)),
+            ("Sure, but not at this price", Some(PurchaseIntent::No)),
+            ("I don't think I'd buy it", Some(PurchaseIntent::No)),
+            ("Not for me.", Some(PurchaseIntent::No)),
+            ("No thanks, too salty", Some(PurchaseIntent::No)),
+            ("I won't lie, I'd buy it", Some(PurchaseIntent::Yes)),
+            ("No doubt, I'd buy it", Some(PurchaseIntent::Yes)),
+            ("अच्छा है ना, लूँगा", Some(PurchaseIntent::Yes)),
+            ("The pack says no added sugar, which is nice", None),
+            ("No doubt about the crunch.", None),
+            ("Han Solo would like the crunch", None),
+            ("", None),
+        ];
+        for (verdict, expected) in cases {
+            assert_eq!(parse_purchase_intent(verdict), expected, "{:?}", verdict);
+        }
+    }
+
+    #[test]
+    fn safe_slice_snaps_to_char_boundaries() {
+        let text = "a🎉b"; // 🎉 is bytes 1..5
+        assert_eq!(safe_slice(text, 1, 5), "🎉");
+        assert_eq!(safe_slice(text, 2, 99), "b"); // Start moves forward past the emoji
+        assert_eq!(safe_slice(text, 0, 3), "a"); // End moves back before it
+        assert_eq!(safe_slice(text, 5, 1), "");
+    }
+}
@@ -2,24 +2,57 @@
 // DYNAMIC VERSION: Context-Sharded Doppelgänger Engine + SKILLS INTEGRATION
 // UPDATED: Fixed Deadlock by removing massive payload injection.
 
-use crate::brain::AgentBrain;
-use crate::agent_swarm::Agent;
+use crate::brain::{AgentBrain, BrainError};
+use crate::agent_swarm::{Agent, TREATMENT_COHORT};
 use crate::memory::MemoryStream; 
 use std::sync::{Arc, Mutex}; 
 use serde_json::Value;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use rand::distributions::{Distribution, WeightedIndex};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
 
 pub struct PersonaGenerator;
 
+// High temperature for varied personas; overridable per request (persona_temperature)
+pub const DEFAULT_PERSONA_TEMPERATURE: f32 = 0.8;
+
+// --- ARCHETYPE STRATEGY ---
+// Each batch gets one "Directional Signal" (key, instruction). Keys are what
+// `archetype_weights` (SimulationRequest) refers to.
+pub const ARCHETYPES: [(&str, &str); 4] = [
+    ("optimists", "FOCUS: Early Adopters & Optimists. Use modern, urban names."),
+    ("skeptics", "FOCUS: Skeptics & Budget-Conscious. Use traditional names."),
+    ("loyalists", "FOCUS: Quality-Conscious & Brand Loyalists. Use specific regional names (e.g. South Indian, Bengali)."),
+    ("critics", "FOCUS: Critics & Detractors. Use diverse names."),
+];
+
+// Agents created per archetype key ("fallback" for agents from failed batches)
+pub type ArchetypeMix = BTreeMap<String, usize>;
+
 impl PersonaGenerator {
+    /// `archetype_weights` (e.g. {"skeptics": 0.6, "optimists": 0.4}) switches the per-batch
+    /// archetype from round-robin to weighted sampling; missing keys weigh 0.
+    /// `seed` picks where failed batches start in the FALLBACK_PERSONAS table.
+    /// Returns the agents plus the realized archetype mix.
     pub fn generate_from_voices(
         count: usize, 
         audience_criteria: &str, 
-        _real_voices: Vec<String>, // INPUT IGNORED to prevent 100MB+ payload deadlock
-        brain: &Arc<AgentBrain>
-    ) -> Vec<Agent> {
+        real_voices: Vec<String>, // Research voices; up to voices_budget() chars ground the prompt
+        brain: &Arc<AgentBrain>,
+        temperature: f32,
+        archetype_weights: Option<&HashMap<String, f32>>,
+        seed: Option<u64>,
+    ) -> (Vec<Agent>, ArchetypeMix) {
         
         let mut agents = Vec::new();
+        let mut mix = ArchetypeMix::new();
+        // Validated up-front (SimulationRequest::validate); a bad map just means round-robin
+        let sampler = archetype_weights.and_then(|weights| {
+            WeightedIndex::new(ARCHETYPES.iter().map(|(key, _)| weights.get(*key).copied().unwrap_or(0.0).max(0.0))).ok()
+        });
+        let mut rng = rand::thread_rng();
         let mut global_id_counter = 1; 
         let mut used_names = HashSet::new();
 
@@ -29,54 +62,58 @@ impl PersonaGenerator {
         let batch_size = 5; 
         let batches = (count as f32 / batch_size as f32).ceil() as usize;
 
+        // Built once: every batch sees the same voices
+        let voices_block = voice_context(brain, &real_voices, voices_budget());
+
         for batch_idx in 0..batches {
             if agents.len() >= count { break; }
 
             // --- ARCHETYPE STRATEGY ---
-            // Instead of sending raw text (which blocks the pipe), we send high-level
-            // "Directional Signals" to the LLM. This keeps the prompt under 1KB.
-            let archetype_instruction = match batch_idx % 4 {
-                0 => "FOCUS: Early Adopters & Optimists. Use modern, urban names.",
-                1 => "FOCUS: Skeptics & Budget-Conscious. Use traditional names.",
-                2 => "FOCUS: Quality-Conscious & Brand Loyalists. Use specific regional names (e.g. South Indian, Bengali).",
-                _ => "FOCUS: Critics & Detractors. Use diverse names."
+            // Each batch also gets a high-level "Directional Signal", so batches differ
+            // even though they share the same voices.
+            let archetype_idx = match &sampler {
+                Some(sampler) => sampler.sample(&mut rng),
+                None => batch_idx % ARCHETYPES.len(),
             };
+            let (archetype_key, archetype_instruction) = ARCHETYPES[archetype_idx];
 
-            // --- THE LIGHTWEIGHT PROMPT ---
-            // We removed 'voice_context' to ensure 100% stability.
+            // --- THE PROMPT ---
+            // The brain is reached over HTTP, so the voices block no longer has to fit a pipe buffer
             let prompt = format!(
                 "<|user|>Task: Generate a JSON array of {} unique Indian consumer personas matching: '{}'.\n\n\
+                {}\
                 DIVERSITY INSTRUCTION: {}\n\
                 CRITICAL RULES:\n\
                 1. USE DIVERSE REGIONAL NAMES: Pick names from South India, Bengal, Punjab, Maharashtra, etc.\n\
                 2. VARY THE SKEPTICISM: Not everyone agrees.\n\
                 3. REALISM: Create realistic 'speaking_style' (e.g. 'Casual', 'Formal', 'Hinglish').\n\
                 \n\
-                Format: [{{ \"name\": \"...\", \"age\": 20, \"city\": \"...\", \"occupation\": \"...\", \"spending_behavior\": \"...\", \"cultural_values\": \"...\", \"speaking_style\": \"...\", \"skepticism_level\": \"...\" }}] \
+                Format: [{{ \"name\": \"...\", \"age\": 20, \"city\": \"...\", \"occupation\": \"...\", \"spending_behavior\": \"...\", \"cultural_values\": \"...\", \"speaking_style\": \"...\", \"skepticism_level\": \"...\", \"product_affinity\": [\"...\"] }}] \
                 \n\
                 Return ONLY JSON. No text.<|end|>\n<|assistant|>",
                 batch_size, 
                 audience_criteria, 
+                voices_block,
                 archetype_instruction
             );
 
-            // Call Python Brain with HIGH TEMPERATURE (0.8)
-            let response_text = brain.generate(&prompt, 1000, None, None, 0.8); 
+            // Call Python Brain with HIGH TEMPERATURE (default 0.8)
+            let response_text = brain.generate(&prompt, 1000, None, None, temperature); 
             let clean_json = clean_json_text(&response_text);
             
-            // Parse & Build
-            if let Ok(parsed) = serde_json::from_str::<Value>(&clean_json) {
+            // Parse & Build (a reply cut off at the token limit keeps its complete personas)
+            let parsed = serde_json::from_str::<Value>(&clean_json).ok().or_else(|| {
+                let salvaged = salvage_json_objects(&response_text)?;
+                println!("   🩹 Truncated JSON: salvaged {} complete personas", salvaged.len());
+                Some(Value::Array(salvaged))
+            });
+            if let Some(parsed) = parsed {
                 if let Some(array) = parsed.as_array() {
                     for item in array {
                         if agents.len() >= count { break; }
 
-                        let mut raw_name = item["name"].as_str().unwrap_or("Agent").to_string();
-                        
-                        // Fallback duplicate handler
-                        if used_names.contains(&raw_name) {
-                            raw_name = format!("{} {}", raw_name, global_id_counter);
-                        }
-                        used_names.insert(raw_name.clone());
+                        // Duplicate handler: "Priya" twice becomes "Priya Iyer", not "Priya 7"
+                        let raw_name = unique_name(item["name"].as_str().unwrap_or("Agent"), &mut used_names);
 
                         let id = global_id_counter;
                         global_id_counter += 1;
@@ -89,6 +126,10 @@ impl PersonaGenerator {
                         let culture = item["cultural_values"].as_str().unwrap_or("Traditional").to_string();
                         let style = item["speaking_style"].as_str().unwrap_or("Neutral").to_string();
                         let skepticism = item["skepticism_level"].as_str().unwrap_or("Medium").to_string();
+                        let affinity: Vec<String> = item["product_affinity"].as_array()
+                            .map(|a| a.iter().filter_map(|v| v.as_str()).map(|s| s.to_string()).collect())
+                            .filter(|a: &Vec<String>| !a.is_empty())
+                            .unwrap_or_else(|| vec!["General".to_string()]);
 
                         let full_demographic = format!("{}, {}y/o, {}, {}", city, age, role, spending);
                         
@@ -99,51 +140,310 @@ impl PersonaGenerator {
                             demographic: full_demographic,
                             beliefs: vec![culture, spending.clone()],
                             spending_profile: spending,
-                            product_affinity: vec!["General".to_string()],
+                            product_affinity: affinity,
                             messaging_resonance: vec![],
                             speaking_style: style.clone(),
                             skepticism_level: skepticism.clone(),
                             
                             // --- Dynamic Skill Assignment ---
-                            skills: if role.to_lowercase().contains("analyst") 
-                                    || role.to_lowercase().contains("engineer")
-                                    || role.to_lowercase().contains("journalist") {
-                                vec!["deep_research".to_string(), "fact_check".to_string()]
-                            } else {
-                                vec!["deep_research".to_string()]
-                            },
+                            skills: skills_for_role(&role),
 
                             simulated_responses: 0,
                             avg_sentiment: 0.5,
+                            cohort: None,
                             memory: Arc::new(Mutex::new(MemoryStream::new())), 
                         };
                         
                         println!("   └── Created: {} ({}) [Style: {}]", raw_name, role, style);
                         agents.push(agent);
+                        *mix.entry(archetype_key.to_string()).or_insert(0) += 1;
                     }
                 }
             } else {
                 // Fallback
                 println!("   ⚠️ JSON Parse Error. Generating Fallback Agent.");
-                let fallback = get_fallback_agents(global_id_counter, 1, audience_criteria);
+                let fallback = get_fallback_agents(global_id_counter, 1, audience_criteria, seed);
                 global_id_counter += 1;
                 agents.extend(fallback);
+                *mix.entry("fallback".to_string()).or_insert(0) += 1;
             }
         }
+
+        // Batches don't see each other, so the same persona can come back twice
+        let mutated = Self::diversify(&mut agents);
+        if mutated > 0 {
+            println!("   🔀 Diversified {} duplicate personas", mutated);
+        }
+        println!("✨ GENERATOR: Swarm diversity {:.2}", Self::diversity(&agents));
+        println!("✨ GENERATOR: Archetype mix {:?}", mix);
         
-        agents
+        (agents, mix)
+    }
+
+    /// Fixed, inference-free panel (the same agents used when generation fails), e.g. for previews.
+    /// The same `seed` always yields the same panel.
+    pub fn fallback_panel(count: usize, criteria: &str, seed: Option<u64>) -> Vec<Agent> {
+        get_fallback_agents(1, count, criteria, seed)
+    }
+
+    // --- DIVERSITY ---
+
+    /// Makes every (role, demographic, skepticism_level) tuple unique by moving duplicates
+    /// to another city from CITY_POOL (or, as a last resort, tagging them as a variant).
+    /// Returns how many agents were changed.
+    pub fn diversify(agents: &mut [Agent]) -> usize {
+        let mut seen: HashSet<(String, String, String)> = HashSet::new();
+        let mut mutated = 0;
+
+        for (idx, agent) in agents.iter_mut().enumerate() {
+            let key = |demographic: &str| (agent.role.clone(), demographic.to_string(), agent.skepticism_level.clone());
+            if seen.insert(key(&agent.demographic)) {
+                continue;
+            }
+
+            // Rotate the starting city so duplicates don't all land in the same place
+            let relocated = (0..CITY_POOL.len())
+                .map(|i| with_city(&agent.demographic, CITY_POOL[(idx + i) % CITY_POOL.len()]))
+                .find(|demographic| !seen.contains(&key(demographic)));
+            let demographic = relocated.unwrap_or_else(|| {
+                (2..)
+                    .map(|n| format!("{} (variant {})", agent.demographic, n))
+                    .find(|demographic| !seen.contains(&key(demographic)))
+                    .unwrap_or_default()
+            });
+
+            seen.insert(key(&demographic));
+            agent.demographic = demographic;
+            mutated += 1;
+        }
+        mutated
+    }
+
+    /// 0.0 - 1.0: average share of distinct values across role, city, skepticism, speaking
+    /// style and spending profile. 1.0 means no two agents share any of these.
+    pub fn diversity(agents: &[Agent]) -> f32 {
+        if agents.len() < 2 {
+            return 1.0;
+        }
+
+        let distinct_share = |field: &dyn Fn(&Agent) -> String| {
+            let values: HashSet<String> = agents.iter().map(|a| field(a).to_lowercase()).collect();
+            (values.len() - 1) as f32 / (agents.len() - 1) as f32
+        };
+        let fields: [&dyn Fn(&Agent) -> String; 5] = [
+            &|a| a.role.clone(),
+            &|a| a.demographic.split(", ").next().unwrap_or_default().to_string(), // City
+            &|a| a.skepticism_level.clone(),
+            &|a| a.speaking_style.clone(),
+            &|a| a.spending_profile.clone(),
+        ];
+
+        fields.iter().map(|field| distinct_share(*field)).sum::<f32>() / fields.len() as f32
+    }
+
+    // --- CONTROL COHORT ---
+
+    /// Turns `fraction` of the panel (at least one agent, never all of them) into blank-slate
+    /// control agents (Agent::make_control); the rest are tagged as treatment. Control agents are
+    /// spread evenly through the panel so every archetype batch gives some up.
+    /// Returns the control count (0 for panels too small to split).
+    pub fn assign_control_cohort(agents: &mut [Agent], fraction: f32) -> usize {
+        let total = agents.len();
+        if total < 2 {
+            return 0;
+        }
+        let controls = ((total as f32 * fraction).round() as usize).clamp(1, total - 1);
+        let control_idx: HashSet<usize> = (0..controls).map(|k| k * total / controls).collect();
+
+        for (idx, agent) in agents.iter_mut().enumerate() {
+            if control_idx.contains(&idx) {
+                agent.make_control();
+            } else {
+                agent.cohort = Some(TREATMENT_COHORT.to_string());
+            }
+        }
+        controls
     }
 
+    #[allow(dead_code)] // Voice-less shorthand; the API always passes research voices
     pub fn generate_batch(count: usize, criteria: &str, brain: &Arc<AgentBrain>) -> Vec<Agent> {
-        Self::generate_from_voices(count, criteria, Vec::new(), brain)
+        Self::generate_from_voices(count, criteria, Vec::new(), brain, DEFAULT_PERSONA_TEMPERATURE, None, None).0
+    }
+
+    // --- PERSONA REUSE ---
+    // Generation costs several LLM calls, so a panel can be saved once and replayed.
+    // NOTE: `memory` is #[serde(skip)], so reloaded agents start with a fresh MemoryStream.
+    pub fn save_to_file(agents: &[Agent], path: &Path) -> Result<(), Box<dyn Error>> {
+        fs::write(path, serde_json::to_string_pretty(agents)?)?;
+        println!("💾 GENERATOR: Saved {} personas to {}", agents.len(), path.display());
+        Ok(())
+    }
+
+    pub fn load_from_file(path: &Path) -> Result<Vec<Agent>, Box<dyn Error>> {
+        let agents: Vec<Agent> = serde_json::from_str(&fs::read_to_string(path)?)?;
+        println!("📂 GENERATOR: Loaded {} saved personas from {}", agents.len(), path.display());
+        Ok(agents)
+    }
+
+    /// Saves a panel under its name in the panel directory (see `panel_path`).
+    pub fn save_panel(agents: &[Agent], name: &str) -> Result<(), Box<dyn Error>> {
+        let path = panel_path(name)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        Self::save_to_file(agents, &path)
+    }
+
+    pub fn load_panel(name: &str) -> Result<Vec<Agent>, Box<dyn Error>> {
+        Self::load_from_file(&panel_path(name)?)
     }
 }
 
+// Saved panels are `<ORACULUM_PERSONA_PANELS>/<name>.json`. Requests only ever name a panel,
+// so a client can't read or overwrite arbitrary files on the server.
+const DEFAULT_PANEL_DIR: &str = "persona_panels";
+const MAX_PANEL_NAME_LEN: usize = 64;
+
+/// File behind a panel name. Names are letters, digits, '-' and '_' only, which rules out
+/// separators, "..", and absolute paths.
+pub fn panel_path(name: &str) -> Result<PathBuf, String> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_PANEL_NAME_LEN
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(format!(
+            "persona panel names must be 1-{} letters, digits, '-' or '_' (got '{}')",
+            MAX_PANEL_NAME_LEN, name
+        ));
+    }
+    let dir = std::env::var("ORACULUM_PERSONA_PANELS").unwrap_or_else(|_| DEFAULT_PANEL_DIR.to_string());
+    Ok(PathBuf::from(dir).join(format!("{}.json", name)))
+}
+
 // --- UTILS ---
 
+// Chars of research voices the persona prompt may carry (ORACULUM_PERSONA_VOICES_CHARS; 0 = none)
+const DEFAULT_VOICES_CHARS: usize = 16 * 1024;
+
+pub fn voices_budget() -> usize {
+    std::env::var("ORACULUM_PERSONA_VOICES_CHARS").ok().and_then(|v| v.parse().ok()).unwrap_or(DEFAULT_VOICES_CHARS)
+}
+
+// Prompt block grounding the personas in real consumer voices. Whole voices are kept while they
+// fit 3/4 of `budget`; the rest are summarized into themes by the brain (one call) instead of
+// being cut mid-sentence. Empty when there are no usable voices or the budget is 0.
+fn voice_context(brain: &AgentBrain, voices: &[String], budget: usize) -> String {
+    let voices: Vec<&str> = voices
+        .iter()
+        .map(|v| v.trim())
+        .filter(|v| !v.is_empty() && !v.starts_with("SYSTEM_ALERT"))
+        .collect();
+    if voices.is_empty() || budget == 0 {
+        return String::new();
+    }
+
+    let total: usize = voices.iter().map(|v| v.len() + 3).sum();
+    let verbatim_budget = if total <= budget { budget } else { budget * 3 / 4 };
+    let mut used = 0;
+    let split = voices
+        .iter()
+        .position(|v| {
+            used += v.len() + 3; // "- " + newline
+            used > verbatim_budget
+        })
+        .unwrap_or(voices.len());
+    let (kept, overflow) = voices.split_at(split);
+
+    let mut block = String::from("--- REAL CONSUMER VOICES (ground the personas in these) ---\n");
+    for voice in kept {
+        block.push_str(&format!("- {}\n", voice));
+    }
+
+    if !overflow.is_empty() {
+        // The summary input is capped too (whole voices only), so a huge crawl can't blow the context
+        let mut input_used = 0;
+        let input: Vec<&str> = overflow
+            .iter()
+            .copied()
+            .take_while(|v| {
+                input_used += v.len() + 3;
+                input_used <= budget * 4
+            })
+            .collect();
+        let prompt = format!(
+            "<|user|>Summarize the recurring themes in these consumer comments as 3-6 short bullet points. \
+            Keep concrete complaints, praise and price remarks. No preamble.\n\n{}<|end|>\n<|assistant|>",
+            input.iter().map(|v| format!("- {}", v)).collect::<Vec<_>>().join("\n")
+        );
+        let summary = brain.generate(&prompt, 300, None, None, 0.3);
+        if BrainError::is_error_reply(&summary) || summary.trim().is_empty() {
+            println!("   ⚠️ GENERATOR: Could not summarize {} extra voices; using the first {}", overflow.len(), kept.len());
+        } else {
+            println!("   📝 GENERATOR: {} voices verbatim, {} summarized into themes", kept.len(), input.len());
+            block.push_str(&format!("Themes from {} more voices:\n{}\n", input.len(), summary.trim()));
+        }
+    }
+    block.push('\n');
+    block
+}
+
+// Regional surnames used to disambiguate repeated names (North, South, East, West, Central)
+const SURNAME_POOL: [&str; 20] = [
+    "Sharma", "Iyer", "Banerjee", "Patil", "Verma",
+    "Reddy", "Das", "Deshmukh", "Gill", "Nair",
+    "Chatterjee", "Kulkarni", "Sandhu", "Menon", "Bose",
+    "Joshi", "Rao", "Mukherjee", "Shah", "Pillai",
+];
+
+// Returns a name not yet in `used` (and records it). Collisions keep the first name and
+// try surnames from the pool; a numeric suffix is the last resort.
+fn unique_name(raw: &str, used: &mut HashSet<String>) -> String {
+    let raw = raw.trim();
+    let mut candidate = raw.to_string();
+
+    if used.contains(&candidate) {
+        let first_name = raw.split_whitespace().next().unwrap_or("Agent");
+        // Rotate the starting surname so repeated collisions don't all try "Sharma" first
+        let offset = used.len() % SURNAME_POOL.len();
+
+        candidate = (0..SURNAME_POOL.len())
+            .map(|i| format!("{} {}", first_name, SURNAME_POOL[(offset + i) % SURNAME_POOL.len()]))
+            .find(|name| !used.contains(name))
+            .unwrap_or_else(|| {
+                (2..)
+                    .map(|n| format!("{} {}", raw, n))
+                    .find(|name| !used.contains(name))
+                    .unwrap_or_default()
+            });
+    }
+
+    used.insert(candidate.clone());
+    candidate
+}
+
+// Cities used to relocate duplicate personas (see PersonaGenerator::diversify)
+const CITY_POOL: [&str; 12] = [
+    "Mumbai", "Delhi", "Bengaluru", "Chennai", "Kolkata", "Hyderabad",
+    "Pune", "Ahmedabad", "Jaipur", "Lucknow", "Kochi", "Chandigarh",
+];
+
+// Demographics are "City, 25y/o, Role, Spending": swap the city segment
+// (or prefix one when the demographic has no city).
+fn with_city(demographic: &str, city: &str) -> String {
+    match demographic.split_once(", ") {
+        Some((_, rest)) => format!("{}, {}", city, rest),
+        None => format!("{}, {}", city, demographic),
+    }
+}
+
 fn clean_json_text(text: &str) -> String {
-    let start = text.find('[').unwrap_or(0);
-    let end = text.rfind(']').map(|i| i + 1).unwrap_or(text.len());
+    clean_json_block(text, '[', ']')
+}
+
+// Tolerant JSON extraction: keeps the outermost `open`..`close` span and strips markdown fences.
+pub fn clean_json_block(text: &str, open: char, close: char) -> String {
+    let start = text.find(open).unwrap_or(0);
+    let end = text.rfind(close).map(|i| i + 1).unwrap_or(text.len());
     if start < end {
         text[start..end].replace("```json", "").replace("```", "").trim().to_string()
     } else {
@@ -151,22 +451,204 @@ fn clean_json_text(text: &str) -> String {
     }
 }
 
-fn get_fallback_agents(start_id: u32, needed: usize, criteria: &str) -> Vec<Agent> {
+// Repair for truncated/invalid arrays: every balanced top-level `{...}` that parses on its own.
+// `[{a}, {b}, {"name": "Pri` -> [a, b]. Braces inside strings are ignored. None if nothing parses.
+fn salvage_json_objects(text: &str) -> Option<Vec<Value>> {
+    let mut objects = Vec::new();
+    let mut depth = 0usize;
+    let mut start = None;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (idx, c) in text.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' => {
+                if depth == 0 {
+                    start = Some(idx);
+                }
+                depth += 1;
+            }
+            '}' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    if let Some(begin) = start.take() {
+                        if let Ok(value @ Value::Object(_)) = serde_json::from_str::<Value>(&text[begin..=idx]) {
+                            objects.push(value);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if objects.is_empty() { None } else { Some(objects) }
+}
+
+// Professions that check claims get fact_check on top of deep_research
+fn skills_for_role(role: &str) -> Vec<String> {
+    let role = role.to_lowercase();
+    if role.contains("analyst") || role.contains("engineer") || role.contains("journalist") {
+        vec!["deep_research".to_string(), "fact_check".to_string()]
+    } else {
+        vec!["deep_research".to_string()]
+    }
+}
+
+// --- FALLBACK PANEL ---
+// Used when persona generation fails (and for previews): hand-written archetypes that differ in
+// city, age, occupation, spending, skepticism and voice, so a degraded run still has a varied panel.
+struct FallbackPersona {
+    name: &'static str,
+    age: u32,
+    city: &'static str,
+    role: &'static str,
+    spending: &'static str,
+    style: &'static str,
+    skepticism: &'static str,
+    beliefs: [&'static str; 2],
+    affinity: &'static str,
+}
+
+const FALLBACK_PERSONAS: [FallbackPersona; 12] = [
+    FallbackPersona { name: "Priya Sharma", age: 28, city: "Mumbai", role: "Marketing Executive", spending: "Premium Seeker", style: "Casual", skepticism: "Low", beliefs: ["New launches are worth trying once", "Instagram reviews shape what I buy"], affinity: "Trendy Brands" },
+    FallbackPersona { name: "Ramesh Iyer", age: 52, city: "Chennai", role: "Bank Manager", spending: "Value Conscious", style: "Formal", skepticism: "High", beliefs: ["I read every label before buying", "Old brands earned my trust over decades"], affinity: "Heritage Brands" },
+    FallbackPersona { name: "Aditya Verma", age: 24, city: "Bengaluru", role: "Software Engineer", spending: "Convenience First", style: "Analytical", skepticism: "Medium", beliefs: ["Specs and numbers beat marketing", "Quick-commerce saves my evenings"], affinity: "Tech-Forward Products" },
+    FallbackPersona { name: "Sunita Devi", age: 41, city: "Lucknow", role: "Homemaker", spending: "Budget Constrained", style: "Emotional", skepticism: "High", beliefs: ["Every rupee of the household budget counts", "My family's health comes first"], affinity: "Value Packs" },
+    FallbackPersona { name: "Arjun Mehta", age: 35, city: "Ahmedabad", role: "Small Business Owner", spending: "Bulk Buyer", style: "Direct", skepticism: "Medium", beliefs: ["Margins matter more than hype", "I prefer brands my customers already ask for"], affinity: "Wholesale Deals" },
+    FallbackPersona { name: "Kavya Nair", age: 30, city: "Kochi", role: "Doctor", spending: "Quality Over Price", style: "Analytical", skepticism: "High", beliefs: ["Health claims need evidence", "Ingredients lists tell the real story"], affinity: "Clean-Label Products" },
+    FallbackPersona { name: "Rohit Singh", age: 19, city: "Delhi", role: "College Student", spending: "Pocket Money", style: "Rant", skepticism: "Low", beliefs: ["If my friends use it, I'll try it", "Discounts decide most of my purchases"], affinity: "Snackable Formats" },
+    FallbackPersona { name: "Ananya Banerjee", age: 33, city: "Kolkata", role: "Journalist", spending: "Selective Spender", style: "Formal", skepticism: "High", beliefs: ["Brands overstate their claims", "Local makers deserve a chance"], affinity: "Regional Brands" },
+    FallbackPersona { name: "Harpreet Kaur", age: 46, city: "Chandigarh", role: "School Teacher", spending: "Moderate", style: "Casual", skepticism: "Medium", beliefs: ["Kids' nutrition is non-negotiable", "I compare prices across three apps"], affinity: "Family Packs" },
+    FallbackPersona { name: "Vikram Reddy", age: 38, city: "Hyderabad", role: "Financial Analyst", spending: "Premium Seeker", style: "Analytical", skepticism: "Medium", beliefs: ["Price per gram is the only fair comparison", "Premium is fine if the quality is measurable"], affinity: "Premium Variants" },
+    FallbackPersona { name: "Meena Patil", age: 58, city: "Pune", role: "Retired Government Clerk", spending: "Frugal", style: "Direct", skepticism: "High", beliefs: ["Homemade is always better", "Ads never tell the full truth"], affinity: "Traditional Recipes" },
+    FallbackPersona { name: "Sameer Khan", age: 27, city: "Jaipur", role: "Delivery Partner", spending: "Budget Constrained", style: "Casual", skepticism: "Low", beliefs: ["Whatever is fast and filling works", "Small packs fit my daily budget"], affinity: "Sachet Packs" },
+];
+
+/// Agents `start_id..start_id + needed`, drawn from FALLBACK_PERSONAS in order. `seed` rotates the
+/// starting persona; once the table wraps, each lap moves everyone to another CITY_POOL city.
+fn get_fallback_agents(start_id: u32, needed: usize, criteria: &str, seed: Option<u64>) -> Vec<Agent> {
+    let offset = seed.map_or(0, |seed| (seed % FALLBACK_PERSONAS.len() as u64) as usize);
     let mut fallbacks = Vec::new();
     for i in 0..needed {
         let id = start_id + i as u32;
+        let slot = offset + id.saturating_sub(1) as usize;
+        let persona = &FALLBACK_PERSONAS[slot % FALLBACK_PERSONAS.len()];
+        let lap = slot / FALLBACK_PERSONAS.len();
+
+        let city = match lap {
+            0 => persona.city,
+            _ => {
+                let home = CITY_POOL.iter().position(|c| *c == persona.city).unwrap_or(0);
+                CITY_POOL[(home + lap) % CITY_POOL.len()]
+            }
+        };
+        let mut beliefs: Vec<String> = persona.beliefs.iter().map(|b| b.to_string()).collect();
+        if !criteria.trim().is_empty() {
+            beliefs.push(format!("I belong to this audience: {}", criteria.trim()));
+        }
+
         fallbacks.push(Agent {
             id,
-            name: format!("Participant {}", id),
-            role: "General Consumer".to_string(),
-            demographic: format!("Target: {}", criteria),
-            beliefs: vec![], spending_profile: "Moderate".to_string(), 
-            product_affinity: vec![], messaging_resonance: vec![], 
-            speaking_style: "Neutral".to_string(), skepticism_level: "Medium".to_string(),
-            skills: vec!["deep_research".to_string()],
-            simulated_responses: 0, avg_sentiment: 0.5,
+            name: persona.name.to_string(),
+            role: persona.role.to_string(),
+            demographic: format!("{}, {}y/o, {}, {}", city, persona.age, persona.role, persona.spending),
+            beliefs,
+            spending_profile: persona.spending.to_string(),
+            product_affinity: vec![persona.affinity.to_string()],
+            messaging_resonance: vec![],
+            speaking_style: persona.style.to_string(),
+            skepticism_level: persona.skepticism.to_string(),
+            skills: skills_for_role(persona.role),
+            simulated_responses: 0, avg_sentiment: 0.5, cohort: None,
             memory: Arc::new(Mutex::new(MemoryStream::new())),
         });
     }
     fallbacks
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panel_names_cannot_escape_the_panel_directory() {
+        for name in ["../etc/passwd", "/etc/passwd", "..", "a/b", "a\\b", "", "panel.json"] {
+            assert!(panel_path(name).is_err(), "{} was accepted", name);
+        }
+        let path = panel_path("mumbai-moms_v2").unwrap();
+        assert_eq!(path.file_name().and_then(|n| n.to_str()), Some("mumbai-moms_v2.json"));
+    }
+
+    #[test]
+    fn colliding_names_get_regional_surnames() {
+        let mut used = HashSet::new();
+        let names: Vec<String> = (0..=SURNAME_POOL.len()).map(|_| unique_name("Priya", &mut used)).collect();
+
+        assert_eq!(names[0], "Priya");
+        assert_eq!(names.iter().collect::<HashSet<_>>().len(), names.len());
+        // Every surname is tried before falling back to a number
+        assert!(names.iter().all(|n| !n.ends_with(char::is_numeric)));
+        assert_eq!(unique_name("Priya", &mut used), "Priya 2");
+    }
+
+    #[test]
+    fn generated_panels_have_unique_readable_names() {
+        // The demo brain only knows 8 names, so a 40-agent panel collides on every batch
+        let brain = Arc::new(AgentBrain::demo());
+        let voices = vec!["Too sweet for me".to_string(), "Love the crunch".to_string()];
+        let (agents, _) = PersonaGenerator::generate_from_voices(40, "Urban snackers", voices, &brain, 0.8, None, Some(1));
+
+        assert_eq!(agents.len(), 40);
+        let names: HashSet<&str> = agents.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names.len(), 40);
+        assert!(agents.iter().all(|a| !a.name.ends_with(char::is_numeric)), "numeric suffix used");
+    }
+
+    #[test]
+    fn truncated_arrays_keep_their_complete_personas() {
+        let reply = r#"```json
+[{"name": "Asha", "city": "Pune"}, {"name": "Ravi {the \"quiet\" one}", "age": 31},
+ {"name": "Meera", "product_affinity": ["tea", "snacks"]}, {"name": "Kabir"}, {"name": "Pri"#;
+        assert!(serde_json::from_str::<Value>(&clean_json_text(reply)).is_err());
+
+        let salvaged = salvage_json_objects(reply).unwrap();
+        let names: Vec<&str> = salvaged.iter().filter_map(|v| v["name"].as_str()).collect();
+        assert_eq!(names, ["Asha", "Ravi {the \"quiet\" one}", "Meera", "Kabir"]);
+    }
+
+    #[test]
+    fn salvage_gives_up_when_nothing_parses() {
+        assert!(salvage_json_objects("Sorry, I can't help with that.").is_none());
+        assert!(salvage_json_objects(r#"[{"name": "Pri"#).is_none());
+    }
+
+    #[test]
+    fn long_voice_lists_are_not_cut_to_2000_bytes() {
+        let brain = AgentBrain::demo();
+        let voices: Vec<String> = (1..=40)
+            .map(|i| format!("Voice {}: I switched to this brand last year because the old one got too sweet and pricey.", i))
+            .collect();
+        assert!(voices.iter().map(String::len).sum::<usize>() > 2000);
+
+        let block = voice_context(&brain, &voices, DEFAULT_VOICES_CHARS);
+        assert!(block.len() > 2000);
+        assert!(voices.iter().all(|v| block.contains(&format!("- {}\n", v))), "a voice was dropped or cut");
+
+        // Over budget: whole voices up to 3/4 of it, the rest summarized instead of truncated
+        let block = voice_context(&brain, &voices, 2000);
+        assert!(block.contains(&format!("- {}\n", voices[0])));
+        assert!(!block.contains(&voices[39]));
+        assert!(block.contains("Themes from"));
+    }
+}
@@ -1,23 +1,86 @@
 // src/reporter.rs
-// Enhanced CSV & JSON Export with proper escaping
+// Enhanced CSV, JSON, XLSX & Markdown Export with proper escaping
 // UPDATE: Now captures 'thought_process' (Hidden Thoughts)
 
-use crate::agent_swarm::{Agent, SimulationResult};
-use std::fs::File;
+use crate::agent_swarm::{Agent, AgentSwarm, SimulationResult, VerdictDistribution};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
 use std::error::Error;
+use std::io::Write;
 use csv::Writer;
+use rust_xlsxwriter::{Format, Workbook};
 
 pub struct Reporter;
 
+/// Where `Reporter::export_all` wrote one run's files.
+#[derive(Debug, Clone)]
+pub struct ExportPaths {
+    pub dir: PathBuf,
+    pub csv: PathBuf,
+    pub jsonl: PathBuf,
+    pub xlsx: PathBuf,
+    pub json: PathBuf,
+    pub markdown: PathBuf,
+}
+
+// Excel rejects cells longer than this (in chars); longer text is cut with "…"
+const XLSX_MAX_CELL_CHARS: usize = 32_767;
+
 impl Reporter {
+    /// Archives one run under `dir/<job_id>_<UTC timestamp>/` (results.csv, results.jsonl,
+    /// results.xlsx, personas.json, report.md),
+    /// so repeated exports never overwrite each other. Creates `dir` if needed.
+    pub fn export_all(
+        dir: impl AsRef<Path>,
+        job_id: &str,
+        agents: &[Agent],
+        results: &[SimulationResult],
+    ) -> Result<ExportPaths, Box<dyn Error>> {
+        // Job ids are UUIDs, but keep anything path-like out of the directory name
+        let safe_id: String = job_id
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%SZ");
+        let run_dir = dir.as_ref().join(format!("{}_{}", safe_id, stamp));
+        fs::create_dir_all(&run_dir)?;
+
+        let paths = ExportPaths {
+            csv: run_dir.join("results.csv"),
+            jsonl: run_dir.join("results.jsonl"),
+            xlsx: run_dir.join("results.xlsx"),
+            json: run_dir.join("personas.json"),
+            markdown: run_dir.join("report.md"),
+            dir: run_dir,
+        };
+        Self::export_csv(&paths.csv.to_string_lossy(), results)?;
+        Self::export_jsonl(&paths.jsonl.to_string_lossy(), results)?;
+        Self::export_xlsx(&paths.xlsx.to_string_lossy(), agents, results)?;
+        Self::export_json(&paths.json.to_string_lossy(), agents, results)?;
+        Self::export_markdown(&paths.markdown.to_string_lossy(), agents, results, None)?;
+
+        Ok(paths)
+    }
+
     pub fn export_csv(
         filename: &str,
         results: &[SimulationResult],
     ) -> Result<(), Box<dyn Error>> {
-        let mut wtr = Writer::from_path(filename)?;
+        Self::export_csv_to_writer(File::create(filename)?, results)?;
+        println!("✅ CSV exported to: {}", filename);
+        Ok(())
+    }
+
+    /// Same rows as `export_csv`, written to any sink (file, HTTP body, buffer).
+    /// Rows are serialized one at a time, so the full CSV is never held in memory.
+    pub fn export_csv_to_writer<W: Write>(
+        writer: W,
+        results: &[SimulationResult],
+    ) -> Result<(), Box<dyn Error>> {
+        let mut wtr = Writer::from_writer(writer);
 
         // Write CSV header
-        wtr.write_record(&[
+        wtr.write_record([
             "agent_id",
             "agent_role",
             "agent_demographic", 
@@ -27,12 +90,14 @@ impl Reporter {
             "response",
             "thought_process", // <--- NEW COLUMN
             "sentiment",
+            "sentiment_score",
             "category",
+            "sources", // Skill evidence ("Glass Box"); multi-line, quoted by the csv writer
         ])?;
 
         // Write each result
         for result in results {
-            wtr.write_record(&[
+            wtr.write_record([
                 &result.agent_id.to_string(),
                 &result.agent_role,
                 &result.agent_demographic, 
@@ -43,12 +108,13 @@ impl Reporter {
                 // Handle optional thought process safely
                 result.thought_process.as_deref().unwrap_or(""),
                 &result.sentiment,
+                &format!("{:.3}", result.sentiment_score),
                 result.category.as_deref().unwrap_or(""),
+                result.sources.as_deref().unwrap_or(""),
             ])?;
         }
 
         wtr.flush()?;
-        println!("✅ CSV exported to: {}", filename);
         Ok(())
     }
 
@@ -56,28 +122,22 @@ impl Reporter {
         filename: &str,
         agents: &[Agent],
         results: &[SimulationResult],
+    ) -> Result<(), Box<dyn Error>> {
+        Self::export_json_to_writer(File::create(filename)?, agents, results)?;
+        println!("✅ JSON personas exported to: {}", filename);
+        Ok(())
+    }
+
+    /// Same document as `export_json`, written to any sink (file, HTTP body, buffer).
+    pub fn export_json_to_writer<W: Write>(
+        mut writer: W,
+        agents: &[Agent],
+        results: &[SimulationResult],
     ) -> Result<(), Box<dyn Error>> {
         let mut personas = Vec::new();
 
         for agent in agents {
-            let agent_results: Vec<_> =
-                results.iter().filter(|r| r.agent_id == agent.id).collect();
-
-            let sentiment_sum: f32 = agent_results
-                .iter()
-                .map(|r| match r.sentiment.as_str() {
-                    "positive" => 1.0,
-                    "negative" => -1.0,
-                    "neutral" => 0.0,
-                    _ => 0.5,
-                })
-                .sum();
-
-            let avg_sentiment = if !agent_results.is_empty() {
-                sentiment_sum / agent_results.len() as f32
-            } else {
-                0.5
-            };
+            let (agent_results, avg_sentiment, avg_sentiment_score) = Self::persona_stats(agent, results);
 
             let persona = serde_json::json!({
                 "id": agent.id,
@@ -90,6 +150,7 @@ impl Reporter {
                 "messaging_resonance": agent.messaging_resonance,
                 "simulated_responses": agent_results.len(),
                 "avg_sentiment": avg_sentiment,
+                "avg_sentiment_score": avg_sentiment_score,
                 "recent_responses": agent_results
                     .iter()
                     .take(5) // Increased history depth
@@ -97,7 +158,9 @@ impl Reporter {
                         "scenario": r.scenario,
                         "response": r.response,
                         "thought_process": r.thought_process, // <--- NEW FIELD
+                        "sources": r.source_refs,
                         "sentiment": r.sentiment,
+                        "sentiment_score": r.sentiment_score,
                         "category": r.category
                     }))
                     .collect::<Vec<_>>()
@@ -113,48 +176,413 @@ impl Reporter {
             "export_timestamp": chrono::Utc::now().to_rfc3339(),
         });
 
+        writer.write_all(serde_json::to_string_pretty(&output)?.as_bytes())?;
+        Ok(())
+    }
+
+    /// Newline-delimited JSON: one compact `SimulationResult` per line (incl. `sources` and
+    /// `thought_process`), ready for `jq` or a BigQuery load job.
+    pub fn export_jsonl(
+        filename: &str,
+        results: &[SimulationResult],
+    ) -> Result<(), Box<dyn Error>> {
+        Self::export_jsonl_to_writer(File::create(filename)?, results)?;
+        println!("✅ JSON Lines exported to: {}", filename);
+        Ok(())
+    }
+
+    /// Same lines as `export_jsonl`, written to any sink (file, HTTP body, buffer).
+    pub fn export_jsonl_to_writer<W: Write>(
+        mut writer: W,
+        results: &[SimulationResult],
+    ) -> Result<(), Box<dyn Error>> {
+        for result in results {
+            serde_json::to_writer(&mut writer, result)?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Spreadsheet export: a "Results" sheet (CSV columns + sources) and a "Personas" sheet
+    /// (JSON export fields). Native cells mean multi-line thoughts survive without an import wizard.
+    pub fn export_xlsx(
+        filename: &str,
+        agents: &[Agent],
+        results: &[SimulationResult],
+    ) -> Result<(), Box<dyn Error>> {
+        let mut workbook = Workbook::new();
+        let header = Format::new().set_bold();
+
+        // --- Sheet 1: Results ---
+        let sheet = workbook.add_worksheet();
+        sheet.set_name("Results")?;
+        let columns = [
+            "agent_id",
+            "agent_role",
+            "agent_demographic",
+            "scenario",
+            "timestamp",
+            "prompt",
+            "response",
+            "thought_process",
+            "sentiment",
+            "sentiment_score",
+            "category",
+            "sources",
+        ];
+        for (col, title) in columns.iter().enumerate() {
+            sheet.write_string_with_format(0, col as u16, *title, &header)?;
+        }
+
+        for (i, result) in results.iter().enumerate() {
+            let row = i as u32 + 1;
+            sheet.write_number(row, 0, result.agent_id)?;
+            sheet.write_string(row, 1, &result.agent_role)?;
+            sheet.write_string(row, 2, &result.agent_demographic)?;
+            sheet.write_string(row, 3, &result.scenario)?;
+            sheet.write_string(row, 4, &result.timestamp)?;
+            sheet.write_string(row, 5, xlsx_cell(&result.prompt))?;
+            sheet.write_string(row, 6, xlsx_cell(&result.response))?;
+            // Optional fields become empty cells; long text is cut to Excel's cell limit
+            sheet.write_string(row, 7, xlsx_cell(result.thought_process.as_deref().unwrap_or("")))?;
+            sheet.write_string(row, 8, &result.sentiment)?;
+            sheet.write_number(row, 9, result.sentiment_score)?;
+            sheet.write_string(row, 10, result.category.as_deref().unwrap_or(""))?;
+            sheet.write_string(row, 11, xlsx_cell(result.sources.as_deref().unwrap_or("")))?;
+        }
+        sheet.set_freeze_panes(1, 0)?;
+        sheet.autofit();
+
+        // --- Sheet 2: Personas ---
+        let sheet = workbook.add_worksheet();
+        sheet.set_name("Personas")?;
+        let columns = [
+            "id",
+            "name",
+            "role",
+            "demographic",
+            "beliefs",
+            "spending_profile",
+            "product_affinity",
+            "messaging_resonance",
+            "simulated_responses",
+            "avg_sentiment",
+            "avg_sentiment_score",
+        ];
+        for (col, title) in columns.iter().enumerate() {
+            sheet.write_string_with_format(0, col as u16, *title, &header)?;
+        }
+
+        for (i, agent) in agents.iter().enumerate() {
+            let row = i as u32 + 1;
+            let (agent_results, avg_sentiment, avg_sentiment_score) = Self::persona_stats(agent, results);
+
+            sheet.write_number(row, 0, agent.id)?;
+            sheet.write_string(row, 1, &agent.name)?;
+            sheet.write_string(row, 2, &agent.role)?;
+            sheet.write_string(row, 3, &agent.demographic)?;
+            sheet.write_string(row, 4, xlsx_cell(&agent.beliefs.join("; ")))?;
+            sheet.write_string(row, 5, &agent.spending_profile)?;
+            sheet.write_string(row, 6, agent.product_affinity.join("; "))?;
+            sheet.write_string(row, 7, agent.messaging_resonance.join("; "))?;
+            sheet.write_number(row, 8, agent_results.len() as u32)?;
+            sheet.write_number(row, 9, avg_sentiment)?;
+            sheet.write_number(row, 10, avg_sentiment_score)?;
+        }
+        sheet.set_freeze_panes(1, 0)?;
+        sheet.autofit();
+
+        workbook.save(filename)?;
+        println!("✅ XLSX exported to: {}", filename);
+        Ok(())
+    }
+
+    // Per-agent results plus (label-based avg sentiment, avg sentiment_score); failed inferences excluded
+    fn persona_stats<'a>(agent: &Agent, results: &'a [SimulationResult]) -> (Vec<&'a SimulationResult>, f32, f32) {
+        let agent_results: Vec<_> =
+            results.iter().filter(|r| r.agent_id == agent.id && !r.failed).collect();
+
+        let sentiment_sum: f32 = agent_results
+            .iter()
+            .map(|r| match r.sentiment.as_str() {
+                "positive" => 1.0,
+                "negative" => -1.0,
+                "neutral" => 0.0,
+                _ => 0.5,
+            })
+            .sum();
+
+        let avg_sentiment = if !agent_results.is_empty() {
+            sentiment_sum / agent_results.len() as f32
+        } else {
+            0.5
+        };
+
+        let avg_sentiment_score = if !agent_results.is_empty() {
+            agent_results.iter().map(|r| r.sentiment_score).sum::<f32>() / agent_results.len() as f32
+        } else {
+            0.0
+        };
+
+        (agent_results, avg_sentiment, avg_sentiment_score)
+    }
+
+    /// Shareable Markdown document (Notion / GitHub wiki): summary table, per-scenario breakdown,
+    /// collapsible transcript and, if provided, the AnalystEngine report.
+    pub fn export_markdown(
+        filename: &str,
+        agents: &[Agent],
+        results: &[SimulationResult],
+        analysis: Option<&str>,
+    ) -> Result<(), Box<dyn Error>> {
+        let counts = SummaryCounts::from_results(results);
+        let mut md = String::new();
+
+        md.push_str("# Oraculum Simulation Report\n\n");
+        md.push_str(&format!("_Exported {}_\n\n", chrono::Utc::now().to_rfc3339()));
+
+        // 1. Summary
+        md.push_str("## Summary\n\n| Metric | Value |\n|---|---|\n");
+        md.push_str(&format!("| Total Agents | {} |\n", agents.len()));
+        md.push_str(&format!("| Total Responses | {} |\n", counts.total));
+        md.push_str(&format!(
+            "| Hidden Thoughts Captured | {} ({:.0}%) |\n",
+            counts.thoughts,
+            counts.percent(counts.thoughts)
+        ));
+        md.push_str(&format!("| Positive | {:.1}% |\n", counts.percent(counts.positive)));
+        md.push_str(&format!("| Negative | {:.1}% |\n", counts.percent(counts.negative)));
+        md.push_str(&format!("| Neutral | {:.1}% |\n", counts.percent(counts.neutral)));
+        md.push_str(&format!("| Mixed | {:.1}% |\n", counts.percent(counts.mixed)));
+        md.push_str(&format!("| Failed Inferences | {} |\n\n", counts.failed));
+
+        // 2. Per-scenario breakdown
+        md.push_str("## By Scenario\n\n| Scenario | Responses | Positive | Avg Sentiment Score |\n|---|---|---|---|\n");
+        for (scenario, count) in &counts.by_scenario {
+            let scenario_results: Vec<_> = results.iter().filter(|r| &r.scenario == scenario && !r.failed).collect();
+            let positive = scenario_results.iter().filter(|r| r.sentiment == "positive").count();
+            let avg_score = scenario_results.iter().map(|r| r.sentiment_score).sum::<f32>() / *count as f32;
+            md.push_str(&format!(
+                "| {} | {} | {:.1}% | {:.3} |\n",
+                md_cell(scenario),
+                count,
+                (positive as f32 / *count as f32) * 100.0,
+                avg_score
+            ));
+        }
+        md.push('\n');
+
+        // 2b. Purchase intent (product launch verdicts, parsed to Yes/No/Maybe)
+        if let Some(verdicts) = VerdictDistribution::from_results(results) {
+            md.push_str("## Purchase Intent\n\n| Product | Yes | No | Maybe | Unclear |\n|---|---|---|---|---|\n");
+            let rows = std::iter::once(("All products", &verdicts))
+                .chain(verdicts.by_product.iter().map(|(product, d)| (product.as_str(), d)));
+            for (label, d) in rows {
+                md.push_str(&format!(
+                    "| {} | {:.1}% ({}) | {:.1}% ({}) | {:.1}% ({}) | {:.1}% ({}) |\n",
+                    md_cell(label),
+                    d.yes_pct, d.yes, d.no_pct, d.no, d.maybe_pct, d.maybe, d.unclear_pct, d.unclear
+                ));
+            }
+            md.push('\n');
+        }
+
+        // 3. Personas
+        md.push_str("## Personas\n\n| Name | Role | Demographic | Responses | Avg Sentiment Score |\n|---|---|---|---|---|\n");
+        for agent in agents {
+            let (agent_results, _, avg_sentiment_score) = Self::persona_stats(agent, results);
+            md.push_str(&format!(
+                "| {} | {} | {} | {} | {:.3} |\n",
+                md_cell(&agent.name),
+                md_cell(&agent.role),
+                md_cell(&agent.demographic),
+                agent_results.len(),
+                avg_sentiment_score
+            ));
+        }
+        md.push('\n');
+
+        // 4. Transcript (collapsed by default - it gets long)
+        md.push_str(&format!("## Transcript\n\n<details>\n<summary>{} responses</summary>\n\n", results.len()));
+        for result in results {
+            let speaker = result.agent_name.as_deref().unwrap_or(&result.agent_role);
+            md.push_str(&format!(
+                "**{}** ({}) — _{}_, {}\n\n> {}\n\n",
+                speaker,
+                result.agent_demographic,
+                result.scenario,
+                if result.failed { "failed" } else { &result.sentiment },
+                result.response.replace('\n', "\n> ")
+            ));
+            if let Some(thought) = &result.thought_process {
+                md.push_str(&format!("Hidden thought: _{}_\n\n", thought.replace('\n', " ")));
+            }
+        }
+        md.push_str("</details>\n");
+
+        // 5. Analyst narrative
+        if let Some(report) = analysis {
+            md.push_str("\n## Analyst Report\n\n");
+            md.push_str(report.trim());
+            md.push('\n');
+        }
+
         let mut file = File::create(filename)?;
-        use std::io::Write;
-        file.write_all(serde_json::to_string_pretty(&output)?.as_bytes())?;
+        file.write_all(md.as_bytes())?;
 
-        println!("✅ JSON personas exported to: {}", filename);
+        println!("✅ Markdown report exported to: {}", filename);
         Ok(())
     }
 
+    #[allow(dead_code)] // Console summary for local runs; export_markdown carries the same counts
     pub fn print_summary(agents: &[Agent], results: &[SimulationResult]) {
+        let counts = SummaryCounts::from_results(results);
+
         println!("\n📊 SIMULATION SUMMARY");
         println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
         println!("Total Agents: {}", agents.len());
-        println!("Total Responses: {}", results.len());
+        println!("Total Responses: {}", counts.total);
+        if counts.failed > 0 {
+            println!("Failed Inferences: {} (excluded from the stats below)", counts.failed);
+        }
 
         // Count hidden thoughts captured
-        let thought_count = results.iter().filter(|r| r.thought_process.is_some()).count();
         println!("Hidden Thoughts Captured: {} (Cognitive Depth: {:.0}%)", 
-            thought_count, 
-            if results.is_empty() { 0.0 } else { (thought_count as f32 / results.len() as f32) * 100.0 }
+            counts.thoughts, 
+            counts.percent(counts.thoughts)
         );
 
-        // Sentiment breakdown
-        let positive = results.iter().filter(|r| r.sentiment == "positive").count();
-        let negative = results.iter().filter(|r| r.sentiment == "negative").count();
-        let neutral = results.iter().filter(|r| r.sentiment == "neutral").count();
-        let mixed = results.iter().filter(|r| r.sentiment == "mixed").count();
+        // Mode collapse check (near-identical answers inflate consensus)
+        let refs: Vec<&SimulationResult> = results.iter().filter(|r| !r.failed).collect();
+        println!("Near-Duplicate Responses: {:.0}%", AgentSwarm::duplicate_ratio(&refs) * 100.0);
 
+        // Sentiment breakdown
         println!("\n📈 Sentiment Distribution:");
-        println!("  Positive: {:.1}%", if results.len() > 0 { (positive as f32 / results.len() as f32) * 100.0 } else { 0.0 });
-        println!("  Negative: {:.1}%", if results.len() > 0 { (negative as f32 / results.len() as f32) * 100.0 } else { 0.0 });
-        println!("  Neutral: {:.1}%", if results.len() > 0 { (neutral as f32 / results.len() as f32) * 100.0 } else { 0.0 });
-        println!("  Mixed: {:.1}%", if results.len() > 0 { (mixed as f32 / results.len() as f32) * 100.0 } else { 0.0 });
+        println!("  Positive: {:.1}%", counts.percent(counts.positive));
+        println!("  Negative: {:.1}%", counts.percent(counts.negative));
+        println!("  Neutral: {:.1}%", counts.percent(counts.neutral));
+        println!("  Mixed: {:.1}%", counts.percent(counts.mixed));
 
         // By scenario
-        let scenarios: std::collections::HashSet<_> =
-            results.iter().map(|r| r.scenario.clone()).collect();
         println!("\n🎯 Response by Scenario:");
-        for scenario in scenarios {
-            let count = results.iter().filter(|r| r.scenario == scenario).count();
+        for (scenario, count) in &counts.by_scenario {
             println!("  {}: {} responses", scenario, count);
         }
 
+        // Would they buy it? (product launch only)
+        if let Some(verdicts) = VerdictDistribution::from_results(results) {
+            println!("\n🛒 Purchase Intent:");
+            println!("  Yes: {:.1}% ({})", verdicts.yes_pct, verdicts.yes);
+            println!("  No: {:.1}% ({})", verdicts.no_pct, verdicts.no);
+            println!("  Maybe: {:.1}% ({})", verdicts.maybe_pct, verdicts.maybe);
+            println!("  Unclear: {:.1}% ({})", verdicts.unclear_pct, verdicts.unclear);
+        }
+
         println!("\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
     }
-}
\ No newline at end of file
+}
+
+// Shared tallies behind print_summary and export_markdown (failed inferences only count in `failed`)
+struct SummaryCounts {
+    total: usize,
+    failed: usize,
+    thoughts: usize,
+    positive: usize,
+    negative: usize,
+    neutral: usize,
+    mixed: usize,
+    by_scenario: Vec<(String, usize)>, // Sorted by scenario name
+}
+
+impl SummaryCounts {
+    fn from_results(results: &[SimulationResult]) -> Self {
+        let failed = results.iter().filter(|r| r.failed).count();
+        let results: Vec<&SimulationResult> = results.iter().filter(|r| !r.failed).collect();
+        let count = |label: &str| results.iter().filter(|r| r.sentiment == label).count();
+
+        let mut by_scenario: std::collections::BTreeMap<String, usize> = std::collections::BTreeMap::new();
+        for result in &results {
+            *by_scenario.entry(result.scenario.clone()).or_insert(0) += 1;
+        }
+
+        Self {
+            total: results.len(),
+            failed,
+            thoughts: results.iter().filter(|r| r.thought_process.is_some()).count(),
+            positive: count("positive"),
+            negative: count("negative"),
+            neutral: count("neutral"),
+            mixed: count("mixed"),
+            by_scenario: by_scenario.into_iter().collect(),
+        }
+    }
+
+    fn percent(&self, count: usize) -> f32 {
+        if self.total > 0 { (count as f32 / self.total as f32) * 100.0 } else { 0.0 }
+    }
+}
+
+// Text that fits an Excel cell: anything over XLSX_MAX_CELL_CHARS is cut and marked with "…"
+fn xlsx_cell(text: &str) -> std::borrow::Cow<'_, str> {
+    match text.char_indices().nth(XLSX_MAX_CELL_CHARS) {
+        Some(_) => {
+            let cut = text.char_indices().nth(XLSX_MAX_CELL_CHARS - 1).map_or(text.len(), |(i, _)| i);
+            format!("{}…", &text[..cut]).into()
+        }
+        None => text.into(),
+    }
+}
+
+// Keeps table cells on one line and stops '|' from breaking the column layout
+fn md_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multi_line_sources_round_trip_through_csv() {
+        let sources = "\n### SENSORY OBSERVATION (Source: DEEP_RESEARCH)\nLine one, with a comma\n\"Quoted\" line two\n";
+        let result = SimulationResult {
+            agent_id: 7,
+            response: "Maybe.".to_string(),
+            sources: Some(sources.to_string()),
+            ..SimulationResult::default()
+        };
+
+        let mut buffer = Vec::new();
+        Reporter::export_csv_to_writer(&mut buffer, &[result]).unwrap();
+
+        let mut reader = csv::Reader::from_reader(buffer.as_slice());
+        let headers = reader.headers().unwrap().clone();
+        let sources_column = headers.iter().position(|h| h == "sources").expect("sources column");
+        let rows: Vec<csv::StringRecord> = reader.records().map(Result::unwrap).collect();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(&rows[0][sources_column], sources);
+        assert_eq!(&rows[0][0], "7");
+    }
+
+    #[test]
+    fn xlsx_cells_are_cut_to_the_excel_limit() {
+        let long = "है".repeat(XLSX_MAX_CELL_CHARS + 10);
+        let cell = xlsx_cell(&long);
+        assert_eq!(cell.chars().count(), XLSX_MAX_CELL_CHARS);
+        assert!(cell.ends_with('…'));
+        assert_eq!(xlsx_cell("short"), "short");
+    }
+
+    #[test]
+    fn export_all_writes_every_format() {
+        let dir = std::env::temp_dir().join(format!("oraculum_export_test_{}", std::process::id()));
+        let result = SimulationResult { response: "x".repeat(XLSX_MAX_CELL_CHARS + 1), ..SimulationResult::default() };
+
+        let paths = Reporter::export_all(&dir, "job-1", &[], &[result]).unwrap();
+        for path in [&paths.csv, &paths.jsonl, &paths.xlsx, &paths.json, &paths.markdown] {
+            assert!(path.is_file(), "{} missing", path.display());
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
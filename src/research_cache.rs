@@ -0,0 +1,162 @@
+// src/research_cache.rs
+// ORACULUM CORE - RESEARCH CACHE
+// Remembers `brain.research` voices + `brain.get_facts` sheets per product/context for a while,
+// so re-running the same product (prompt tuning) skips the Reddit / Wiki / OpenFoodFacts round-trip.
+
+use std::time::{Duration, Instant};
+use dashmap::DashMap;
+
+const DEFAULT_TTL_SECS: u64 = 3600; // 1 hour
+// Distinct products kept at once; the oldest entry makes room beyond this
+const MAX_ENTRIES: usize = 256;
+
+// Names accepted in SimulationRequest::sources
+pub const RESEARCH_SOURCES: [&str; 3] = ["reddit", "wiki", "openfoodfacts"];
+
+/// Which research lookups a job fires. Reddit + Wiki go to `brain.research`, OpenFoodFacts to
+/// `brain.get_facts`; a lookup nobody asked for is skipped (no round-trip).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ResearchSources {
+    pub reddit: bool,
+    pub wiki: bool,
+    pub openfoodfacts: bool,
+}
+
+impl Default for ResearchSources {
+    fn default() -> Self {
+        Self { reddit: true, wiki: true, openfoodfacts: true }
+    }
+}
+
+impl ResearchSources {
+    /// None keeps every source; names are case-insensitive (see RESEARCH_SOURCES).
+    pub fn from_names(names: Option<&[String]>) -> Result<Self, String> {
+        let Some(names) = names else {
+            return Ok(Self::default());
+        };
+        let mut sources = Self { reddit: false, wiki: false, openfoodfacts: false };
+        for name in names {
+            match name.trim().to_lowercase().as_str() {
+                "reddit" => sources.reddit = true,
+                "wiki" => sources.wiki = true,
+                "openfoodfacts" => sources.openfoodfacts = true,
+                other => {
+                    return Err(format!("Unknown research source '{}'. Valid sources: {}", other, RESEARCH_SOURCES.join(", ")))
+                }
+            }
+        }
+        Ok(sources)
+    }
+
+    /// The `brain.research` half (Reddit / Wiki), as sent to the worker.
+    pub fn web(&self) -> Vec<&'static str> {
+        [("reddit", self.reddit), ("wiki", self.wiki)]
+            .into_iter()
+            .filter_map(|(name, on)| on.then_some(name))
+            .collect()
+    }
+
+    // Part of the cache key: a Reddit-only run must not be served an all-sources entry
+    fn tag(&self) -> String {
+        format!("{}{}{}", self.reddit as u8, self.wiki as u8, self.openfoodfacts as u8)
+    }
+}
+
+#[derive(Clone)]
+pub struct CachedResearch {
+    pub voices: Vec<String>,
+    pub fact_sheet: String,
+}
+
+pub struct ResearchCache {
+    ttl: Duration,
+    entries: DashMap<String, (Instant, CachedResearch)>,
+}
+
+impl ResearchCache {
+    /// Reads `ORACULUM_RESEARCH_CACHE_SECS` (0 disables caching).
+    pub fn from_env() -> Self {
+        let secs = std::env::var("ORACULUM_RESEARCH_CACHE_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_TTL_SECS);
+
+        Self {
+            ttl: Duration::from_secs(secs),
+            entries: DashMap::new(),
+        }
+    }
+
+    // "  Maggi  Noodles " and "maggi noodles" are the same research question
+    fn key(product: &str, context: &str, sources: &ResearchSources) -> String {
+        let normalize = |s: &str| s.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+        format!("{}|{}|{}", normalize(product), normalize(context), sources.tag())
+    }
+
+    pub fn get(&self, product: &str, context: &str, sources: &ResearchSources) -> Option<CachedResearch> {
+        let key = Self::key(product, context, sources);
+
+        let fresh = self.entries.get(&key).and_then(|entry| {
+            let (stored_at, research) = entry.value();
+            (stored_at.elapsed() < self.ttl).then(|| research.clone())
+        });
+
+        if fresh.is_none() {
+            self.entries.remove(&key); // Expired (or never there)
+        }
+        fresh
+    }
+
+    pub fn insert(&self, product: &str, context: &str, sources: &ResearchSources, research: CachedResearch) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        // Don't pin a failed lookup for an hour
+        if research.voices.is_empty() && research.fact_sheet.is_empty() {
+            return;
+        }
+        // Entries nobody asks for again would otherwise sit here until restart
+        self.entries.retain(|_, (stored_at, _)| stored_at.elapsed() < self.ttl);
+        let key = Self::key(product, context, sources);
+        if self.entries.len() >= MAX_ENTRIES && !self.entries.contains_key(&key) {
+            let oldest = self.entries.iter().min_by_key(|entry| entry.value().0).map(|entry| entry.key().clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(key, (Instant::now(), research));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn research(voice: &str) -> CachedResearch {
+        CachedResearch { voices: vec![voice.to_string()], fact_sheet: String::new() }
+    }
+
+    #[test]
+    fn inserting_sweeps_expired_entries() {
+        let cache = ResearchCache { ttl: Duration::from_millis(20), entries: DashMap::new() };
+        let sources = ResearchSources::default();
+        cache.insert("Maggi", "", &sources, research("old"));
+        std::thread::sleep(Duration::from_millis(30));
+
+        cache.insert("Yippee", "", &sources, research("new"));
+        assert_eq!(cache.entries.len(), 1);
+        assert!(cache.get("  yippee ", "", &sources).is_some());
+    }
+
+    #[test]
+    fn the_oldest_entry_makes_room_at_the_cap() {
+        let cache = ResearchCache { ttl: Duration::from_secs(60), entries: DashMap::new() };
+        let sources = ResearchSources::default();
+        for i in 0..=MAX_ENTRIES {
+            cache.insert(&format!("Product {}", i), "", &sources, research("voice"));
+        }
+        assert_eq!(cache.entries.len(), MAX_ENTRIES);
+        assert!(cache.get("Product 0", "", &sources).is_none());
+        assert!(cache.get(&format!("Product {}", MAX_ENTRIES), "", &sources).is_some());
+    }
+}
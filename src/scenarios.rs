@@ -3,7 +3,11 @@
 // Implements Chain-of-Thought (CoT) & Phi-3 Chat Formats
 // UPGRADE: Contextual Tasking, Dynamic Personality, Memory Graph Injection & Robust Parsing
 
-use crate::agent_swarm::Agent;
+use std::collections::HashMap;
+use serde::Deserialize;
+use crate::agent_swarm::{Agent, AgentSwarm};
+use crate::api::SimulationRequest;
+use crate::parsing::{parse_cognitive_response, parse_purchase_intent, safe_slice, PurchaseIntent, ResponseTags};
 
 /// The Core Trait for all simulation types.
 /// Must be Sync + Send for parallel execution on M4 chips.
@@ -12,48 +16,50 @@ pub trait Scenario: Sync + Send {
     fn scenario_key(&self) -> &str;
     fn generate_prompt(&self, agent: &Agent, custom_context: Option<&str>) -> String;
     
+    // Chain-of-Thought markers this scenario's prompt asks the model to emit.
+    // Override when a prompt uses different (e.g. localized) tags.
+    fn response_tags(&self) -> ResponseTags {
+        ResponseTags::default()
+    }
+
+    // Token budget for one agent's reply (thinking + verdict).
+    // Override for scenarios whose answers are short (CX Flow) or long.
+    fn max_response_tokens(&self) -> usize {
+        800
+    }
+
+    // Tags + one sample answer shown under "MANDATORY RESPONSE FORMAT" (see `response_format`).
+    // Shown for EVERY product, so keep it product-agnostic: no brands, claims or categories.
+    fn format_example(&self) -> &str {
+        "[Thinking]\n(Your private reasoning, in your own voice)\n[Verdict]\n(Your final answer)"
+    }
+
+    // The closing block of every prompt: format spec + example, then the "Response:" cue
+    // (LocalizedScenario inserts its language instruction right before that cue).
+    fn response_format(&self) -> String {
+        format!(
+            "MANDATORY RESPONSE FORMAT (Strictly follow this):\n{}\n\nResponse:<|end|>\n<|assistant|>",
+            self.format_example().trim_end()
+        )
+    }
+
+    // Bucket for the (already parsed) public response, shown as `category` in results/exports.
+    // Scenarios own their buckets; the default is the generic keyword table.
+    fn categorize(&self, response: &str) -> Option<String> {
+        AgentSwarm::extract_category(response, self.scenario_key())
+    }
+
+    // Normalized Yes/No/Maybe for scenarios whose verdict is a purchase decision
+    // (rolled up as JobStatus `verdicts`). None: this scenario doesn't ask for one.
+    fn purchase_intent(&self, _response: &str) -> Option<PurchaseIntent> {
+        None
+    }
+
     // NEW: Robust Response Parser (Default Implementation)
-    // Extracts [Verdict] and [Thinking] tags to keep CSVs clean.
-    // UPDATE: Now automatically strips hallucinated artifacts like "---" or "ROOM CONTEXT"
+    // Extracts the verdict/action and thinking sections to keep CSVs clean.
     fn process_response(&self, raw: &str) -> (String, Option<String>) {
-        let raw_clean = raw.trim();
-        
-        // Helper to clean artifacts
-        let clean = |text: &str| -> String {
-            text.replace("---", "")
-                .replace("ROOM CONTEXT", "")
-                .trim()
-                .to_string()
-        };
-        
-        if let Some(verdict_idx) = raw_clean.find("[Verdict]") {
-            // Extract the final spoken verdict
-            let verdict = clean(&raw_clean[verdict_idx + 9..]);
-            
-            // Extract the hidden thought process
-            // We strictly use the start of [Verdict] as the end of [Thinking]
-            let thought = if let Some(think_idx) = raw_clean.find("[Thinking]") {
-                Some(clean(&raw_clean[think_idx + 10..verdict_idx]))
-            } else {
-                None
-            };
-            
-            return (verdict, thought);
-
-        } else if let Some(action_idx) = raw_clean.find("[Action]") {
-            // For CX Flow scenarios that use [Action] instead of [Verdict]
-            let action = clean(&raw_clean[action_idx + 8..]);
-
-            let thought = if let Some(think_idx) = raw_clean.find("[Thinking]") {
-                Some(clean(&raw_clean[think_idx + 10..action_idx]))
-            } else {
-                None
-            };
-            return (action, thought);
-        }
-        
-        // Fallback: Return whole string if tags are missing (Graceful Fail)
-        (clean(raw_clean), None)
+        let (thought, answer) = parse_cognitive_response(raw, &self.response_tags());
+        (answer, thought)
     }
 }
 
@@ -80,6 +86,27 @@ impl ProductLaunchScenario {
 impl Scenario for ProductLaunchScenario {
     fn name(&self) -> &str { "Product Launch Test" }
     fn scenario_key(&self) -> &str { "product_launch" }
+    fn format_example(&self) -> &str {
+        "[Thinking]\nThe claims sound good, but I need to know if they hold up. The price is okay, but...\n[Verdict]\nI might buy it once to try, but only if it's easy to find where I already shop."
+    }
+
+    fn categorize(&self, response: &str) -> Option<String> {
+        let category = if response.contains("buy") || response.contains("purchase") {
+            "intent_to_buy"
+        } else if response.contains("healthy") || response.contains("quality") {
+            "quality_focused"
+        } else if response.contains("price") || response.contains("cost") {
+            "price_sensitive"
+        } else {
+            "intrigued"
+        };
+        Some(category.to_string())
+    }
+
+    // The prompt asks "Would you buy it? (Yes/No/Maybe)"
+    fn purchase_intent(&self, response: &str) -> Option<PurchaseIntent> {
+        parse_purchase_intent(response)
+    }
 
     fn generate_prompt(&self, agent: &Agent, _custom_context: Option<&str>) -> String {
         let full_context = self.context_docs.join("\n");
@@ -119,13 +146,7 @@ impl Scenario for ProductLaunchScenario {
             1. [Thinking]: First, analyze if this fits your budget and daily routine. BE CRITICAL. {}\n\
             2. [Verdict]: Give your final answer. Would you buy it? (Yes/No/Maybe) and why. Use your defined speaking style ('{}').\n\
             \
-            MANDATORY RESPONSE FORMAT (Strictly follow this):\n\
-            [Thinking]\n\
-            I need to verify if the protein claim is real. 80 rupees is okay, but...\n\
-            [Verdict]\n\
-            I might buy it once to try, but only if it's on Zepto.\n\
-            \n\
-            Response:<|end|>\n<|assistant|>",
+            {}",
             agent.name, agent.role, 
             agent.demographic, 
             agent.speaking_style, agent.skepticism_level,
@@ -136,7 +157,8 @@ impl Scenario for ProductLaunchScenario {
             full_context, // Fallback injection
             task_directive,
             tone_instruction,
-            agent.speaking_style
+            agent.speaking_style,
+            self.response_format()
         )
     }
 }
@@ -146,6 +168,7 @@ impl Scenario for ProductLaunchScenario {
 // Goal: Determine Emotional Resonance & Clarity of Ad Copy
 // =========================================================================
 pub struct CreativeTestScenario {
+    #[allow(dead_code)]
     pub tagline_a: String, // Kept for compatibility but usually parsed from product_context
     pub product_context: String,
     pub campaign_goal: String,
@@ -164,6 +187,21 @@ impl CreativeTestScenario {
 impl Scenario for CreativeTestScenario {
     fn name(&self) -> &str { "Creative Testing" }
     fn scenario_key(&self) -> &str { "creative_test" }
+    fn format_example(&self) -> &str {
+        "[Thinking]\nThis concept sounds too corporate. It doesn't hit my pain point about time.\n[Verdict]\nLow resonance. It feels fake."
+    }
+
+    fn categorize(&self, response: &str) -> Option<String> {
+        let response = response.to_lowercase();
+        let category = if response.contains("second") {
+            "option_b_preference"
+        } else if response.contains("first") {
+            "option_a_preference"
+        } else {
+            "unclear_preference"
+        };
+        Some(category.to_string())
+    }
 
     fn generate_prompt(&self, agent: &Agent, _custom_context: Option<&str>) -> String {
         format!(
@@ -182,19 +220,14 @@ impl Scenario for CreativeTestScenario {
             1. [Thinking]: Compare the concept to your values. Which feels more authentic? Which feels 'salesy'?\n\
             2. [Verdict]: State clearly if this resonates (High/Medium/Low) and why. Speak in your natural voice.\n\
             \
-            MANDATORY RESPONSE FORMAT:\n\
-            [Thinking]\n\
-            This concept sounds too corporate. It doesn't hit my pain point about time.\n\
-            [Verdict]\n\
-            Low resonance. It feels fake.\n\
-            \n\
-            Response:<|end|>\n<|assistant|>",
+            {}",
             agent.name, agent.role, agent.demographic,
             agent.speaking_style, agent.skepticism_level,
             self.campaign_goal, // Memory Injection
             self.campaign_goal,
             self.product_context,
-            agent.beliefs.join(", ")
+            agent.beliefs.join(", "),
+            self.response_format()
         )
     }
 }
@@ -220,6 +253,22 @@ impl CXFlowScenario {
 impl Scenario for CXFlowScenario {
     fn name(&self) -> &str { "Customer Journey Flow" }
     fn scenario_key(&self) -> &str { "cx_flow" }
+    fn format_example(&self) -> &str {
+        "[Thinking]\nThe delivery fee is too high. I can buy this locally.\n[Action]\nI'm closing the app. Not worth it today."
+    }
+    // One quick reaction + one action: no room for essays
+    fn max_response_tokens(&self) -> usize { 400 }
+
+    fn categorize(&self, response: &str) -> Option<String> {
+        let category = if response.contains("buy") || response.contains("cart") {
+            "converted"
+        } else if response.contains("consider") || response.contains("check") {
+            "considering"
+        } else {
+            "aware"
+        };
+        Some(category.to_string())
+    }
 
     fn generate_prompt(&self, agent: &Agent, _custom_context: Option<&str>) -> String {
         // Dynamic prompt based on funnel stage
@@ -246,19 +295,14 @@ impl Scenario for CXFlowScenario {
             1. [Thinking]: specific to your spending profile ({}), how do you react?\n\
             2. [Action]: What do you actually do next? Use your defined speaking style.\n\
             \
-            MANDATORY RESPONSE FORMAT:\n\
-            [Thinking]\n\
-            The delivery fee is too high. I can buy this locally.\n\
-            [Action]\n\
-            I'm closing the app. Not worth it today.\n\
-            \n\
-            Response:<|end|>\n<|assistant|>",
+            {}",
             agent.name, agent.role, agent.demographic,
             agent.speaking_style, agent.skepticism_level,
             self.stage, // Memory Injection
             stage_context,
             self.product_info,
-            agent.spending_profile
+            agent.spending_profile,
+            self.response_format()
         )
     }
 }
@@ -286,6 +330,20 @@ impl ABMessagingScenario {
 impl Scenario for ABMessagingScenario {
     fn name(&self) -> &str { "A/B Messaging Strategy" }
     fn scenario_key(&self) -> &str { "ab_messaging" }
+    fn format_example(&self) -> &str {
+        "[Thinking]\nThis appeals to my need for status, but ignores price.\n[Verdict]\n7/10. It sounds premium but expensive."
+    }
+
+    fn categorize(&self, response: &str) -> Option<String> {
+        let category = if response.contains("affordable") || response.contains("value") {
+            "value_resonance"
+        } else if response.contains("premium") || response.contains("indulgent") {
+            "premium_resonance"
+        } else {
+            "neutral_resonance"
+        };
+        Some(category.to_string())
+    }
 
     fn generate_prompt(&self, agent: &Agent, _custom_context: Option<&str>) -> String {
         format!(
@@ -305,58 +363,590 @@ impl Scenario for ABMessagingScenario {
             1. [Thinking]: Evaluate which benefit matters more to you personally (e.g. price vs health vs status).\n\
             2. [Verdict]: Rate persuasiveness (1-10) and explain why. Use your voice.\n\
             \
-            MANDATORY RESPONSE FORMAT:\n\
-            [Thinking]\n\
-            This appeals to my need for status, but ignores price.\n\
-            [Verdict]\n\
-            7/10. It sounds premium but expensive.\n\
-            \n\
-            Response:<|end|>\n<|assistant|>",
+            {}",
             agent.name, agent.role, agent.demographic,
             agent.speaking_style, agent.skepticism_level,
             self.product_name, // Memory Injection
             self.product_name,
             self.strategy_name,
-            self.context
+            self.context,
+            self.response_format()
         )
     }
 }
 
 // =========================================================================
-// FACTORY FUNCTIONS
-// Used by CLI and API to construct scenarios dynamically
+// 5. PRICE SENSITIVITY SCENARIO (Van Westendorp)
+// Goal: Find the acceptable price range via the four classic PSM questions
 // =========================================================================
+pub struct PriceSensitivityScenario {
+    pub product_name: String,
+    pub context: String,
+    pub price_anchor: Option<f32>, // Intended retail price in ₹ (optional)
+}
+
+impl PriceSensitivityScenario {
+    pub fn new(product_name: &str, context: &str, price_anchor: Option<f32>) -> Self {
+        Self {
+            product_name: product_name.to_string(),
+            context: context.to_string(),
+            price_anchor,
+        }
+    }
+}
+
+impl Scenario for PriceSensitivityScenario {
+    fn name(&self) -> &str { "Price Sensitivity (Van Westendorp)" }
+    fn scenario_key(&self) -> &str { "price_sensitivity" }
+    fn format_example(&self) -> &str {
+        "[Thinking]\nI usually pay about ₹60 for something like this at the kirana store.\n[Verdict]\nTOO CHEAP: ₹20\nBARGAIN: ₹45\nEXPENSIVE: ₹90\nTOO EXPENSIVE: ₹130\nAbove ₹90 I'd rather stick with my usual brand."
+    }
+    // Four amounts and a one-line reason
+    fn max_response_tokens(&self) -> usize { 500 }
+
+    // Bucket by where the tested price lands in the agent's own thresholds
+    fn categorize(&self, response: &str) -> Option<String> {
+        let category = match PricePoints::from_response(response) {
+            Some((points, Some(anchor))) => {
+                if anchor <= points.bargain {
+                    "price_attractive"
+                } else if anchor <= points.expensive {
+                    "price_acceptable"
+                } else if anchor <= points.too_expensive {
+                    "price_stretch"
+                } else {
+                    "price_rejected"
+                }
+            }
+            Some((_, None)) => "price_points_captured",
+            None => "price_unclear",
+        };
+        Some(category.to_string())
+    }
+
+    fn generate_prompt(&self, agent: &Agent, _custom_context: Option<&str>) -> String {
+        let anchor_line = match self.price_anchor {
+            Some(price) => format!("The brand is considering a price of around ₹{:.0}.\n", price),
+            None => String::new(),
+        };
+
+        format!(
+            "<|user|>You are {}, a {}. ({})\n\
+            PERSONALITY: Style: '{}', Skepticism: '{}'.\n\
+            SPENDING PROFILE: {}\n\n\
+            --- ROOM CONTEXT ---\n\
+            Topic: {} (Pricing)\n\
+            \
+            PRODUCT: {}\n\
+            {}\
+            CONTEXT:\n{}\n\
+            \
+            TASK: Answer the four pricing questions from YOUR budget and habits, in rupees.\n\
+            1. TOO CHEAP: At what price would it be so cheap that you'd doubt its quality?\n\
+            2. BARGAIN: At what price is it a great deal you'd happily buy?\n\
+            3. EXPENSIVE: At what price does it start feeling expensive, but you'd still consider it?\n\
+            4. TOO EXPENSIVE: At what price would you never buy it?\n\
+            \
+            INSTRUCTIONS:\n\
+            1. [Thinking]: Compare with what you pay today for similar products.\n\
+            2. [Verdict]: Give exactly four rupee amounts in the order above, then one line on why.\n\
+            \
+            {}",
+            agent.name, agent.role, agent.demographic,
+            agent.speaking_style, agent.skepticism_level,
+            agent.spending_profile,
+            self.product_name, // Memory Injection
+            self.product_name,
+            anchor_line,
+            self.context,
+            self.response_format()
+        )
+    }
+
+    // Normalizes the four answers into a machine-readable "PRICE POINTS:" line
+    // (kept above the original verdict) so categorization and the analyst can read them back.
+    fn process_response(&self, raw: &str) -> (String, Option<String>) {
+        let (thought, verdict) = parse_cognitive_response(raw, &self.response_tags());
+
+        match PricePoints::parse(&verdict) {
+            Some(points) => {
+                let line = points.to_line(self.price_anchor);
+                (format!("{}\n{}", line, verdict), thought)
+            }
+            None => (verdict, thought),
+        }
+    }
+}
+
+// The four Van Westendorp answers for one agent (₹)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PricePoints {
+    pub too_cheap: f32,
+    pub bargain: f32,
+    pub expensive: f32,
+    pub too_expensive: f32,
+}
+
+impl PricePoints {
+    const LINE_PREFIX: &'static str = "PRICE POINTS:";
+
+    /// Parses free text. Only rupee-marked amounts count (₹80, Rs. 80, INR 80, 80 rupees, 80/-),
+    /// so list numbering ("1.") and quantities ("200g") are never read as prices. Needs at least
+    /// four amounts, taken in question order. Out-of-order answers are sorted, since the four
+    /// thresholds must be ascending.
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut values = rupee_amounts(text);
+
+        if values.len() < 4 {
+            return None;
+        }
+        values.truncate(4);
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+        Some(Self {
+            too_cheap: values[0],
+            bargain: values[1],
+            expensive: values[2],
+            too_expensive: values[3],
+        })
+    }
+
+    pub fn to_line(self, anchor: Option<f32>) -> String {
+        let mut line = format!(
+            "{} too_cheap=₹{:.0} | bargain=₹{:.0} | expensive=₹{:.0} | too_expensive=₹{:.0}",
+            Self::LINE_PREFIX, self.too_cheap, self.bargain, self.expensive, self.too_expensive
+        );
+        if let Some(anchor) = anchor {
+            line.push_str(&format!(" | anchor=₹{:.0}", anchor));
+        }
+        line
+    }
+
+    /// Reads back the normalized line written by `PriceSensitivityScenario::process_response`.
+    /// Returns the points plus the tested anchor price (if one was set).
+    pub fn from_response(response: &str) -> Option<(Self, Option<f32>)> {
+        let line = response.lines().find(|l| l.trim_start().starts_with(Self::LINE_PREFIX))?;
 
-pub fn get_scenario(scenario_type: &str) -> Option<Box<dyn Scenario>> {
-    match scenario_type {
-        "product_launch" => Some(Box::new(ProductLaunchScenario::new(
-            "Mondelez Plant-Based Bites",
-            "Healthy Snacking",
-            vec!["High protein", "No artificial flavors", "10-min Blinkit delivery"],
-        ))),
-        "creative_test" => Some(Box::new(CreativeTestScenario::new(
-            "Snack Smart, Live Better",
-            "Target Audience: Gen Z",
-            "Launch Campaign",
-        ))),
-        "cx_flow" => Some(Box::new(CXFlowScenario::new(
-            "awareness",
-            "New premium snacking line launched on Zepto",
-        ))),
-        "ab_messaging" => Some(Box::new(ABMessagingScenario::new(
-            "Mondelez Bites",
-            "Affordable vs Premium Positioning",
-            "Value Strategy",
-        ))),
-        _ => None,
+        let field = |name: &str| -> Option<f32> {
+            line.split('|')
+                .map(str::trim)
+                .find_map(|part| part.trim_start_matches(Self::LINE_PREFIX).trim().strip_prefix(name))
+                .and_then(|v| v.trim_start_matches('=').trim_start_matches('₹').trim().parse().ok())
+        };
+
+        let points = Self {
+            too_cheap: field("too_cheap")?,
+            bargain: field("bargain")?,
+            expensive: field("expensive")?,
+            too_expensive: field("too_expensive")?,
+        };
+        Some((points, field("anchor")))
     }
 }
 
-pub fn list_available_scenarios() -> Vec<&'static str> {
-    vec![
-        "product_launch",
-        "creative_test",
-        "cx_flow",
-        "ab_messaging",
-    ]
-}
\ No newline at end of file
+// Every number in the text that is marked as a rupee amount, in order.
+fn rupee_amounts(text: &str) -> Vec<f32> {
+    let lower = text.to_lowercase();
+    let chars: Vec<(usize, char)> = lower.char_indices().collect();
+    let mut amounts = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        if !chars[i].1.is_ascii_digit() {
+            i += 1;
+            continue;
+        }
+
+        let start = chars[i].0;
+        let mut digits = String::new();
+        while i < chars.len() && (chars[i].1.is_ascii_digit() || chars[i].1 == ',' || chars[i].1 == '.') {
+            if chars[i].1 != ',' {
+                digits.push(chars[i].1);
+            }
+            i += 1;
+        }
+        let end = if i < chars.len() { chars[i].0 } else { lower.len() };
+
+        let digits = digits.trim_end_matches('.');
+        if let Ok(value) = digits.parse::<f32>() {
+            let before = lower[..start].trim_end();
+            let after = lower[end..].trim_start();
+            let marked = before.ends_with('₹')
+                || before.ends_with("rs")
+                || before.ends_with("rs.")
+                || before.ends_with("inr")
+                || after.starts_with("rupee")
+                || after.starts_with("/-");
+            if marked {
+                amounts.push(value);
+            }
+        }
+    }
+
+    amounts
+}
+
+// =========================================================================
+// 6. TEMPLATE SCENARIO (User-defined)
+// Goal: Let the frontend define a scenario without a Rust rebuild
+// =========================================================================
+// `custom_template` is either a raw prompt string, or a JSON object:
+//   { "prompt": "You are {name}, a {role}... {product} ... {context}", "verdict_tags": ["Thinking", "Verdict"] }
+// The LAST tag is the public response; earlier tags are captured as the hidden thought process.
+#[derive(Clone)]
+pub struct TemplateScenario {
+    pub template: String,
+    pub verdict_tags: Vec<String>,
+    format_example: String, // "[Tag]\n...\n" per verdict tag, built once in `parse`
+    pub product_name: String,
+    pub context: String,
+}
+
+#[derive(Deserialize)]
+struct TemplateSpec {
+    prompt: String,
+    #[serde(default)]
+    verdict_tags: Vec<String>,
+}
+
+impl TemplateScenario {
+    pub const PLACEHOLDERS: [&'static str; 6] = ["name", "role", "demographic", "product", "context", "beliefs"];
+    pub const REQUIRED_PLACEHOLDERS: [&'static str; 3] = ["name", "product", "context"];
+
+    /// Parses and validates a template. Errors are human-readable (surfaced as a 400).
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let spec = spec.trim();
+        let (template, verdict_tags) = if spec.starts_with('{') && spec.contains("\"prompt\"") {
+            let parsed: TemplateSpec = serde_json::from_str(spec)
+                .map_err(|e| format!("custom_template is not valid JSON: {}", e))?;
+            (parsed.prompt, parsed.verdict_tags)
+        } else {
+            (spec.to_string(), Vec::new())
+        };
+
+        if template.trim().is_empty() {
+            return Err("custom_template prompt is empty".to_string());
+        }
+
+        // Every {placeholder} must be one we know how to fill...
+        for placeholder in Self::placeholders_in(&template) {
+            if !Self::PLACEHOLDERS.contains(&placeholder.as_str()) {
+                return Err(format!(
+                    "custom_template uses unknown placeholder {{{}}} (allowed: {})",
+                    placeholder,
+                    Self::PLACEHOLDERS.map(|p| format!("{{{}}}", p)).join(", ")
+                ));
+            }
+        }
+
+        // ...and the ones that make it a simulation must be present
+        let missing: Vec<String> = Self::REQUIRED_PLACEHOLDERS
+            .iter()
+            .filter(|p| !template.contains(&format!("{{{}}}", p)))
+            .map(|p| format!("{{{}}}", p))
+            .collect();
+        if !missing.is_empty() {
+            return Err(format!("custom_template is missing required placeholder(s): {}", missing.join(", ")));
+        }
+
+        let verdict_tags: Vec<String> = verdict_tags
+            .into_iter()
+            .map(|t| t.trim().trim_start_matches('[').trim_end_matches(']').to_string())
+            .filter(|t| !t.is_empty())
+            .collect();
+
+        let verdict_tags = if verdict_tags.is_empty() {
+            vec!["Thinking".to_string(), "Verdict".to_string()]
+        } else {
+            verdict_tags
+        };
+        Ok(Self {
+            template,
+            format_example: verdict_tags.iter().map(|t| format!("[{}]\n...\n", t)).collect(),
+            verdict_tags,
+            product_name: String::new(),
+            context: String::new(),
+        })
+    }
+
+    /// Binds the job-specific product and (enriched) context.
+    pub fn with_context(mut self, product_name: &str, context: &str) -> Self {
+        self.product_name = product_name.to_string();
+        self.context = context.to_string();
+        self
+    }
+
+    // Names of all `{word}` tokens in the template (JSON-ish braces like `{ "a": 1 }` are ignored)
+    fn placeholders_in(template: &str) -> Vec<String> {
+        let mut found = Vec::new();
+        let mut rest = template;
+        while let Some(open) = rest.find('{') {
+            rest = &rest[open + 1..];
+            if let Some(close) = rest.find('}') {
+                let inner = &rest[..close];
+                if !inner.is_empty() && inner.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                    found.push(inner.to_string());
+                }
+            }
+        }
+        found
+    }
+}
+
+impl Scenario for TemplateScenario {
+    fn name(&self) -> &str { "Custom Template" }
+    fn scenario_key(&self) -> &str { "custom" }
+    fn format_example(&self) -> &str { &self.format_example }
+
+    fn generate_prompt(&self, agent: &Agent, _custom_context: Option<&str>) -> String {
+        let body = self.template
+            .replace("{name}", &agent.name)
+            .replace("{role}", &agent.role)
+            .replace("{demographic}", &agent.demographic)
+            .replace("{beliefs}", &agent.beliefs.join(", "))
+            .replace("{product}", &self.product_name)
+            .replace("{context}", &self.context);
+
+        // Templates written in plain prose still need the chat markers the Brain expects
+        if body.contains("<|user|>") {
+            body
+        } else {
+            format!("<|user|>{}\n\n{}", body, self.response_format())
+        }
+    }
+
+    fn process_response(&self, raw: &str) -> (String, Option<String>) {
+        let raw_clean = raw.trim();
+
+        // Locate every configured tag, then slice the text between consecutive tags
+        let mut positions: Vec<(usize, &str)> = self.verdict_tags
+            .iter()
+            .filter_map(|tag| raw_clean.find(&format!("[{}]", tag)).map(|idx| (idx, tag.as_str())))
+            .collect();
+        positions.sort_by_key(|(idx, _)| *idx);
+
+        // Model ignored the format (Graceful Fail): fall back to the shared parser
+        let fallback = || {
+            let (thought, answer) = parse_cognitive_response(raw, &ResponseTags::default());
+            (answer, thought)
+        };
+        let public_tag = match self.verdict_tags.last() {
+            Some(tag) => tag.as_str(),
+            None => return fallback(),
+        };
+        if !positions.iter().any(|(_, tag)| *tag == public_tag) {
+            return fallback();
+        }
+
+        let mut response = String::new();
+        let mut thoughts = Vec::new();
+        for (i, (idx, tag)) in positions.iter().enumerate() {
+            let start = idx + tag.len() + 2;
+            let end = positions.get(i + 1).map(|(next, _)| *next).unwrap_or(raw_clean.len());
+            let section = safe_slice(raw_clean, start, end).trim().to_string();
+
+            if *tag == public_tag {
+                response = section;
+            } else if !section.is_empty() {
+                thoughts.push(format!("{}: {}", tag, section));
+            }
+        }
+
+        let thought = if thoughts.is_empty() { None } else { Some(thoughts.join("\n")) };
+        (response, thought)
+    }
+}
+
+// =========================================================================
+// 7. LOCALIZED SCENARIO (Wrapper)
+// Goal: Run any single-turn scenario in the audience's language (SimulationRequest::language)
+// =========================================================================
+// Wraps every parallel scenario (built-ins and custom templates). Focus groups are not
+// localized. Markers stay in English so `parse_cognitive_response` still finds the sections;
+// only the text between them (and the example lines' language) changes.
+pub struct LocalizedScenario {
+    pub inner: Box<dyn Scenario>,
+    pub language: String,
+}
+
+impl LocalizedScenario {
+    /// `None` for English (or a blank language), since the prompts are already English.
+    pub fn wrap(inner: Box<dyn Scenario>, language: Option<&str>) -> Box<dyn Scenario> {
+        match language.map(str::trim).filter(|l| !is_english(l)) {
+            Some(language) => Box::new(Self { inner, language: language.to_string() }),
+            None => inner,
+        }
+    }
+
+    fn instruction(&self) -> String {
+        let tags = self.inner.response_tags();
+        let markers = [Some(tags.thought), Some(tags.verdict), tags.action]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" / ");
+        format!(
+            "LANGUAGE: Respond in {lang}. Keep the {markers} markers exactly as written (in English), \
+            but write every line under them in {lang}. The example lines above only show the format; \
+            your answer must be your own words in {lang}.\n",
+            lang = self.language,
+            markers = markers
+        )
+    }
+}
+
+/// True for "English" / "en" / "en-IN" / "" (case-insensitive).
+pub fn is_english(language: &str) -> bool {
+    let lower = language.trim().to_lowercase();
+    lower.is_empty() || lower == "english" || lower == "en" || lower.starts_with("en-") || lower.starts_with("en_")
+}
+
+/// Puts `block` right before a prompt's final "Response:" cue (or `<|end|>`), so it's read last;
+/// appended when the prompt has neither.
+pub fn insert_before_response_cue(prompt: String, block: &str) -> String {
+    match prompt.rfind("Response:<|end|>").or_else(|| prompt.rfind("<|end|>")) {
+        Some(idx) => format!("{}{}{}", &prompt[..idx], block, &prompt[idx..]),
+        None => format!("{}\n{}", prompt, block),
+    }
+}
+
+impl Scenario for LocalizedScenario {
+    fn name(&self) -> &str { self.inner.name() }
+    fn scenario_key(&self) -> &str { self.inner.scenario_key() }
+
+    // The instruction goes right before the final "Response:" cue, so it is the last thing read
+    fn generate_prompt(&self, agent: &Agent, custom_context: Option<&str>) -> String {
+        let prompt = self.inner.generate_prompt(agent, custom_context);
+        insert_before_response_cue(prompt, &self.instruction())
+    }
+
+    fn response_tags(&self) -> ResponseTags { self.inner.response_tags() }
+    fn format_example(&self) -> &str { self.inner.format_example() }
+    fn max_response_tokens(&self) -> usize { self.inner.max_response_tokens() }
+    fn categorize(&self, response: &str) -> Option<String> { self.inner.categorize(response) }
+    fn purchase_intent(&self, response: &str) -> Option<PurchaseIntent> { self.inner.purchase_intent(response) }
+    fn process_response(&self, raw: &str) -> (String, Option<String>) { self.inner.process_response(raw) }
+}
+
+// =========================================================================
+// SCENARIO REGISTRY
+// Maps a scenario key (e.g. "product_launch") to a factory that builds it from the
+// API request + the enriched research context. Mirrors `SkillRegistry`.
+// =========================================================================
+
+pub type ScenarioFactory = Box<dyn Fn(&SimulationRequest, &str) -> Box<dyn Scenario> + Send + Sync>;
+
+pub struct ScenarioRegistry {
+    factories: HashMap<String, ScenarioFactory>,
+}
+
+impl ScenarioRegistry {
+    pub fn new() -> Self {
+        Self { factories: HashMap::new() }
+    }
+
+    /// Registry pre-loaded with the built-in scenarios.
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+
+        registry.register("product_launch", |req, context| Box::new(ProductLaunchScenario::new(
+            &req.product_name.primary(),
+            "Consumer Product",
+            vec![context],
+        )));
+        registry.register("creative_test", |req, context| Box::new(CreativeTestScenario::new(
+            &req.product_name.primary(),
+            context,
+            "Ad Campaign",
+        )));
+        registry.register("ab_messaging", |req, context| Box::new(ABMessagingScenario::new(
+            &req.product_name.primary(),
+            context,
+            "Brand Positioning",
+        )));
+        registry.register("cx_flow", |req, context| Box::new(CXFlowScenario::new(
+            "consideration",
+            &format!("{} - {}", req.product_name.primary(), context),
+        )));
+        registry.register("price_sensitivity", |req, context| Box::new(PriceSensitivityScenario::new(
+            &req.product_name.primary(),
+            context,
+            req.price_anchor,
+        )));
+
+        registry
+    }
+
+    pub fn register<F>(&mut self, key: &str, factory: F)
+    where
+        F: Fn(&SimulationRequest, &str) -> Box<dyn Scenario> + Send + Sync + 'static,
+    {
+        self.factories.insert(key.to_string(), Box::new(factory));
+    }
+
+    pub fn create(&self, key: &str, req: &SimulationRequest, enriched_context: &str) -> Option<Box<dyn Scenario>> {
+        self.factories.get(key).map(|factory| factory(req, enriched_context))
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.factories.contains_key(key)
+    }
+
+    /// Sorted so API consumers get a stable order.
+    pub fn list_available(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.factories.keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+
+    /// (key, display name) for every registered scenario, sorted by key.
+    /// Names come from `Scenario::name()` on an instance built from an empty request.
+    pub fn describe(&self) -> Vec<(String, String)> {
+        let placeholder = SimulationRequest::default();
+        self.list_available()
+            .into_iter()
+            .filter_map(|key| {
+                let name = self.create(&key, &placeholder, "")?.name().to_string();
+                Some((key, name))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn price_points_ignore_list_numbering_and_quantities() {
+        let verdict = "1. ₹40\n2. Rs. 60\n3. 90 rupees\n4. INR 150\nFor a 200g pack that's my limit.";
+        let points = PricePoints::parse(verdict).expect("four rupee amounts");
+        assert_eq!(points, PricePoints { too_cheap: 40.0, bargain: 60.0, expensive: 90.0, too_expensive: 150.0 });
+    }
+
+    #[test]
+    fn price_points_need_four_rupee_amounts() {
+        assert_eq!(PricePoints::parse("1. 40\n2. 60\n3. 90\n4. 150"), None);
+        assert_eq!(PricePoints::parse("₹40, ₹60 and ₹90, maybe 150"), None);
+    }
+
+    #[test]
+    fn prompts_only_mention_the_product_under_test() {
+        let registry = ScenarioRegistry::with_builtins();
+        let req = SimulationRequest {
+            product_name: crate::api::ProductNames::One("Herbal Shampoo".to_string()),
+            ..SimulationRequest::default()
+        };
+        let agent = Agent::new(1, "Teacher");
+
+        for key in registry.list_available() {
+            let scenario = registry.create(&key, &req, "Sulphate-free, Rs 299 for 180ml").unwrap();
+            let prompt = scenario.generate_prompt(&agent, None).to_lowercase();
+
+            assert!(prompt.contains("sulphate-free"), "{} prompt lost the product context", key);
+            for leak in ["protein", "snack", "oats", "maggi", "sugar"] {
+                assert!(!prompt.contains(leak), "{} prompt mentions '{}'", key, leak);
+            }
+        }
+    }
+}
@@ -1,6 +1,9 @@
 // src/scout.rs
+// Standalone research entry points; jobs go through api::gather_research (cached, per-source).
+#![allow(dead_code)]
 use std::sync::Arc;
 use crate::brain::AgentBrain;
+use crate::research_cache::ResearchSources;
 
 pub struct MarketScout;
 
@@ -17,7 +20,7 @@ impl MarketScout {
         
         // We call the 'research' method on the brain, which sends the {"task": "research"} JSON command.
         // The Python bridge handles the Searching, Reading, and Synthesizing.
-        let voices = brain.research(product, context);
+        let voices = brain.research(product, context, &ResearchSources::default().web());
         
         // Handle Empty/Alert states
         if voices.is_empty() {
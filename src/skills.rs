@@ -5,9 +5,15 @@
 
 use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::sync::{mpsc, Arc, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
 use crate::brain::AgentBrain;
 use crate::systems::sensory::SensoryCortex;
+use crate::wiki::WikiScout;
+use std::net::Ipv4Addr;
+use url::{Host, Url};
+use dashmap::DashMap;
 
 // 1. The Standard Input/Output for any Skill
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,7 +26,24 @@ pub struct SkillInput {
 pub struct SkillOutput {
     pub success: bool,
     pub data: String,
-    pub metadata: String, // JSON string for citations/sources
+    pub metadata: SkillMetadata, // Citations/sources
+}
+
+/// Where a skill's output came from. Serialized by serde, so quotes in skill data
+/// can never corrupt it (the old hand-formatted JSON string could).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SkillMetadata {
+    pub source: String, // Empty when the skill found nothing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hits: Option<usize>,
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    pub extra: serde_json::Value, // Skill-specific details (e.g. the crawled URL)
+}
+
+impl SkillMetadata {
+    pub fn from_source(source: &str) -> Self {
+        Self { source: source.to_string(), ..Self::default() }
+    }
 }
 
 // 2. The Skill Trait
@@ -28,6 +51,35 @@ pub trait AgentSkill: Send + Sync {
     fn name(&self) -> String;
     fn description(&self) -> String;
     fn execute(&self, brain: &Arc<AgentBrain>, input: SkillInput) -> SkillOutput;
+
+    /// Whether identical calls within a job may share one result (see SkillCache).
+    /// Skills whose output must be fresh on every call should return false.
+    fn cacheable(&self) -> bool { true }
+}
+
+// 2b. Per-job Skill Cache
+// Every agent in a job runs its skills on the same product query, so without this
+// 30 agents mean 30 identical memory queries. Keyed by (skill_id, query); the input
+// context (demographic) is not part of the key, as no skill reads it today.
+pub struct SkillCache {
+    entries: DashMap<(String, String), Arc<OnceLock<SkillOutput>>>,
+}
+
+impl SkillCache {
+    pub fn new() -> Self {
+        Self { entries: DashMap::new() }
+    }
+
+    /// Returns the cached output for this call, running `run` only if it is the first one.
+    /// Concurrent callers with the same key wait for that first run instead of repeating it.
+    pub fn get_or_run(&self, skill_id: &str, query: &str, run: impl FnOnce() -> SkillOutput) -> SkillOutput {
+        // Clone the cell out so the map shard isn't locked while the skill runs
+        let cell = self.entries
+            .entry((skill_id.to_string(), query.to_string()))
+            .or_default()
+            .clone();
+        cell.get_or_init(run).clone()
+    }
 }
 
 // 3. The Registry (Singleton)
@@ -46,7 +98,7 @@ impl SkillRegistry {
         registry.register(Box::new(FactCheckSkill));
         
         // NEW: Register the Autonomous Web Agent
-        registry.register(Box::new(WebScout)); 
+        registry.register(Box::new(WebScout { cortex: SensoryCortex::from_env() }));
         
         registry
     }
@@ -55,13 +107,74 @@ impl SkillRegistry {
         self.skills.insert(skill.name(), skill);
     }
 
-    pub fn get(&self, name: &str) -> Option<&Box<dyn AgentSkill>> {
-        self.skills.get(name)
+    pub fn get(&self, name: &str) -> Option<&dyn AgentSkill> {
+        self.skills.get(name).map(|skill| skill.as_ref())
     }
     
     pub fn list_available(&self) -> Vec<String> {
         self.skills.keys().cloned().collect()
     }
+
+    /// Runs several skills concurrently (one thread each) and returns their outputs in the
+    /// order of `skill_ids`. Skills still running after `timeout` are reported as failed;
+    /// their threads are left to finish in the background so a slow crawl can't stall the agent.
+    /// Cacheable skills go through `cache`, so identical calls across agents run once.
+    /// Each output comes with its wall-clock time (`timeout` for skills that timed out).
+    pub fn execute_all(
+        brain: &Arc<AgentBrain>,
+        skill_ids: &[String],
+        input: &SkillInput,
+        timeout: Duration,
+        cache: &Arc<SkillCache>,
+    ) -> Vec<(String, SkillOutput, Duration)> {
+        let (tx, rx) = mpsc::channel();
+
+        for (idx, skill_id) in skill_ids.iter().enumerate() {
+            let (tx, brain, input, skill_id, cache) = (tx.clone(), brain.clone(), input.clone(), skill_id.clone(), cache.clone());
+            thread::spawn(move || {
+                let started = Instant::now();
+                // Skills are Box<dyn>, so each thread resolves its own (the registry is cheap to build)
+                let registry = SkillRegistry::new();
+                let output = registry.get(&skill_id).map(|skill| {
+                    if skill.cacheable() {
+                        let query = input.query.clone();
+                        cache.get_or_run(&skill_id, &query, || skill.execute(&brain, input))
+                    } else {
+                        skill.execute(&brain, input)
+                    }
+                });
+                let _ = tx.send((idx, output, started.elapsed()));
+            });
+        }
+        drop(tx);
+
+        let deadline = Instant::now() + timeout;
+        let mut slots: Vec<Option<(Option<SkillOutput>, Duration)>> = vec![None; skill_ids.len()];
+        while slots.iter().any(Option::is_none) {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match rx.recv_timeout(remaining) {
+                Ok((idx, output, elapsed)) => slots[idx] = Some((output, elapsed)),
+                Err(_) => break, // Timed out (or every sender is gone)
+            }
+        }
+
+        skill_ids
+            .iter()
+            .zip(slots)
+            .filter_map(|(skill_id, slot)| match slot {
+                Some((Some(output), elapsed)) => Some((skill_id.clone(), output, elapsed)),
+                Some((None, _)) => None, // Unknown skill id: silently skipped (as before)
+                None => {
+                    println!("[WARN] Skill {} timed out after {:?}", skill_id, timeout);
+                    Some((skill_id.clone(), SkillOutput {
+                        success: false,
+                        data: "Skill timed out.".to_string(),
+                        metadata: SkillMetadata::default(),
+                    }, timeout))
+                }
+            })
+            .collect()
+    }
 }
 
 // --- CORE SKILL IMPLEMENTATIONS ---
@@ -76,13 +189,13 @@ impl AgentSkill for DeepResearchSkill {
         let results = brain.query_memory(&input.query);
         
         if results.is_empty() {
-            SkillOutput { success: false, data: "No data found.".to_string(), metadata: "{}".to_string() }
+            SkillOutput { success: false, data: "No data found.".to_string(), metadata: SkillMetadata::default() }
         } else {
             let combined_data = results.join("\n\n");
             SkillOutput { 
                 success: true, 
                 data: combined_data, 
-                metadata: format!("{{\"source\": \"HybridMemory\", \"hits\": {}}}", results.len()) 
+                metadata: SkillMetadata { hits: Some(results.len()), ..SkillMetadata::from_source("HybridMemory") }
             }
         }
     }
@@ -92,22 +205,40 @@ impl AgentSkill for DeepResearchSkill {
 struct FactCheckSkill;
 impl AgentSkill for FactCheckSkill {
     fn name(&self) -> String { "fact_check".to_string() }
-    fn description(&self) -> String { "Verifies product specs via OpenFoodFacts".to_string() }
+    fn description(&self) -> String { "Verifies product specs via OpenFoodFacts, with Wikipedia as fallback".to_string() }
     
     fn execute(&self, brain: &Arc<AgentBrain>, input: SkillInput) -> SkillOutput {
+        let mut sections = Vec::new();
+        let mut sources = Vec::new();
+
         let facts = brain.get_facts(&input.query);
-        
-        if facts.is_empty() || facts.contains("No structured data") {
-             SkillOutput { 
+        if !facts.trim().is_empty() && !facts.contains("No structured data") && !facts.contains("SYSTEM_ALERT") {
+            sections.push(facts);
+            sources.push("OpenFoodFacts");
+        }
+
+        // Fallback: non-food products (or OpenFoodFacts misses) still get encyclopedia grounding
+        if sources.is_empty() {
+            if let Some(summary) = WikiScout::fetch_summary(&input.query, brain) {
+                sections.push(format!("Background (Wikipedia): {}", summary));
+                sources.push("Wikipedia");
+            }
+        }
+
+        if sections.is_empty() {
+            SkillOutput { 
                 success: false, 
                 data: "Facts unavailable".to_string(), 
-                metadata: "{}".to_string() 
+                metadata: SkillMetadata::default() 
             }
         } else {
             SkillOutput { 
                 success: true, 
-                data: facts, 
-                metadata: "{\"source\": \"OpenFoodFacts\"}".to_string() 
+                data: sections.join("\n\n"), 
+                metadata: SkillMetadata {
+                    extra: serde_json::json!({ "sources": sources }),
+                    ..SkillMetadata::from_source(&sources.join(" + "))
+                }
             }
         }
     }
@@ -115,31 +246,188 @@ impl AgentSkill for FactCheckSkill {
 
 // Skill 3: Web Scout (Sensory Cortex / Crawl4AI)
 // Connects to: crate::systems::sensory -> Python API (Port 8000)
-struct WebScout;
+struct WebScout {
+    cortex: SensoryCortex,
+}
+
+// Used only when the query names no site of its own (override: ORACULUM_WEBSCOUT_DEFAULT_URL)
+const DEFAULT_SCOUT_URL: &str = "https://scrapeme.live/shop";
+
+impl WebScout {
+    /// First http(s) URL mentioned in the query, e.g. "check https://example.com/p" -> that URL.
+    fn target_from_query(query: &str) -> Option<Url> {
+        query
+            .split_whitespace()
+            .map(|token| token.trim_matches(|c: char| matches!(c, '"' | '\'' | '(' | ')' | '<' | '>' | ',' | ';' | '.')))
+            .filter_map(|token| Url::parse(token).ok())
+            .find(Self::is_browsable)
+    }
+
+    // Public http(s) sites only: the query comes from API callers, so loopback, private,
+    // link-local (cloud metadata) and unspecified hosts would let them crawl our own network.
+    // Names that resolve to such addresses are not caught here.
+    fn is_browsable(url: &Url) -> bool {
+        if !matches!(url.scheme(), "http" | "https") {
+            return false;
+        }
+        match url.host() {
+            Some(Host::Domain(domain)) => {
+                let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+                domain != "localhost" && !domain.ends_with(".localhost")
+            }
+            Some(Host::Ipv4(ip)) => is_public_ipv4(ip),
+            Some(Host::Ipv6(ip)) => match ip.to_ipv4_mapped() {
+                Some(mapped) => is_public_ipv4(mapped),
+                None => {
+                    let first = ip.segments()[0];
+                    !(ip.is_loopback()
+                        || ip.is_unspecified()
+                        || (first & 0xfe00) == 0xfc00 // Unique local fc00::/7
+                        || (first & 0xffc0) == 0xfe80) // Link-local fe80::/10
+                }
+            },
+            None => false,
+        }
+    }
+
+    fn default_target() -> Option<Url> {
+        let configured = std::env::var("ORACULUM_WEBSCOUT_DEFAULT_URL").unwrap_or_else(|_| DEFAULT_SCOUT_URL.to_string());
+        // Operator-configured, so an intranet default is allowed
+        Url::parse(&configured).ok().filter(|url| matches!(url.scheme(), "http" | "https") && url.host().is_some())
+    }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    !(ip.is_loopback() || ip.is_private() || ip.is_link_local() || ip.is_unspecified() || ip.is_broadcast())
+}
+
 impl AgentSkill for WebScout {
     fn name(&self) -> String { "web_scout".to_string() }
     fn description(&self) -> String { "Autonomous Web Agent (Crawl4AI + Qwen) that browses live sites".to_string() }
 
     fn execute(&self, _brain: &Arc<AgentBrain>, input: SkillInput) -> SkillOutput {
-        // Identify Target
-        // For this scenario, we default to the shop, but you could parse input.query for a URL
-        let target_url = "https://scrapeme.live/shop";
+        // Identify Target: a URL in the query wins, otherwise the configured default
+        let target_url = match Self::target_from_query(&input.query).or_else(Self::default_target) {
+            Some(url) => url,
+            None => {
+                return SkillOutput {
+                    success: false,
+                    data: "No valid http(s) target URL for WebScout.".to_string(),
+                    metadata: SkillMetadata::default()
+                };
+            }
+        };
 
         println!("[SKILL] WebScout engaged. Target: {}", target_url);
 
         // Call the Sensory Cortex (Python)
-        if let Some(knowledge) = SensoryCortex::perceive(target_url, &input.query) {
-             SkillOutput {
+        match self.cortex.perceive(target_url.as_str(), &input.query) {
+            Ok(knowledge) => SkillOutput {
                 success: true,
                 data: knowledge,
-                metadata: "{\"source\": \"SensoryCortex/Crawl4AI\"}".to_string()
+                metadata: SkillMetadata {
+                    extra: serde_json::json!({ "url": target_url.as_str() }),
+                    ..SkillMetadata::from_source("SensoryCortex/Crawl4AI")
+                }
+            },
+            Err(e) => {
+                println!("[ERROR] {}", e);
+                if let Some(hints) = e.troubleshooting() {
+                    println!("{}", hints);
+                }
+                SkillOutput {
+                    success: false,
+                    data: "Sensory Cortex failed to retrieve data.".to_string(),
+                    metadata: SkillMetadata::default()
+                }
             }
-        } else {
-            SkillOutput {
-                success: false,
-                data: "Sensory Cortex failed to retrieve data.".to_string(),
-                metadata: "{}".to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    // One-shot stand-in for the Python cortex: answers a single /perceive call and hands back its JSON body
+    fn fake_cortex() -> (String, thread::JoinHandle<serde_json::Value>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut raw = Vec::new();
+            let mut buf = [0u8; 4096];
+            let body_start = loop {
+                let n = stream.read(&mut buf).unwrap();
+                raw.extend_from_slice(&buf[..n]);
+                if let Some(pos) = raw.windows(4).position(|w| w == b"\r\n\r\n") {
+                    break pos + 4;
+                }
+            };
+            let headers = String::from_utf8_lossy(&raw[..body_start]).to_lowercase();
+            let length: usize = headers
+                .lines()
+                .find_map(|line| line.strip_prefix("content-length:"))
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(0);
+            while raw.len() < body_start + length {
+                let n = stream.read(&mut buf).unwrap();
+                raw.extend_from_slice(&buf[..n]);
             }
+
+            let reply = r#"{"knowledge":"Rs 99 on the product page"}"#;
+            write!(stream, "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}", reply.len(), reply).unwrap();
+            serde_json::from_slice(&raw[body_start..body_start + length]).unwrap()
+        });
+        (base_url, handle)
+    }
+
+    #[test]
+    fn web_scout_sends_the_url_from_the_query_to_the_cortex() {
+        let (base_url, cortex) = fake_cortex();
+        let scout = WebScout { cortex: SensoryCortex { base_url, timeout: Duration::from_secs(5), attempts: 1 } };
+
+        let input = SkillInput { query: "check https://example.com/p".to_string(), context: String::new() };
+        let output = scout.execute(&Arc::new(AgentBrain::demo()), input);
+
+        let request = cortex.join().unwrap();
+        assert_eq!(request["url"], "https://example.com/p");
+        assert!(output.success);
+        assert_eq!(output.data, "Rs 99 on the product page");
+    }
+
+    #[test]
+    fn web_scout_ignores_non_http_urls() {
+        assert!(WebScout::target_from_query("see ftp://example.com/p").is_none());
+        assert_eq!(
+            WebScout::target_from_query("compare (https://a.com/x), then https://b.com").map(String::from),
+            Some("https://a.com/x".to_string())
+        );
+    }
+
+    #[test]
+    fn web_scout_never_targets_internal_hosts() {
+        for url in [
+            "http://127.0.0.1:8003/generate",
+            "http://localhost:8000/perceive",
+            "http://api.localhost/",
+            "http://169.254.169.254/latest/meta-data/",
+            "http://10.0.0.5/admin",
+            "http://192.168.1.1/",
+            "http://172.16.0.1/",
+            "http://0.0.0.0:8000/",
+            "http://2130706433/", // 127.0.0.1 as a single number
+            "http://[::1]:8000/",
+            "http://[::ffff:127.0.0.1]/",
+            "http://[fd00::1]/",
+            "http://[fe80::1]/",
+        ] {
+            assert!(!WebScout::is_browsable(&Url::parse(url).unwrap()), "{} was browsable", url);
         }
+        assert!(WebScout::is_browsable(&Url::parse("https://example.com/p").unwrap()));
+        assert!(WebScout::is_browsable(&Url::parse("http://93.184.216.34/").unwrap()));
+        assert_eq!(WebScout::target_from_query("see http://127.0.0.1:8003/ and https://b.com").map(String::from), Some("https://b.com/".to_string()));
     }
-}
\ No newline at end of file
+}
@@ -3,8 +3,16 @@
 // Connects the Rust Core to the Python Sensory Agent (Microservice)
 
 use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::time::Duration;
 
+const DEFAULT_CORTEX_URL: &str = "http://127.0.0.1:8000";
+// Crawling a real webpage takes time
+const DEFAULT_CORTEX_TIMEOUT_SECS: u64 = 60;
+// Smooths over the uvicorn startup race without masking a real outage
+const DEFAULT_CORTEX_ATTEMPTS: u32 = 3;
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
 // 1. The Data We Send to Python
 #[derive(Serialize)]
 struct CortexRequest {
@@ -18,53 +26,109 @@ struct CortexResponse {
     knowledge: String,
 }
 
-// 3. The Public Interface
-// This acts as a static helper class that 'skills.rs' can call directly.
-pub struct SensoryCortex;
+// 3. What can go wrong (so callers can tell "not running" from "running but broken")
+#[derive(Debug)]
+pub enum CortexError {
+    Unreachable(String),   // Connection refused / timed out
+    BadStatus(u16),        // Cortex answered with a non-2xx status
+    BadResponse(String),   // 2xx, but the body wasn't the JSON we expect
+}
+
+impl fmt::Display for CortexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CortexError::Unreachable(e) => write!(f, "Sensory Cortex unreachable: {}", e),
+            CortexError::BadStatus(code) => write!(f, "Sensory Cortex returned error status: {}", code),
+            CortexError::BadResponse(e) => write!(f, "Sensory Cortex response was not valid JSON: {}", e),
+        }
+    }
+}
+
+impl CortexError {
+    /// Operator hints for the most common failure (Python server not running).
+    pub fn troubleshooting(&self) -> Option<String> {
+        match self {
+            CortexError::Unreachable(_) => Some(format!(
+                "1. Is the 'sensory_cortex' terminal open?\n\
+                2. Did you run 'python3 -m uvicorn main:app --reload --port 8000'?\n\
+                3. Is ORACULUM_CORTEX_URL pointing at it? (current: {})",
+                std::env::var("ORACULUM_CORTEX_URL").unwrap_or_else(|_| DEFAULT_CORTEX_URL.to_string())
+            )),
+            _ => None,
+        }
+    }
+}
+
+// 4. The Public Interface
+pub struct SensoryCortex {
+    pub base_url: String,
+    pub timeout: Duration,
+    pub attempts: u32, // Tries per perceive() on connection errors / timeouts (>= 1)
+}
 
 impl SensoryCortex {
-    /// Calls the local Python Microservice (FastAPI) to browse the web.
+    /// Reads `ORACULUM_CORTEX_URL`, `ORACULUM_CORTEX_TIMEOUT_SECS` and `ORACULUM_CORTEX_ATTEMPTS`.
+    pub fn from_env() -> Self {
+        let base_url = std::env::var("ORACULUM_CORTEX_URL").unwrap_or_else(|_| DEFAULT_CORTEX_URL.to_string());
+        let secs = std::env::var("ORACULUM_CORTEX_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_CORTEX_TIMEOUT_SECS);
+        let attempts = std::env::var("ORACULUM_CORTEX_ATTEMPTS")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(DEFAULT_CORTEX_ATTEMPTS);
+
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            timeout: Duration::from_secs(secs.max(1)),
+            attempts: attempts.max(1),
+        }
+    }
+
+    /// Calls the Python Microservice (FastAPI) to browse the web.
     /// This is a BLOCKING call, designed to run inside the Rayon thread pool
     /// managed by 'main.rs'.
-    pub fn perceive(url: &str, query: &str) -> Option<String> {
+    pub fn perceive(&self, url: &str, query: &str) -> Result<String, CortexError> {
         // Log the attempt
-        println!("[SENSORY] Contacting Cortex (Python 3.11) for target: {}", url);
+        println!("[SENSORY] Contacting Cortex at {} for target: {}", self.base_url, url);
 
         let client = reqwest::blocking::Client::new();
-        
-        // We set a 60s timeout because crawling a real webpage takes time.
-        // We use '127.0.0.1:8000' because that's where uvicorn is running.
-        let response = client.post("http://127.0.0.1:8000/perceive")
-            .json(&CortexRequest {
-                url: url.to_string(),
-                query: query.to_string(),
-            })
-            .timeout(Duration::from_secs(60)) 
-            .send();
-
-        match response {
-            Ok(resp) => {
-                if resp.status().is_success() {
-                    // Success: Parse the JSON knowledge
-                    if let Ok(json) = resp.json::<CortexResponse>() {
-                        println!("[SENSORY] Success! Received {} chars of knowledge.", json.knowledge.len());
-                        return Some(json.knowledge);
-                    } else {
-                        println!("[ERROR] Sensory Cortex response was not valid JSON.");
-                    }
-                } else {
-                    println!("[ERROR] Sensory Cortex returned error status: {}", resp.status());
+        let request = CortexRequest {
+            url: url.to_string(),
+            query: query.to_string(),
+        };
+
+        // Retry only when the Cortex couldn't be reached (connect error / timeout), with
+        // exponential backoff. An HTTP error status is a real answer and is not retried.
+        let mut backoff = INITIAL_BACKOFF;
+        let mut attempt = 1;
+        let resp = loop {
+            match client.post(format!("{}/perceive", self.base_url)).json(&request).timeout(self.timeout).send() {
+                Ok(resp) => break resp,
+                Err(e) if (e.is_connect() || e.is_timeout()) && attempt < self.attempts => {
+                    println!(
+                        "[SENSORY] Attempt {}/{} failed ({}). Retrying in {:?}...",
+                        attempt, self.attempts, e, backoff
+                    );
+                    std::thread::sleep(backoff);
+                    backoff *= 2;
+                    attempt += 1;
                 }
-                None
-            },
-            Err(e) => {
-                // This is the most common error (Python server not running)
-                println!("\n[CRITICAL ERROR] Sensory Cortex Unreachable!");
-                println!("1. Is the 'oraculum_sensory_agent' terminal open?");
-                println!("2. Did you run 'python -m uvicorn oraculum_server:app --reload'?");
-                println!("Details: {}\n", e);
-                None
+                Err(e) => return Err(CortexError::Unreachable(format!("{} (after {} attempt(s))", e, attempt))),
             }
+        };
+
+        if !resp.status().is_success() {
+            return Err(CortexError::BadStatus(resp.status().as_u16()));
         }
+
+        // Success: Parse the JSON knowledge
+        let json = resp
+            .json::<CortexResponse>()
+            .map_err(|e| CortexError::BadResponse(e.to_string()))?;
+
+        println!("[SENSORY] Success! Received {} chars of knowledge.", json.knowledge.len());
+        Ok(json.knowledge)
     }
-}
\ No newline at end of file
+}
@@ -1,7 +1,7 @@
 // src/wiki.rs
 // THE KNOWLEDGE CODEX
 // Fetches factual ground truth to prevent hallucination.
-// UPGRADE: Delegates to Python Fact Agent (Wiki + Web Fallback)
+// Delegates to the Python worker's Wikipedia lookup (/get_wiki)
 
 use std::sync::Arc;
 use crate::brain::AgentBrain;
@@ -9,20 +9,19 @@ use crate::brain::AgentBrain;
 pub struct WikiScout;
 
 impl WikiScout {
-    /// Main Entry Point: Asks the Brain to find concrete specs.
+    /// Main Entry Point: Asks the Brain for the encyclopedia background of a product/brand.
     pub fn fetch_summary(query: &str, brain: &Arc<AgentBrain>) -> Option<String> {
-        println!("📚 WIKI: Consulting the Codex (Fact Agent) for '{}'...", query);
+        println!("📚 WIKI: Consulting the Codex for '{}'...", query);
         
-        // Delegate to Python Fact Agent
-        // This avoids the "Entity not found" issue by allowing fallback to general web search
-        let fact_sheet = brain.get_facts(query);
+        // The worker relaxes the query ("Maggi Masala 70g" -> "Maggi") before giving up
+        let summary = brain.get_wiki_summary(query);
         
-        if fact_sheet.contains("SYSTEM_ALERT") || fact_sheet.trim().is_empty() {
-            println!("   ⚠️ WIKI: No concrete facts found. Using generic fallback.");
+        if summary.contains("SYSTEM_ALERT") || summary.trim().is_empty() {
+            println!("   ⚠️ WIKI: No encyclopedia entry found.");
             return None;
         }
 
-        println!("   -> Fact Sheet Acquired ({} chars)", fact_sheet.len());
-        Some(fact_sheet)
+        println!("   -> Summary Acquired ({} chars)", summary.len());
+        Some(summary)
     }
 }
\ No newline at end of file